@@ -14,8 +14,10 @@
 
 //! Operations on the off-chain testing environment.
 
+/// Exported so that tests outside of this crate can implement the bound of
+/// [`register_chain_extension`].
 #[cfg(feature = "ink-unstable-chain-extensions")]
-use super::chain_extension::ChainExtension;
+pub use super::chain_extension::ChainExtension;
 pub use super::{
     db::ChainSpec,
     CallData,
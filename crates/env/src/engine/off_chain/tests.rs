@@ -90,3 +90,33 @@ fn gas_price() -> crate::Result<()> {
         Ok(())
     })
 }
+
+#[test]
+#[cfg(feature = "ink-unstable-chain-extensions")]
+fn registered_chain_extension_is_called() -> Result<()> {
+    use crate::test::ChainExtension;
+
+    /// Doubles its input.
+    struct MockDoubler;
+
+    impl ChainExtension for MockDoubler {
+        type Input = u32;
+        type Output = u32;
+
+        fn func_id(&self) -> u32 {
+            42
+        }
+
+        fn call(&mut self, input: &u32) -> Result<u32> {
+            Ok(input * 2)
+        }
+    }
+
+    crate::test::run_test::<crate::DefaultEnvironment, _>(|_| {
+        crate::test::register_chain_extension(MockDoubler);
+        assert_eq!(crate::call_chain_extension::<u32, u32>(42, &21), Ok(42));
+        // Unregistered function IDs are rejected.
+        assert!(crate::call_chain_extension::<u32, u32>(43, &21).is_err());
+        Ok(())
+    })
+}
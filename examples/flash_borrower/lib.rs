@@ -0,0 +1,144 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Flash Borrower
+//!
+//! Receives flash loans from a uniswap pair.
+//!
+//! ## Warning
+//!
+//! This contract is an *example*. It is neither audited nor endorsed for production use.
+//! Do **not** rely on it to keep anything of value secure.
+//!
+//! ## Overview
+//!
+//! A pair lending through `flash_loan` sends the tokens to the borrower and calls
+//! its `FlashBorrower::on_flash_loan` message. Before that call returns, the
+//! borrower has to send the loan plus the fee back to the pair, which checks its
+//! balance afterwards and reverts the whole loan if it has not been repaid.
+//!
+//! This borrower only accepts loans from a single lender and repays them right
+//! away. A real borrower would put the tokens to use in between.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::flash_borrower::{
+    FlashBorrower,
+    SimpleBorrower,
+};
+use ink_lang as ink;
+
+#[ink::contract]
+pub mod flash_borrower {
+    use erc20::{
+        BaseErc20,
+        Erc20,
+    };
+    use ink_env::call::FromAccountId;
+    use ink_prelude::vec::Vec;
+
+    /// Trait implemented by contracts receiving flash loans.
+    #[ink::trait_definition]
+    pub trait FlashBorrower {
+        /// Receives a flash loan of `amount` of `token` initiated by `initiator`.
+        ///
+        /// The loan plus `fee` has to be sent back to the caller before
+        /// returning. `data` is passed through from the initiator unchanged.
+        ///
+        /// Returns whether the loan was accepted.
+        #[ink(message)]
+        fn on_flash_loan(
+            &mut self,
+            initiator: AccountId,
+            token: AccountId,
+            amount: Balance,
+            fee: Balance,
+            data: Vec<u8>,
+        ) -> bool;
+    }
+
+    /// A borrower that immediately repays loans from `lender`.
+    #[ink(storage)]
+    pub struct SimpleBorrower {
+        /// The only account loans are accepted from.
+        lender: AccountId,
+    }
+
+    impl SimpleBorrower {
+        /// Creates a borrower accepting loans from `lender`.
+        #[ink(constructor)]
+        pub fn new(lender: AccountId) -> Self {
+            Self { lender }
+        }
+
+        /// Returns the only account loans are accepted from.
+        #[ink(message)]
+        pub fn lender(&self) -> AccountId {
+            self.lender
+        }
+    }
+
+    impl FlashBorrower for SimpleBorrower {
+        /// Repays `amount` plus `fee` of `token` to the lender.
+        ///
+        /// Rejects loans from any other account, since they could make the
+        /// borrower pay fees it never agreed to.
+        #[ink(message)]
+        fn on_flash_loan(
+            &mut self,
+            _initiator: AccountId,
+            token: AccountId,
+            amount: Balance,
+            fee: Balance,
+            _data: Vec<u8>,
+        ) -> bool {
+            if self.env().caller() != self.lender {
+                return false
+            }
+            let mut token: Erc20 = FromAccountId::from_account_id(token);
+            token.transfer(self.lender, amount + fee).is_ok()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        #[ink::test]
+        fn new_works() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let borrower = SimpleBorrower::new(accounts.bob);
+            assert_eq!(borrower.lender(), accounts.bob);
+        }
+
+        #[ink::test]
+        fn loans_from_other_accounts_are_rejected() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            // Alice calls, but only Bob may lend.
+            let mut borrower = SimpleBorrower::new(accounts.bob);
+            assert!(!borrower.on_flash_loan(
+                accounts.alice,
+                accounts.django,
+                1_000,
+                3,
+                Vec::new(),
+            ));
+        }
+    }
+}
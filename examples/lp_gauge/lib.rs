@@ -0,0 +1,185 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # LP Gauge
+//!
+//! Follows the LP token balances of a uniswap pair through its transfer hook.
+//!
+//! ## Warning
+//!
+//! This contract is an *example*. It is neither audited nor endorsed for production use.
+//! Do **not** rely on it to keep anything of value secure.
+//!
+//! ## Overview
+//!
+//! A pair with a transfer hook calls its `LpTransferHook::on_lp_transfer`
+//! message after every LP token transfer, mint and burn. The pair ignores a
+//! failing hook apart from emitting `HookFailed`, so a hook can neither block
+//! nor revert LP token movements.
+//!
+//! This gauge only mirrors the balances and counts the checkpoints of every
+//! account. A real gauge would settle the rewards of both accounts at each
+//! checkpoint, without the LPs having to call it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::lp_gauge::{
+    LpGauge,
+    LpTransferHook,
+};
+use ink_lang as ink;
+
+#[ink::contract]
+pub mod lp_gauge {
+    use ink_storage::collections::HashMap as StorageHashMap;
+
+    /// Trait implemented by contracts notified of LP token movements.
+    #[ink::trait_definition]
+    pub trait LpTransferHook {
+        /// Receives the movement of `amount` LP tokens from `from` to `to`.
+        ///
+        /// `from` is `None` for mints and `to` is `None` for burns. The balances
+        /// of the pair have already been updated when this is called.
+        #[ink(message)]
+        fn on_lp_transfer(
+            &mut self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            amount: Balance,
+        );
+    }
+
+    /// A gauge mirroring the LP token balances of `pair`.
+    #[ink(storage)]
+    pub struct LpGauge {
+        /// The only account notifications are accepted from.
+        pair: AccountId,
+        /// The LP token balance of each account.
+        balances: StorageHashMap<AccountId, Balance>,
+        /// The number of balance changes of each account.
+        checkpoints: StorageHashMap<AccountId, u32>,
+    }
+
+    impl LpGauge {
+        /// Creates a gauge following the LP tokens of `pair`.
+        #[ink(constructor)]
+        pub fn new(pair: AccountId) -> Self {
+            Self {
+                pair,
+                balances: StorageHashMap::new(),
+                checkpoints: StorageHashMap::new(),
+            }
+        }
+
+        /// Returns the only account notifications are accepted from.
+        #[ink(message)]
+        pub fn pair(&self) -> AccountId {
+            self.pair
+        }
+
+        /// Returns the LP token balance of `account` seen by the gauge.
+        #[ink(message)]
+        pub fn balance_of(&self, account: AccountId) -> Balance {
+            self.balances.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Returns the number of balance changes of `account` seen by the gauge.
+        #[ink(message)]
+        pub fn checkpoints(&self, account: AccountId) -> u32 {
+            self.checkpoints.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Records the new balance of `account`.
+        fn checkpoint(&mut self, account: AccountId, balance: Balance) {
+            self.balances.insert(account, balance);
+            *self.checkpoints.entry(account).or_insert(0) += 1;
+        }
+    }
+
+    impl LpTransferHook for LpGauge {
+        /// Moves `amount` from the balance of `from` to the balance of `to`.
+        ///
+        /// # Panics
+        ///
+        /// If the caller is not the pair. The pair reports the failed call as
+        /// `HookFailed`.
+        #[ink(message)]
+        fn on_lp_transfer(
+            &mut self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            amount: Balance,
+        ) {
+            assert!(self.env().caller() == self.pair, "LpGauge: NOT_PAIR");
+            if let Some(from) = from {
+                let balance = self.balance_of(from).saturating_sub(amount);
+                self.checkpoint(from, balance);
+            }
+            if let Some(to) = to {
+                let balance = self.balance_of(to).saturating_add(amount);
+                self.checkpoint(to, balance);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .unwrap_or([0x0; 32].into());
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                caller,
+                callee,
+                1000000,
+                1000000,
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])),
+            );
+        }
+
+        #[ink::test]
+        fn transfers_are_recorded() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let mut gauge = LpGauge::new(accounts.django);
+            assert_eq!(gauge.pair(), accounts.django);
+
+            set_caller(accounts.django);
+            gauge.on_lp_transfer(None, Some(accounts.alice), 1_000);
+            gauge.on_lp_transfer(Some(accounts.alice), Some(accounts.bob), 400);
+            gauge.on_lp_transfer(Some(accounts.bob), None, 100);
+            ink_env::test::pop_execution_context();
+
+            assert_eq!(gauge.balance_of(accounts.alice), 600);
+            assert_eq!(gauge.balance_of(accounts.bob), 300);
+            assert_eq!(gauge.checkpoints(accounts.alice), 2);
+            assert_eq!(gauge.checkpoints(accounts.bob), 2);
+            assert_eq!(gauge.checkpoints(accounts.charlie), 0);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "LpGauge: NOT_PAIR")]
+        fn notifications_from_other_accounts_trap() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            // Alice calls, but only Django is the pair.
+            let mut gauge = LpGauge::new(accounts.django);
+            gauge.on_lp_transfer(None, Some(accounts.alice), 1_000);
+        }
+    }
+}
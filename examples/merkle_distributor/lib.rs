@@ -0,0 +1,281 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Merkle Distributor
+//!
+//! Lets accounts claim token rewards listed in a Merkle tree.
+//!
+//! ## Warning
+//!
+//! This contract is an *example*. It is neither audited nor endorsed for production use.
+//! Do **not** rely on it to keep anything of value secure.
+//!
+//! ## Overview
+//!
+//! Paying thousands of accounts in a loop exceeds the gas limit of a block. The
+//! distributor instead stores only the root of a Merkle tree whose leaves list
+//! the rewards, and every account claims its own reward with a proof.
+//!
+//! A leaf is the Keccak-256 hash of the SCALE encoded `(index, account, amount)`,
+//! where `index` is unique across the tree. Inner nodes hash their two children
+//! in ascending order, see `math::hash_pair`.
+//!
+//! The distributor has to hold enough of the reward token to pay all claims.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::merkle_distributor::MerkleDistributor;
+use ink_lang as ink;
+
+mod math;
+
+#[ink::contract]
+pub mod merkle_distributor {
+    use crate::math;
+    use erc20::{
+        BaseErc20,
+        Erc20,
+    };
+    use ink_env::{
+        call::FromAccountId,
+        hash::Keccak256,
+    };
+    use ink_prelude::vec::Vec;
+    use ink_storage::{
+        collections::HashMap as StorageHashMap,
+        lazy::Lazy,
+    };
+
+    /// The distributor error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the reward of an index has already been claimed.
+        AlreadyClaimed,
+        /// Returned if the proof does not prove the claimed leaf.
+        InvalidProof,
+    }
+
+    /// The distributor result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Returns the leaf of the reward of `amount` for `account` at `index`.
+    pub fn leaf_of(index: u64, account: AccountId, amount: Balance) -> [u8; 32] {
+        let mut output = [0x0; 32];
+        ink_env::hash_encoded::<Keccak256, _>(&(index, account, amount), &mut output);
+        output
+    }
+
+    /// Event emitted when `account` claims `amount` at `index`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Claimed {
+        #[ink(topic)]
+        index: u64,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(storage)]
+    pub struct MerkleDistributor {
+        /// The token the rewards are paid in.
+        token: Lazy<Erc20>,
+        /// The root of the tree of rewards.
+        merkle_root: [u8; 32],
+        /// The indices of the rewards that have been claimed.
+        claimed: StorageHashMap<u64, bool>,
+    }
+
+    impl MerkleDistributor {
+        /// Creates a distributor paying the rewards of the tree with the root
+        /// `merkle_root` in `token`.
+        #[ink(constructor)]
+        pub fn new(token: AccountId, merkle_root: [u8; 32]) -> Self {
+            Self {
+                token: Lazy::new(FromAccountId::from_account_id(token)),
+                merkle_root,
+                claimed: StorageHashMap::new(),
+            }
+        }
+
+        /// Returns the root of the tree of rewards.
+        #[ink(message)]
+        pub fn merkle_root(&self) -> [u8; 32] {
+            self.merkle_root
+        }
+
+        /// Returns whether the reward at `index` has been claimed.
+        #[ink(message)]
+        pub fn is_claimed(&self, index: u64) -> bool {
+            self.claimed.get(&index).copied().unwrap_or(false)
+        }
+
+        /// Pays `amount` of the reward token to `account` for the leaf at
+        /// `index`, proven by `proof`.
+        ///
+        /// Anyone may submit the claim of an account, the reward is always paid
+        /// to the account of the leaf.
+        ///
+        /// On success a `Claimed` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `AlreadyClaimed` if the reward has already been claimed.
+        /// - Returns `InvalidProof` if `proof` does not prove the leaf.
+        ///
+        /// # Panics
+        ///
+        /// If the token transfer fails.
+        #[ink(message)]
+        pub fn claim(
+            &mut self,
+            index: u64,
+            account: AccountId,
+            amount: Balance,
+            proof: Vec<[u8; 32]>,
+        ) -> Result<()> {
+            self.mark_claimed(index, account, amount, &proof)?;
+            assert!(
+                self.token.transfer(account, amount).is_ok(),
+                "MerkleDistributor: TRANSFER_FAILED"
+            );
+            self.env().emit_event(Claimed {
+                index,
+                account,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Verifies the claim of `amount` for `account` at `index` and marks it
+        /// as claimed, leaving the transfer to the caller.
+        fn mark_claimed(
+            &mut self,
+            index: u64,
+            account: AccountId,
+            amount: Balance,
+            proof: &[[u8; 32]],
+        ) -> Result<()> {
+            if self.is_claimed(index) {
+                return Err(Error::AlreadyClaimed)
+            }
+            let leaf = leaf_of(index, account, amount);
+            if !math::verify_proof(proof, self.merkle_root, leaf) {
+                return Err(Error::InvalidProof)
+            }
+            self.claimed.insert(index, true);
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        /// A distributor of three rewards together with the accounts used
+        /// across the tests.
+        struct TestEnv {
+            distributor: MerkleDistributor,
+            leaves: [[u8; 32]; 3],
+            alice: AccountId,
+            bob: AccountId,
+            charlie: AccountId,
+        }
+
+        impl TestEnv {
+            /// Creates a distributor of 100 to Alice, 200 to Bob and 300 to
+            /// Charlie with the root `h(h(alice, bob), charlie)`.
+            fn new() -> Self {
+                let accounts =
+                    ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                        .expect("Cannot get accounts");
+                let leaves = [
+                    leaf_of(0, accounts.alice, 100),
+                    leaf_of(1, accounts.bob, 200),
+                    leaf_of(2, accounts.charlie, 300),
+                ];
+                let root =
+                    math::hash_pair(math::hash_pair(leaves[0], leaves[1]), leaves[2]);
+                Self {
+                    distributor: MerkleDistributor::new(accounts.django, root),
+                    leaves,
+                    alice: accounts.alice,
+                    bob: accounts.bob,
+                    charlie: accounts.charlie,
+                }
+            }
+        }
+
+        #[ink::test]
+        fn valid_claim_is_marked() {
+            let TestEnv {
+                mut distributor,
+                leaves,
+                bob,
+                ..
+            } = TestEnv::new();
+            assert!(!distributor.is_claimed(1));
+            assert_eq!(
+                distributor.mark_claimed(1, bob, 200, &[leaves[0], leaves[2]]),
+                Ok(())
+            );
+            assert!(distributor.is_claimed(1));
+            assert!(!distributor.is_claimed(0));
+        }
+
+        #[ink::test]
+        fn claim_fails_twice() {
+            let TestEnv {
+                mut distributor,
+                leaves,
+                charlie,
+                ..
+            } = TestEnv::new();
+            let proof = [math::hash_pair(leaves[0], leaves[1])];
+            assert_eq!(distributor.mark_claimed(2, charlie, 300, &proof), Ok(()));
+            assert_eq!(
+                distributor.claim(2, charlie, 300, proof.to_vec()),
+                Err(Error::AlreadyClaimed)
+            );
+        }
+
+        #[ink::test]
+        fn claim_fails_with_invalid_proof() {
+            let TestEnv {
+                mut distributor,
+                leaves,
+                alice,
+                bob,
+                ..
+            } = TestEnv::new();
+            let proof = vec![leaves[1], leaves[2]];
+            // Wrong amount, wrong account and wrong index.
+            assert_eq!(
+                distributor.claim(0, alice, 101, proof.clone()),
+                Err(Error::InvalidProof)
+            );
+            assert_eq!(
+                distributor.claim(0, bob, 100, proof.clone()),
+                Err(Error::InvalidProof)
+            );
+            assert_eq!(
+                distributor.claim(1, alice, 100, proof),
+                Err(Error::InvalidProof)
+            );
+            assert!(!distributor.is_claimed(0));
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merkle proof verification used by the distributor.
+
+use ink_env::hash::Keccak256;
+
+/// Returns the Keccak-256 hash of the concatenation of `a` and `b` in
+/// ascending order.
+///
+/// Sorting the pair makes proofs independent of whether a node is the left or
+/// the right child of its parent.
+pub fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut input = [0x0; 64];
+    input[..32].copy_from_slice(&low);
+    input[32..].copy_from_slice(&high);
+    let mut output = [0x0; 32];
+    ink_env::hash_bytes::<Keccak256>(&input, &mut output);
+    output
+}
+
+/// Returns `true` if `proof` proves that `leaf` is part of the tree with the
+/// root `root`.
+///
+/// `proof` lists the siblings on the path from `leaf` up to the root.
+pub fn verify_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    proof.iter().fold(leaf, |node, &sibling| hash_pair(node, sibling)) == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_pair_is_symmetric() {
+        let (a, b) = ([0x01; 32], [0x02; 32]);
+        assert_eq!(hash_pair(a, b), hash_pair(b, a));
+        assert_ne!(hash_pair(a, b), hash_pair(a, a));
+    }
+
+    #[test]
+    fn verify_proof_works() {
+        // root = h(h(l0, l1), h(l2, l3))
+        let leaves = [[0x10; 32], [0x11; 32], [0x12; 32], [0x13; 32]];
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        let root = hash_pair(left, right);
+
+        assert!(verify_proof(&[leaves[3], left], root, leaves[2]));
+        assert!(verify_proof(&[leaves[0], right], root, leaves[1]));
+        // A single leaf is its own root.
+        assert!(verify_proof(&[], leaves[0], leaves[0]));
+        assert!(!verify_proof(&[leaves[2], left], root, leaves[2]));
+        assert!(!verify_proof(&[leaves[3]], root, leaves[2]));
+        assert!(!verify_proof(&[leaves[3], left], root, [0x14; 32]));
+    }
+}
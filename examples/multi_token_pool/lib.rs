@@ -0,0 +1,525 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+mod math;
+
+#[ink::contract]
+mod multi_token_pool {
+    use crate::math::{
+        self,
+        BONE,
+    };
+    use erc20::{
+        BaseErc20,
+        Erc20,
+    };
+    use ink_env::call::FromAccountId;
+    use ink_prelude::vec::Vec;
+    #[cfg(not(feature = "ink-as-dependency"))]
+    use ink_storage::collections::HashMap as StorageHashMap;
+
+    /// The maximum number of tokens in a pool.
+    pub const MAX_TOKENS: usize = 8;
+
+    /// The sum of all token weights.
+    pub const TOTAL_WEIGHT: u8 = 100;
+
+    /// The swap fee as a fixed-point fraction, i.e. 0.3%.
+    pub const SWAP_FEE: u128 = BONE * 3 / 1000;
+
+    /// The pool tokens minted for the first deposit.
+    pub const INIT_POOL_SUPPLY: Balance = 100 * BONE;
+
+    /// The maximum share of the in-token balance that can be swapped at once.
+    pub const MAX_IN_RATIO: u128 = BONE / 2;
+
+    /// The multi token pool error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if a token index does not refer to a pool token.
+        InvalidTokenIndex,
+        /// Returned if a swap has the same in and out token.
+        SameToken,
+        /// Returned if an amount is zero.
+        ZeroAmount,
+        /// Returned if a swap exceeds `MAX_IN_RATIO` of the in-token balance.
+        MaxInRatio,
+        /// Returned if a deposit or withdrawal would mint or return nothing.
+        InsufficientLiquidity,
+        /// Returned if a swap would decrease the pool invariant.
+        InvariantViolated,
+        /// Returned if not enough pool tokens are available.
+        InsufficientBalance,
+        /// Returned if an intermediate value does not fit into a `u128`.
+        Overflow,
+    }
+
+    /// The multi token pool result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A Balancer-style pool of up to `MAX_TOKENS` weighted tokens.
+    ///
+    /// Swaps keep the invariant `∏ reserve_i ^ (weight_i / TOTAL_WEIGHT)` from
+    /// decreasing. Unused token slots have a zero weight and a zero reserve.
+    #[ink(storage)]
+    pub struct MultiTokenPool {
+        /// The number of tokens in the pool.
+        num_tokens: u8,
+        /// The pool tokens.
+        tokens: [AccountId; MAX_TOKENS],
+        /// The reserves of the pool tokens.
+        reserves: [Balance; MAX_TOKENS],
+        /// The weights of the pool tokens, summing up to `TOTAL_WEIGHT`.
+        weights: [u8; MAX_TOKENS],
+        /// The total supply of pool shares.
+        total_supply: Balance,
+        /// Mapping from owner to number of owned pool shares.
+        balances: StorageHashMap<AccountId, Balance>,
+    }
+
+    /// Event emitted when liquidity is added to the pool.
+    #[ink(event)]
+    pub struct LiquidityAdded {
+        #[ink(topic)]
+        provider: AccountId,
+        amounts: [Balance; MAX_TOKENS],
+        liquidity: Balance,
+    }
+
+    /// Event emitted when liquidity is removed from the pool.
+    #[ink(event)]
+    pub struct LiquidityRemoved {
+        #[ink(topic)]
+        provider: AccountId,
+        amounts: [Balance; MAX_TOKENS],
+        liquidity: Balance,
+    }
+
+    /// Event emitted when tokens are swapped.
+    #[ink(event)]
+    pub struct Swapped {
+        #[ink(topic)]
+        sender: AccountId,
+        token_in_idx: u8,
+        token_out_idx: u8,
+        amount_in: Balance,
+        amount_out: Balance,
+    }
+
+    impl MultiTokenPool {
+        /// Creates a new pool of `tokens` with the given `weights`.
+        ///
+        /// # Panics
+        ///
+        /// - If there are less than two or more than `MAX_TOKENS` tokens.
+        /// - If the number of weights does not match the number of tokens.
+        /// - If a weight is zero or the weights do not sum up to `TOTAL_WEIGHT`.
+        /// - If a token is listed twice.
+        #[ink(constructor)]
+        pub fn new(tokens: Vec<AccountId>, weights: Vec<u8>) -> Self {
+            ensure_tokens_are_valid(&tokens, &weights);
+            let mut pool_tokens = [AccountId::from([0x00; 32]); MAX_TOKENS];
+            let mut pool_weights = [0; MAX_TOKENS];
+            pool_tokens[..tokens.len()].copy_from_slice(&tokens);
+            pool_weights[..weights.len()].copy_from_slice(&weights);
+            Self {
+                num_tokens: tokens.len() as u8,
+                tokens: pool_tokens,
+                reserves: [0; MAX_TOKENS],
+                weights: pool_weights,
+                total_supply: 0,
+                balances: StorageHashMap::new(),
+            }
+        }
+
+        /// Returns the pool tokens.
+        #[ink(message)]
+        pub fn tokens(&self) -> [AccountId; MAX_TOKENS] {
+            self.tokens
+        }
+
+        /// Returns the reserves of the pool tokens.
+        #[ink(message)]
+        pub fn reserves(&self) -> [Balance; MAX_TOKENS] {
+            self.reserves
+        }
+
+        /// Returns the weights of the pool tokens.
+        #[ink(message)]
+        pub fn weights(&self) -> [u8; MAX_TOKENS] {
+            self.weights
+        }
+
+        /// Returns the total supply of pool shares.
+        #[ink(message)]
+        pub fn total_supply(&self) -> Balance {
+            self.total_supply
+        }
+
+        /// Returns the pool shares of `owner`.
+        #[ink(message)]
+        pub fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(&owner).copied().unwrap_or(0)
+        }
+
+        /// Returns the amount of token `token_out_idx` received for `amount_in`
+        /// of token `token_in_idx` at the current reserves.
+        #[ink(message)]
+        pub fn get_amount_out(
+            &self,
+            token_in_idx: u8,
+            token_out_idx: u8,
+            amount_in: Balance,
+        ) -> Result<Balance> {
+            let (amount_out, _, _) =
+                self.compute_swap(token_in_idx, token_out_idx, amount_in)?;
+            Ok(amount_out)
+        }
+
+        /// Swaps `amount_in` of token `token_in_idx` for token `token_out_idx`.
+        ///
+        /// The caller must have approved the pool to spend `amount_in`.
+        ///
+        /// Returns the amount of token `token_out_idx` sent to the caller.
+        ///
+        /// # Panics
+        ///
+        /// If a token transfer fails.
+        #[ink(message)]
+        pub fn swap_tokens(
+            &mut self,
+            token_in_idx: u8,
+            token_out_idx: u8,
+            amount_in: Balance,
+        ) -> Result<Balance> {
+            let (amount_out, new_reserve_in, new_reserve_out) =
+                self.compute_swap(token_in_idx, token_out_idx, amount_in)?;
+            let sender = self.env().caller();
+            let (i, o) = (token_in_idx as usize, token_out_idx as usize);
+            self.reserves[i] = new_reserve_in;
+            self.reserves[o] = new_reserve_out;
+            self.pull(i, sender, amount_in);
+            self.push(o, sender, amount_out);
+            self.env().emit_event(Swapped {
+                sender,
+                token_in_idx,
+                token_out_idx,
+                amount_in,
+                amount_out,
+            });
+            Ok(amount_out)
+        }
+
+        /// Deposits up to `amounts` of each pool token in exchange for pool shares.
+        ///
+        /// The first deposit sets the pool's prices and mints `INIT_POOL_SUPPLY`.
+        /// Later deposits are proportional to the reserves: only the amounts
+        /// matching the most limiting token are pulled from the caller.
+        ///
+        /// Returns the amount of pool shares minted to the caller.
+        ///
+        /// # Panics
+        ///
+        /// If a token transfer fails.
+        #[ink(message)]
+        pub fn add_liquidity(&mut self, amounts: [Balance; MAX_TOKENS]) -> Result<Balance> {
+            let n = self.num_tokens as usize;
+            let (liquidity, deposits) = if self.total_supply == 0 {
+                if amounts[..n].contains(&0) {
+                    return Err(Error::ZeroAmount)
+                }
+                let mut deposits = [0; MAX_TOKENS];
+                deposits[..n].copy_from_slice(&amounts[..n]);
+                (INIT_POOL_SUPPLY, deposits)
+            } else {
+                let mut ratio = u128::MAX;
+                for (&amount, &reserve) in amounts[..n].iter().zip(&self.reserves[..n]) {
+                    let token_ratio = math::bdiv(amount, reserve).ok_or(Error::Overflow)?;
+                    ratio = core::cmp::min(ratio, token_ratio);
+                }
+                let liquidity =
+                    math::bmul(ratio, self.total_supply).ok_or(Error::Overflow)?;
+                let mut deposits = [0; MAX_TOKENS];
+                let reserves = &self.reserves[..n];
+                for (deposit, &reserve) in deposits[..n].iter_mut().zip(reserves) {
+                    *deposit = math::bmul_up(ratio, reserve).ok_or(Error::Overflow)?;
+                }
+                (liquidity, deposits)
+            };
+            if liquidity == 0 {
+                return Err(Error::InsufficientLiquidity)
+            }
+            let mut reserves = self.reserves;
+            for (reserve, &deposit) in reserves[..n].iter_mut().zip(&deposits[..n]) {
+                *reserve = reserve.checked_add(deposit).ok_or(Error::Overflow)?;
+            }
+            let total_supply = self
+                .total_supply
+                .checked_add(liquidity)
+                .ok_or(Error::Overflow)?;
+            let provider = self.env().caller();
+            self.reserves = reserves;
+            self.total_supply = total_supply;
+            let balance = self.balance_of(provider);
+            self.balances.insert(provider, balance + liquidity);
+            for (i, &deposit) in deposits[..n].iter().enumerate() {
+                self.pull(i, provider, deposit);
+            }
+            self.env().emit_event(LiquidityAdded {
+                provider,
+                amounts: deposits,
+                liquidity,
+            });
+            Ok(liquidity)
+        }
+
+        /// Burns `liquidity` pool shares of the caller in exchange for the
+        /// proportional share of every reserve.
+        ///
+        /// Returns the amounts of each pool token sent to the caller.
+        ///
+        /// # Panics
+        ///
+        /// If a token transfer fails.
+        #[ink(message)]
+        pub fn remove_liquidity(&mut self, liquidity: Balance) -> Result<[Balance; MAX_TOKENS]> {
+            let provider = self.env().caller();
+            let balance = self.balance_of(provider);
+            if balance < liquidity {
+                return Err(Error::InsufficientBalance)
+            }
+            let ratio =
+                math::bdiv(liquidity, self.total_supply).ok_or(Error::InsufficientLiquidity)?;
+            let n = self.num_tokens as usize;
+            let mut amounts = [0; MAX_TOKENS];
+            for (amount, &reserve) in amounts[..n].iter_mut().zip(&self.reserves[..n]) {
+                *amount = math::bmul(ratio, reserve).ok_or(Error::Overflow)?;
+            }
+            if amounts[..n].iter().all(|&amount| amount == 0) {
+                return Err(Error::InsufficientLiquidity)
+            }
+            self.balances.insert(provider, balance - liquidity);
+            self.total_supply -= liquidity;
+            for (reserve, &amount) in self.reserves[..n].iter_mut().zip(&amounts[..n]) {
+                *reserve -= amount;
+            }
+            for (i, &amount) in amounts[..n].iter().enumerate() {
+                self.push(i, provider, amount);
+            }
+            self.env().emit_event(LiquidityRemoved {
+                provider,
+                amounts,
+                liquidity,
+            });
+            Ok(amounts)
+        }
+
+        /// Returns `(amount_out, new_reserve_in, new_reserve_out)` for swapping
+        /// `amount_in` of token `token_in_idx` for token `token_out_idx`.
+        fn compute_swap(
+            &self,
+            token_in_idx: u8,
+            token_out_idx: u8,
+            amount_in: Balance,
+        ) -> Result<(Balance, Balance, Balance)> {
+            let (i, o) = (token_in_idx as usize, token_out_idx as usize);
+            if i >= self.num_tokens as usize || o >= self.num_tokens as usize {
+                return Err(Error::InvalidTokenIndex)
+            }
+            if i == o {
+                return Err(Error::SameToken)
+            }
+            if amount_in == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            let (reserve_in, reserve_out) = (self.reserves[i], self.reserves[o]);
+            let max_in = math::bmul(reserve_in, MAX_IN_RATIO).ok_or(Error::Overflow)?;
+            if amount_in > max_in {
+                return Err(Error::MaxInRatio)
+            }
+            let (weight_in, weight_out) = (self.weights[i] as u128, self.weights[o] as u128);
+            let amount_out = math::calc_out_given_in(
+                reserve_in, weight_in, reserve_out, weight_out, amount_in, SWAP_FEE,
+            )
+            .ok_or(Error::Overflow)?;
+            if amount_out == 0 || amount_out >= reserve_out {
+                return Err(Error::InsufficientLiquidity)
+            }
+            let new_reserve_in = reserve_in.checked_add(amount_in).ok_or(Error::Overflow)?;
+            let new_reserve_out = reserve_out - amount_out;
+            let holds = math::invariant_holds(
+                reserve_in,
+                weight_in,
+                reserve_out,
+                weight_out,
+                new_reserve_in,
+                new_reserve_out,
+            )
+            .ok_or(Error::Overflow)?;
+            if !holds {
+                return Err(Error::InvariantViolated)
+            }
+            Ok((amount_out, new_reserve_in, new_reserve_out))
+        }
+
+        /// Transfers `amount` of token `idx` from `from` to the pool.
+        ///
+        /// Panics instead of returning an error if the transfer fails, so that
+        /// the whole call is reverted together with the state changed before.
+        fn pull(&self, idx: usize, from: AccountId, amount: Balance) {
+            let mut token: Erc20 = FromAccountId::from_account_id(self.tokens[idx]);
+            assert!(
+                token
+                    .transfer_from(from, self.env().account_id(), amount)
+                    .is_ok(),
+                "MultiTokenPool: TRANSFER_FAILED"
+            );
+        }
+
+        /// Transfers `amount` of token `idx` from the pool to `to`.
+        ///
+        /// Panics if the transfer fails, see `pull`.
+        fn push(&self, idx: usize, to: AccountId, amount: Balance) {
+            let mut token: Erc20 = FromAccountId::from_account_id(self.tokens[idx]);
+            assert!(
+                token.transfer(to, amount).is_ok(),
+                "MultiTokenPool: TRANSFER_FAILED"
+            );
+        }
+    }
+
+    /// Panic if `tokens` and `weights` do not describe a valid pool.
+    fn ensure_tokens_are_valid(tokens: &[AccountId], weights: &[u8]) {
+        assert!(
+            tokens.len() >= 2 && tokens.len() <= MAX_TOKENS,
+            "MultiTokenPool: INVALID_TOKEN_COUNT"
+        );
+        assert!(
+            weights.len() == tokens.len()
+                && weights.iter().all(|&weight| weight > 0)
+                && weights.iter().map(|&weight| weight as u32).sum::<u32>()
+                    == TOTAL_WEIGHT as u32,
+            "MultiTokenPool: INVALID_WEIGHTS"
+        );
+        for (n, token) in tokens.iter().enumerate() {
+            assert!(
+                !tokens[n + 1..].contains(token),
+                "MultiTokenPool: DUPLICATE_TOKEN"
+            );
+        }
+    }
+
+    /// Unit tests.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+
+        fn token(n: u8) -> AccountId {
+            AccountId::from([n; 32])
+        }
+
+        fn new_pool(weights: Vec<u8>, reserves: &[Balance]) -> MultiTokenPool {
+            let tokens = (1..=weights.len() as u8).map(token).collect();
+            let mut pool = MultiTokenPool::new(tokens, weights);
+            pool.reserves[..reserves.len()].copy_from_slice(reserves);
+            pool
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let pool = new_pool(vec![20, 30, 50], &[]);
+            assert_eq!(pool.num_tokens, 3);
+            assert_eq!(&pool.tokens()[..3], &[token(1), token(2), token(3)]);
+            assert_eq!(pool.weights(), [20, 30, 50, 0, 0, 0, 0, 0]);
+            assert_eq!(pool.reserves(), [0; MAX_TOKENS]);
+            assert_eq!(pool.total_supply(), 0);
+        }
+
+        #[ink::test]
+        fn eight_tokens_are_supported() {
+            let pool = new_pool(vec![10, 10, 10, 10, 10, 10, 20, 20], &[]);
+            assert_eq!(pool.num_tokens as usize, MAX_TOKENS);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "MultiTokenPool: INVALID_TOKEN_COUNT")]
+        fn single_token_construction_fails() {
+            MultiTokenPool::new(vec![token(1)], vec![100]);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "MultiTokenPool: INVALID_TOKEN_COUNT")]
+        fn too_many_tokens_construction_fails() {
+            let tokens = (1..=9).map(token).collect();
+            MultiTokenPool::new(tokens, vec![10, 10, 10, 10, 10, 10, 10, 10, 20]);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "MultiTokenPool: INVALID_WEIGHTS")]
+        fn weights_not_summing_up_construction_fails() {
+            MultiTokenPool::new(vec![token(1), token(2)], vec![50, 40]);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "MultiTokenPool: INVALID_WEIGHTS")]
+        fn zero_weight_construction_fails() {
+            MultiTokenPool::new(vec![token(1), token(2), token(3)], vec![50, 50, 0]);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "MultiTokenPool: DUPLICATE_TOKEN")]
+        fn duplicate_token_construction_fails() {
+            MultiTokenPool::new(vec![token(1), token(2), token(1)], vec![20, 30, 50]);
+        }
+
+        #[ink::test]
+        fn get_amount_out_works() {
+            let pool = new_pool(vec![25, 25, 50], &[1_000 * BONE, 1_000 * BONE, 1_000 * BONE]);
+            let equal = pool.get_amount_out(0, 1, BONE).unwrap();
+            let heavier = pool.get_amount_out(0, 2, BONE).unwrap();
+            let lighter = pool.get_amount_out(2, 0, BONE).unwrap();
+            // 0.3% fee and a little slippage.
+            assert!(equal < BONE * 997 / 1000 && equal > BONE * 996 / 1000);
+            assert!(heavier < equal);
+            assert!(lighter > equal);
+        }
+
+        #[ink::test]
+        fn get_amount_out_validates_input() {
+            let pool = new_pool(vec![50, 50], &[1_000 * BONE, 1_000 * BONE]);
+            assert_eq!(pool.get_amount_out(0, 2, BONE), Err(Error::InvalidTokenIndex));
+            assert_eq!(pool.get_amount_out(1, 1, BONE), Err(Error::SameToken));
+            assert_eq!(pool.get_amount_out(0, 1, 0), Err(Error::ZeroAmount));
+            assert_eq!(pool.get_amount_out(0, 1, 501 * BONE), Err(Error::MaxInRatio));
+        }
+
+        #[ink::test]
+        fn swap_keeps_invariant() {
+            let pool = new_pool(vec![10, 60, 30], &[300 * BONE, 5_000 * BONE, 70 * BONE]);
+            for &(i, o, amount_in) in &[(0, 1, 10 * BONE), (1, 2, 100 * BONE), (2, 0, BONE)] {
+                let (amount_out, new_in, new_out) =
+                    pool.compute_swap(i, o, amount_in).unwrap();
+                assert_eq!(new_in, pool.reserves[i as usize] + amount_in);
+                assert_eq!(new_out, pool.reserves[o as usize] - amount_out);
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fixed-point arithmetic for weighted pools, following Balancer's `BNum`.
+//!
+//! All fixed-point numbers are scaled by [`BONE`]. Intermediate products are
+//! computed with 256 bits so that balances may use the full `u128` range.
+//! Functions return `None` on overflow instead of wrapping.
+
+pub use full_math::{
+    mul_div,
+    mul_div_up,
+};
+
+/// The balance type of the default environment.
+pub type Balance = u128;
+
+/// The fixed-point representation of `1`.
+pub const BONE: u128 = 1_000_000_000_000_000_000;
+
+/// The smallest base accepted by [`bpow`].
+pub const MIN_BPOW_BASE: u128 = 1;
+
+/// The largest base accepted by [`bpow`].
+pub const MAX_BPOW_BASE: u128 = 2 * BONE - 1;
+
+/// The precision at which the series in [`bpow`] is cut off.
+pub const BPOW_PRECISION: u128 = BONE / 10_000_000_000;
+
+/// Returns the integer part of `a` as a fixed-point number.
+fn bfloor(a: u128) -> u128 {
+    a / BONE * BONE
+}
+
+/// Returns `|a - b|` and whether `a < b`.
+fn bsub_sign(a: u128, b: u128) -> (u128, bool) {
+    if a >= b {
+        (a - b, false)
+    } else {
+        (b - a, true)
+    }
+}
+
+/// Returns `a * b`, rounded down.
+pub fn bmul(a: u128, b: u128) -> Option<u128> {
+    mul_div(a, b, BONE)
+}
+
+/// Returns `a * b`, rounded up.
+pub fn bmul_up(a: u128, b: u128) -> Option<u128> {
+    mul_div_up(a, b, BONE)
+}
+
+/// Returns `a / b`, rounded down.
+///
+/// Returns `None` if `b` is zero.
+pub fn bdiv(a: u128, b: u128) -> Option<u128> {
+    mul_div(a, BONE, b)
+}
+
+/// Returns `a` to the power of the integer `n`.
+fn bpowi(a: u128, mut n: u128) -> Option<u128> {
+    let mut a = a;
+    let mut z = if n % 2 != 0 { a } else { BONE };
+    n /= 2;
+    while n != 0 {
+        a = bmul(a, a)?;
+        if n % 2 != 0 {
+            z = bmul(z, a)?;
+        }
+        n /= 2;
+    }
+    Some(z)
+}
+
+/// Returns `base` to the power of the fractional `exp`, `0 <= exp < BONE`,
+/// using the binomial series until a term drops below `precision`.
+fn bpow_approx(base: u128, exp: u128, precision: u128) -> Option<u128> {
+    let a = exp;
+    let (x, xneg) = bsub_sign(base, BONE);
+    let mut term = BONE;
+    let mut sum = term;
+    let mut negative = false;
+    let mut i = 1;
+    while term >= precision {
+        let big_k = i * BONE;
+        let (c, cneg) = bsub_sign(a, big_k - BONE);
+        term = bmul(term, bmul(c, x)?)?;
+        term = bdiv(term, big_k)?;
+        if term == 0 {
+            break
+        }
+        if xneg {
+            negative = !negative;
+        }
+        if cneg {
+            negative = !negative;
+        }
+        if negative {
+            sum = sum.checked_sub(term)?;
+        } else {
+            sum = sum.checked_add(term)?;
+        }
+        i += 1;
+    }
+    Some(sum)
+}
+
+/// Returns `base` to the power of `exp`.
+///
+/// Returns `None` if `base` lies outside of `[MIN_BPOW_BASE, MAX_BPOW_BASE]`
+/// or on overflow.
+pub fn bpow(base: u128, exp: u128) -> Option<u128> {
+    if !(MIN_BPOW_BASE..=MAX_BPOW_BASE).contains(&base) {
+        return None
+    }
+    let whole = bfloor(exp);
+    let remain = exp - whole;
+    let whole_pow = bpowi(base, whole / BONE)?;
+    if remain == 0 {
+        return Some(whole_pow)
+    }
+    let partial_result = bpow_approx(base, remain, BPOW_PRECISION)?;
+    bmul(whole_pow, partial_result)
+}
+
+/// Returns the amount of the out token received for `amount_in` of the in token.
+///
+/// Implements Balancer's `calcOutGivenIn`:
+///
+/// `amount_out = balance_out * (1 - (balance_in / (balance_in + amount_in * (1 - fee))) ^ (weight_in / weight_out))`
+///
+/// where `swap_fee` is given as a fixed-point fraction.
+pub fn calc_out_given_in(
+    balance_in: Balance,
+    weight_in: u128,
+    balance_out: Balance,
+    weight_out: u128,
+    amount_in: Balance,
+    swap_fee: u128,
+) -> Option<Balance> {
+    let weight_ratio = bdiv(weight_in, weight_out)?;
+    let adjusted_in = bmul(amount_in, BONE.checked_sub(swap_fee)?)?;
+    let y = bdiv(balance_in, balance_in.checked_add(adjusted_in)?)?;
+    let y_pow = bpow(y, weight_ratio)?;
+    let out_ratio = BONE.checked_sub(y_pow)?;
+    bmul(balance_out, out_ratio)
+}
+
+/// Returns `true` if moving the balances from `(balance_in, balance_out)` to
+/// `(new_balance_in, new_balance_out)` does not decrease the weighted product
+/// `balance_in ^ weight_in * balance_out ^ weight_out`.
+///
+/// Since all other balances in the pool are unchanged this is equivalent to
+/// the N-token invariant `∏ balance_i ^ weight_i` not decreasing. The check
+/// uses the same series approximation as [`calc_out_given_in`] and therefore
+/// tolerates a deviation of `BPOW_PRECISION`.
+pub fn invariant_holds(
+    balance_in: Balance,
+    weight_in: u128,
+    balance_out: Balance,
+    weight_out: u128,
+    new_balance_in: Balance,
+    new_balance_out: Balance,
+) -> Option<bool> {
+    let weight_ratio = bdiv(weight_in, weight_out)?;
+    let growth_in = bpow(bdiv(new_balance_in, balance_in)?, weight_ratio)?;
+    let growth = bmul(growth_in, bdiv(new_balance_out, balance_out)?)?;
+    Some(growth + BPOW_PRECISION >= BONE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: u128, expected: u128, tolerance: u128) {
+        let diff = if actual > expected {
+            actual - expected
+        } else {
+            expected - actual
+        };
+        assert!(
+            diff <= tolerance,
+            "{} differs from {} by more than {}",
+            actual,
+            expected,
+            tolerance
+        );
+    }
+
+    #[test]
+    fn bpow_works() {
+        assert_eq!(bpow(BONE / 2, 2 * BONE), Some(BONE / 4));
+        assert_eq!(bpow(BONE / 2, 0), Some(BONE));
+        assert_close(bpow(BONE * 81 / 100, BONE / 2).unwrap(), BONE * 9 / 10, BPOW_PRECISION);
+        assert_close(
+            bpow(BONE + BONE / 2, BONE / 2).unwrap(),
+            1_224_744_871_391_589_049,
+            BPOW_PRECISION,
+        );
+    }
+
+    #[test]
+    fn bpow_rejects_out_of_range_bases() {
+        assert_eq!(bpow(0, BONE), None);
+        assert_eq!(bpow(2 * BONE, BONE), None);
+    }
+
+    #[test]
+    fn equal_weights_match_constant_product() {
+        let (balance_in, balance_out, amount_in) = (1_000 * BONE, 2_000 * BONE, 10 * BONE);
+        let amount_out =
+            calc_out_given_in(balance_in, 50, balance_out, 50, amount_in, 0).unwrap();
+        let expected = mul_div(balance_out, amount_in, balance_in + amount_in).unwrap();
+        assert_close(amount_out, expected, 1_000);
+    }
+
+    #[test]
+    fn heavier_out_token_yields_less() {
+        let balance = 1_000 * BONE;
+        let light_out = calc_out_given_in(balance, 50, balance, 50, BONE, 0).unwrap();
+        let heavy_out = calc_out_given_in(balance, 20, balance, 80, BONE, 0).unwrap();
+        assert!(heavy_out < light_out);
+    }
+
+    #[test]
+    fn swaps_preserve_invariant() {
+        let fee = BONE * 3 / 1000;
+        for &(weight_in, weight_out) in &[(50, 50), (20, 80), (80, 20), (10, 30)] {
+            let (balance_in, balance_out, amount_in) = (500 * BONE, 700 * BONE, 25 * BONE);
+            let amount_out = calc_out_given_in(
+                balance_in, weight_in, balance_out, weight_out, amount_in, fee,
+            )
+            .unwrap();
+            assert_eq!(
+                invariant_holds(
+                    balance_in,
+                    weight_in,
+                    balance_out,
+                    weight_out,
+                    balance_in + amount_in,
+                    balance_out - amount_out,
+                ),
+                Some(true)
+            );
+            // Taking out a single percent more breaks the invariant.
+            assert_eq!(
+                invariant_holds(
+                    balance_in,
+                    weight_in,
+                    balance_out,
+                    weight_out,
+                    balance_in + amount_in,
+                    balance_out - amount_out - balance_out / 100,
+                ),
+                Some(false)
+            );
+        }
+    }
+}
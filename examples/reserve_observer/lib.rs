@@ -0,0 +1,153 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Reserve Observer
+//!
+//! Records the reserve updates of a uniswap pair.
+//!
+//! ## Warning
+//!
+//! This contract is an *example*. It is neither audited nor endorsed for production use.
+//! Do **not** rely on it to keep anything of value secure.
+//!
+//! ## Overview
+//!
+//! A pair calls the `PairObserver::on_sync` message of every registered
+//! observer after each `Sync` event, that is after every swap, mint, burn and
+//! sync. A failing observer is reported with `HookFailed` and does not revert
+//! the pair.
+//!
+//! This observer only keeps the latest reserves and counts the updates. A real
+//! strategy would rebalance or hedge a position on each call.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::reserve_observer::{
+    PairObserver,
+    ReserveObserver,
+};
+use ink_lang as ink;
+
+#[ink::contract]
+pub mod reserve_observer {
+    /// Trait implemented by contracts notified of reserve updates.
+    #[ink::trait_definition]
+    pub trait PairObserver {
+        /// Receives the new reserves of the calling pair.
+        #[ink(message)]
+        fn on_sync(&mut self, reserve0: Balance, reserve1: Balance);
+    }
+
+    /// An observer recording the reserve updates of `pair`.
+    #[ink(storage)]
+    pub struct ReserveObserver {
+        /// The only account updates are accepted from.
+        pair: AccountId,
+        /// The latest reserves of the pair.
+        reserves: (Balance, Balance),
+        /// The number of updates received.
+        sync_count: u32,
+    }
+
+    impl ReserveObserver {
+        /// Creates an observer of `pair`.
+        #[ink(constructor)]
+        pub fn new(pair: AccountId) -> Self {
+            Self {
+                pair,
+                reserves: (0, 0),
+                sync_count: 0,
+            }
+        }
+
+        /// Returns the only account updates are accepted from.
+        #[ink(message)]
+        pub fn pair(&self) -> AccountId {
+            self.pair
+        }
+
+        /// Returns the latest reserves of the pair.
+        #[ink(message)]
+        pub fn reserves(&self) -> (Balance, Balance) {
+            self.reserves
+        }
+
+        /// Returns the number of updates received.
+        #[ink(message)]
+        pub fn sync_count(&self) -> u32 {
+            self.sync_count
+        }
+    }
+
+    impl PairObserver for ReserveObserver {
+        /// Stores `reserve0` and `reserve1` as the latest reserves.
+        ///
+        /// # Panics
+        ///
+        /// If the caller is not the pair.
+        #[ink(message)]
+        fn on_sync(&mut self, reserve0: Balance, reserve1: Balance) {
+            assert!(self.env().caller() == self.pair, "ReserveObserver: NOT_PAIR");
+            self.reserves = (reserve0, reserve1);
+            self.sync_count += 1;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .unwrap_or([0x0; 32].into());
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                caller,
+                callee,
+                1000000,
+                1000000,
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])),
+            );
+        }
+
+        #[ink::test]
+        fn updates_are_recorded() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let mut observer = ReserveObserver::new(accounts.django);
+            assert_eq!(observer.pair(), accounts.django);
+            assert_eq!(observer.sync_count(), 0);
+
+            set_caller(accounts.django);
+            observer.on_sync(1_000, 2_000);
+            observer.on_sync(1_100, 1_820);
+            ink_env::test::pop_execution_context();
+
+            assert_eq!(observer.reserves(), (1_100, 1_820));
+            assert_eq!(observer.sync_count(), 2);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "ReserveObserver: NOT_PAIR")]
+        fn updates_from_other_accounts_trap() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            // Alice calls, but only Django is the pair.
+            let mut observer = ReserveObserver::new(accounts.django);
+            observer.on_sync(1_000, 2_000);
+        }
+    }
+}
@@ -0,0 +1,603 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+mod math;
+
+#[ink::contract]
+mod stable_pair {
+    use crate::math;
+    use erc20::{
+        BaseErc20,
+        Erc20,
+    };
+    use ink_env::call::FromAccountId;
+    #[cfg(not(feature = "ink-as-dependency"))]
+    use ink_storage::{
+        collections::HashMap as StorageHashMap,
+        lazy::Lazy,
+    };
+
+    /// The largest amplification coefficient.
+    pub const MAX_A: u128 = 1_000_000;
+
+    /// The largest factor by which a single ramp may change `A`.
+    pub const MAX_A_CHANGE: u128 = 10;
+
+    /// The shortest duration of a ramp.
+    pub const MIN_RAMP_TIME: Timestamp = 86_400_000;
+
+    /// The swap fee in units of `math::FEE_DENOMINATOR`, i.e. 0.04%.
+    pub const FEE: u128 = 4;
+
+    /// The stable pair error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the caller is not the owner.
+        NotOwner,
+        /// Returned if `A` is zero, above `MAX_A` or changes by more than `MAX_A_CHANGE`.
+        InvalidAmplification,
+        /// Returned if a ramp ends less than `MIN_RAMP_TIME` from now.
+        RampTooShort,
+        /// Returned if an amount is zero.
+        ZeroAmount,
+        /// Returned if the output of a swap is less than requested.
+        InsufficientOutputAmount,
+        /// Returned if a deposit or withdrawal would mint or return nothing.
+        InsufficientLiquidity,
+        /// Returned if not enough LP tokens are available.
+        InsufficientBalance,
+        /// Returned if the invariant cannot be computed.
+        MathFailed,
+        /// Returned if a token transfer failed.
+        TransferFailed,
+    }
+
+    /// The stable pair result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A pair of like-valued tokens priced by the StableSwap invariant.
+    ///
+    /// The amplification coefficient `A` moves the curve between constant
+    /// product (`A = 0`) and constant sum (`A = ∞`). The owner can ramp it
+    /// linearly from `initial_a` at `initial_a_time` to `future_a` at
+    /// `future_a_time`.
+    ///
+    /// The pair is a standalone contract: it has a single owner instead of the
+    /// roles of the uniswap pair and implements no trait shared with it.
+    #[ink(storage)]
+    pub struct StablePair {
+        /// The account allowed to ramp `A`.
+        owner: AccountId,
+        token0: Lazy<Erc20>,
+        token1: Lazy<Erc20>,
+
+        reserve0: Balance,
+        reserve1: Balance,
+
+        /// `A` at the start of the current ramp.
+        initial_a: u128,
+        /// `A` at the end of the current ramp.
+        future_a: u128,
+        /// The start of the current ramp.
+        initial_a_time: Timestamp,
+        /// The end of the current ramp.
+        future_a_time: Timestamp,
+
+        /// Total LP token supply.
+        total_supply: Balance,
+        /// Mapping from owner to number of owned LP tokens.
+        balances: StorageHashMap<AccountId, Balance>,
+    }
+
+    /// Event emitted when liquidity is added to the pair.
+    #[ink(event)]
+    pub struct Mint {
+        #[ink(topic)]
+        sender: AccountId,
+        amount0: Balance,
+        amount1: Balance,
+        liquidity: Balance,
+    }
+
+    /// Event emitted when liquidity is removed from the pair.
+    #[ink(event)]
+    pub struct Burn {
+        #[ink(topic)]
+        sender: AccountId,
+        amount0: Balance,
+        amount1: Balance,
+        liquidity: Balance,
+    }
+
+    /// Event emitted when tokens are swapped.
+    #[ink(event)]
+    pub struct Swap {
+        #[ink(topic)]
+        sender: AccountId,
+        amount0_in: Balance,
+        amount1_in: Balance,
+        amount0_out: Balance,
+        amount1_out: Balance,
+        #[ink(topic)]
+        to: AccountId,
+    }
+
+    /// Event emitted when the owner starts ramping `A`.
+    #[ink(event)]
+    pub struct RampA {
+        old_a: u128,
+        new_a: u128,
+        initial_time: Timestamp,
+        future_time: Timestamp,
+    }
+
+    /// Event emitted when the owner stops ramping `A`.
+    #[ink(event)]
+    pub struct StopRampA {
+        a: u128,
+        time: Timestamp,
+    }
+
+    impl StablePair {
+        /// Creates a new stable pair of `token0` and `token1` with the
+        /// amplification coefficient `a`.
+        ///
+        /// # Panics
+        ///
+        /// If `a` is zero or above `MAX_A`.
+        #[ink(constructor)]
+        pub fn new(token0: AccountId, token1: AccountId, a: u128) -> Self {
+            assert!(a > 0 && a <= MAX_A, "StablePair: INVALID_A");
+            Self {
+                owner: Self::env().caller(),
+                token0: Lazy::new(FromAccountId::from_account_id(token0)),
+                token1: Lazy::new(FromAccountId::from_account_id(token1)),
+                reserve0: 0,
+                reserve1: 0,
+                initial_a: a,
+                future_a: a,
+                initial_a_time: 0,
+                future_a_time: 0,
+                total_supply: 0,
+                balances: StorageHashMap::new(),
+            }
+        }
+
+        /// Returns the current amplification coefficient.
+        #[ink(message)]
+        pub fn a(&self) -> u128 {
+            let now = self.env().block_timestamp();
+            if now >= self.future_a_time {
+                return self.future_a
+            }
+            let elapsed = (now - self.initial_a_time) as u128;
+            let duration = (self.future_a_time - self.initial_a_time) as u128;
+            if self.future_a > self.initial_a {
+                self.initial_a + (self.future_a - self.initial_a) * elapsed / duration
+            } else {
+                self.initial_a - (self.initial_a - self.future_a) * elapsed / duration
+            }
+        }
+
+        /// Ramps `A` linearly from its current value to `future_a` at `future_time`.
+        ///
+        /// On success a `RampA` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotOwner` if the caller is not the owner.
+        /// - Returns `RampTooShort` if `future_time` is less than `MIN_RAMP_TIME` away.
+        /// - Returns `InvalidAmplification` if `future_a` is zero, above `MAX_A` or
+        ///   changes `A` by more than a factor of `MAX_A_CHANGE`.
+        #[ink(message)]
+        pub fn ramp_a(&mut self, future_a: u128, future_time: Timestamp) -> Result<()> {
+            self.ensure_owner()?;
+            let now = self.env().block_timestamp();
+            if future_time < now.saturating_add(MIN_RAMP_TIME) {
+                return Err(Error::RampTooShort)
+            }
+            let initial_a = self.a();
+            if future_a == 0
+                || future_a > MAX_A
+                || future_a > initial_a * MAX_A_CHANGE
+                || future_a * MAX_A_CHANGE < initial_a
+            {
+                return Err(Error::InvalidAmplification)
+            }
+            self.initial_a = initial_a;
+            self.future_a = future_a;
+            self.initial_a_time = now;
+            self.future_a_time = future_time;
+            self.env().emit_event(RampA {
+                old_a: initial_a,
+                new_a: future_a,
+                initial_time: now,
+                future_time,
+            });
+            Ok(())
+        }
+
+        /// Stops the current ramp, keeping `A` at its current value.
+        ///
+        /// On success a `StopRampA` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotOwner` if the caller is not the owner.
+        #[ink(message)]
+        pub fn stop_ramp_a(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            let now = self.env().block_timestamp();
+            let a = self.a();
+            self.initial_a = a;
+            self.future_a = a;
+            self.initial_a_time = now;
+            self.future_a_time = now;
+            self.env().emit_event(StopRampA { a, time: now });
+            Ok(())
+        }
+
+        /// Returns the reserves of token0 and token1.
+        #[ink(message)]
+        pub fn get_reserves(&self) -> (Balance, Balance) {
+            (self.reserve0, self.reserve1)
+        }
+
+        /// Returns `(amount_out, amount_in)` for swapping `amount_in` of token0
+        /// (`zero_for_one`) or token1 against the current reserves.
+        #[ink(message)]
+        pub fn execution_price(
+            &self,
+            amount_in: Balance,
+            zero_for_one: bool,
+        ) -> Result<(Balance, Balance)> {
+            let (amount_out, _, _) = self.quote_exact_in(amount_in, zero_for_one, 0)?;
+            Ok((amount_out, amount_in))
+        }
+
+        /// Swaps exactly `amount_in` of token0 (`zero_for_one`) or token1 for the
+        /// other token and sends the output to `to`.
+        ///
+        /// The caller must have approved the pair to spend `amount_in`.
+        ///
+        /// Returns the amount of the other token sent to `to`.
+        #[ink(message)]
+        pub fn swap_exact_in(
+            &mut self,
+            amount_in: Balance,
+            zero_for_one: bool,
+            amount_out_min: Balance,
+            to: AccountId,
+        ) -> Result<Balance> {
+            let (amount_out, reserve0, reserve1) =
+                self.quote_exact_in(amount_in, zero_for_one, amount_out_min)?;
+            let sender = self.env().caller();
+            let self_account_id = self.env().account_id();
+            let (token_in, token_out) = if zero_for_one {
+                (&mut *self.token0, &mut *self.token1)
+            } else {
+                (&mut *self.token1, &mut *self.token0)
+            };
+            token_in
+                .transfer_from(sender, self_account_id, amount_in)
+                .map_err(|_| Error::TransferFailed)?;
+            token_out
+                .transfer(to, amount_out)
+                .map_err(|_| Error::TransferFailed)?;
+            self.reserve0 = reserve0;
+            self.reserve1 = reserve1;
+            let (amount0_in, amount1_in, amount0_out, amount1_out) = if zero_for_one {
+                (amount_in, 0, 0, amount_out)
+            } else {
+                (0, amount_in, amount_out, 0)
+            };
+            self.env().emit_event(Swap {
+                sender,
+                amount0_in,
+                amount1_in,
+                amount0_out,
+                amount1_out,
+                to,
+            });
+            Ok(amount_out)
+        }
+
+        /// Deposits `amount0` of token0 and `amount1` of token1 in exchange for
+        /// LP tokens proportional to the growth of the invariant `D`.
+        ///
+        /// Returns the amount of LP tokens minted to the caller.
+        #[ink(message)]
+        pub fn add_liquidity(&mut self, amount0: Balance, amount1: Balance) -> Result<Balance> {
+            let (liquidity, reserve0, reserve1) = self.quote_add_liquidity(amount0, amount1)?;
+            let sender = self.env().caller();
+            let self_account_id = self.env().account_id();
+            self.token0
+                .transfer_from(sender, self_account_id, amount0)
+                .map_err(|_| Error::TransferFailed)?;
+            self.token1
+                .transfer_from(sender, self_account_id, amount1)
+                .map_err(|_| Error::TransferFailed)?;
+            self.reserve0 = reserve0;
+            self.reserve1 = reserve1;
+            self.total_supply += liquidity;
+            let balance = self.balance_of(sender);
+            self.balances.insert(sender, balance + liquidity);
+            self.env().emit_event(Mint {
+                sender,
+                amount0,
+                amount1,
+                liquidity,
+            });
+            Ok(liquidity)
+        }
+
+        /// Burns `liquidity` LP tokens of the caller in exchange for the
+        /// proportional share of both reserves.
+        ///
+        /// Returns the amounts of token0 and token1 sent to the caller.
+        #[ink(message)]
+        pub fn remove_liquidity(&mut self, liquidity: Balance) -> Result<(Balance, Balance)> {
+            let sender = self.env().caller();
+            let balance = self.balance_of(sender);
+            if balance < liquidity {
+                return Err(Error::InsufficientBalance)
+            }
+            let amount0 = math::mul_div(liquidity, self.reserve0, self.total_supply)
+                .ok_or(Error::InsufficientLiquidity)?;
+            let amount1 = math::mul_div(liquidity, self.reserve1, self.total_supply)
+                .ok_or(Error::InsufficientLiquidity)?;
+            if amount0 == 0 && amount1 == 0 {
+                return Err(Error::InsufficientLiquidity)
+            }
+            self.balances.insert(sender, balance - liquidity);
+            self.total_supply -= liquidity;
+            self.reserve0 -= amount0;
+            self.reserve1 -= amount1;
+            self.token0
+                .transfer(sender, amount0)
+                .map_err(|_| Error::TransferFailed)?;
+            self.token1
+                .transfer(sender, amount1)
+                .map_err(|_| Error::TransferFailed)?;
+            self.env().emit_event(Burn {
+                sender,
+                amount0,
+                amount1,
+                liquidity,
+            });
+            Ok((amount0, amount1))
+        }
+
+        /// Returns the total LP token supply.
+        #[ink(message)]
+        pub fn total_supply(&self) -> Balance {
+            self.total_supply
+        }
+
+        /// Returns the LP tokens of `owner`.
+        #[ink(message)]
+        pub fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(&owner).copied().unwrap_or(0)
+        }
+
+        /// Returns `(amount_out, reserve0, reserve1)` after swapping exactly
+        /// `amount_in` of token0 (`zero_for_one`) or token1.
+        fn quote_exact_in(
+            &self,
+            amount_in: Balance,
+            zero_for_one: bool,
+            amount_out_min: Balance,
+        ) -> Result<(Balance, Balance, Balance)> {
+            if amount_in == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            let (reserve_in, reserve_out) = if zero_for_one {
+                (self.reserve0, self.reserve1)
+            } else {
+                (self.reserve1, self.reserve0)
+            };
+            if reserve_in == 0 || reserve_out == 0 {
+                return Err(Error::InsufficientLiquidity)
+            }
+            let amount_out =
+                math::get_amount_out(amount_in, reserve_in, reserve_out, self.a(), FEE)
+                    .ok_or(Error::MathFailed)?;
+            if amount_out < amount_out_min || amount_out == 0 {
+                return Err(Error::InsufficientOutputAmount)
+            }
+            let balance_in = reserve_in + amount_in;
+            let balance_out = reserve_out - amount_out;
+            if zero_for_one {
+                Ok((amount_out, balance_in, balance_out))
+            } else {
+                Ok((amount_out, balance_out, balance_in))
+            }
+        }
+
+        /// Returns `(liquidity, reserve0, reserve1)` after depositing `amount0`
+        /// and `amount1`.
+        fn quote_add_liquidity(
+            &self,
+            amount0: Balance,
+            amount1: Balance,
+        ) -> Result<(Balance, Balance, Balance)> {
+            let amp = self.a();
+            let d0 = math::get_d([self.reserve0, self.reserve1], amp)
+                .ok_or(Error::MathFailed)?;
+            let reserve0 = self.reserve0.checked_add(amount0).ok_or(Error::MathFailed)?;
+            let reserve1 = self.reserve1.checked_add(amount1).ok_or(Error::MathFailed)?;
+            let d1 = math::get_d([reserve0, reserve1], amp).ok_or(Error::MathFailed)?;
+            let liquidity = if self.total_supply == 0 {
+                d1
+            } else {
+                math::mul_div(self.total_supply, d1 - d0, d0).ok_or(Error::MathFailed)?
+            };
+            if liquidity == 0 {
+                return Err(Error::InsufficientLiquidity)
+            }
+            Ok((liquidity, reserve0, reserve1))
+        }
+
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            Ok(())
+        }
+    }
+
+    /// Unit tests.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+
+        const E: Balance = 1_000_000_000_000_000_000;
+
+        fn new_pair(a: u128, reserve0: Balance, reserve1: Balance) -> StablePair {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let mut pair = StablePair::new(accounts.django, accounts.eve, a);
+            pair.reserve0 = reserve0;
+            pair.reserve1 = reserve1;
+            pair
+        }
+
+        fn advance_block() {
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "StablePair: INVALID_A")]
+        fn zero_a_construction_fails() {
+            new_pair(0, 0, 0);
+        }
+
+        #[ink::test]
+        fn execution_price_matches_reference() {
+            let pair = new_pair(100, 1_000 * E, 1_000 * E);
+            assert_eq!(
+                pair.execution_price(10 * E, true),
+                Ok((9_995_010_298_009_604_961, 10 * E))
+            );
+            let pair = new_pair(100, 500 * E, 1_500 * E);
+            assert_eq!(
+                pair.execution_price(10 * E, true),
+                Ok((10_167_278_146_829_613_416, 10 * E))
+            );
+            assert_eq!(
+                pair.execution_price(10 * E, false),
+                Ok((9_819_795_496_641_997_967, 10 * E))
+            );
+        }
+
+        #[ink::test]
+        fn quote_exact_in_enforces_min_output() {
+            let pair = new_pair(100, 1_000 * E, 1_000 * E);
+            assert_eq!(
+                pair.quote_exact_in(10 * E, true, 10 * E),
+                Err(Error::InsufficientOutputAmount)
+            );
+            assert_eq!(
+                pair.quote_exact_in(10 * E, false, 9 * E),
+                Ok((9_995_010_298_009_604_961, 990_004_989_701_990_395_039, 1_010 * E))
+            );
+        }
+
+        #[ink::test]
+        fn swaps_without_liquidity_fail() {
+            let pair = new_pair(100, 0, 0);
+            assert_eq!(
+                pair.execution_price(E, true),
+                Err(Error::InsufficientLiquidity)
+            );
+        }
+
+        #[ink::test]
+        fn add_liquidity_mints_invariant_growth() {
+            let mut pair = new_pair(100, 0, 0);
+            assert_eq!(
+                pair.quote_add_liquidity(1_000 * E, 1_000 * E),
+                Ok((2_000 * E, 1_000 * E, 1_000 * E))
+            );
+            pair.reserve0 = 1_000 * E;
+            pair.reserve1 = 1_000 * E;
+            pair.total_supply = 2_000 * E;
+            // A balanced deposit of 10% mints 10% of the supply.
+            assert_eq!(
+                pair.quote_add_liquidity(100 * E, 100 * E),
+                Ok((200 * E, 1_100 * E, 1_100 * E))
+            );
+        }
+
+        #[ink::test]
+        fn ramp_a_works() {
+            let mut pair = new_pair(100, 0, 0);
+            assert_eq!(pair.ramp_a(200, MIN_RAMP_TIME), Ok(()));
+            assert_eq!(pair.a(), 100);
+            // Shorten the ramp to two blocks of 5 time units.
+            pair.future_a_time = 10;
+            advance_block();
+            assert_eq!(pair.a(), 150);
+            advance_block();
+            assert_eq!(pair.a(), 200);
+        }
+
+        #[ink::test]
+        fn ramp_a_can_be_stopped() {
+            let mut pair = new_pair(100, 0, 0);
+            assert_eq!(pair.ramp_a(10, MIN_RAMP_TIME), Ok(()));
+            pair.future_a_time = 10;
+            advance_block();
+            assert_eq!(pair.stop_ramp_a(), Ok(()));
+            advance_block();
+            assert_eq!(pair.a(), 55);
+        }
+
+        #[ink::test]
+        fn ramp_a_validates_input() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let mut pair = new_pair(100, 0, 0);
+            assert_eq!(pair.ramp_a(200, MIN_RAMP_TIME - 1), Err(Error::RampTooShort));
+            assert_eq!(
+                pair.ramp_a(1_001, MIN_RAMP_TIME),
+                Err(Error::InvalidAmplification)
+            );
+            assert_eq!(pair.ramp_a(9, MIN_RAMP_TIME), Err(Error::InvalidAmplification));
+            assert_eq!(pair.ramp_a(0, MIN_RAMP_TIME), Err(Error::InvalidAmplification));
+
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .unwrap_or([0x0; 32].into());
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                accounts.bob,
+                callee,
+                1000000,
+                1000000,
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])),
+            );
+            assert_eq!(pair.ramp_a(200, MIN_RAMP_TIME), Err(Error::NotOwner));
+            assert_eq!(pair.stop_ramp_a(), Err(Error::NotOwner));
+        }
+    }
+}
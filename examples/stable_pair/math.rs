@@ -0,0 +1,194 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! StableSwap arithmetic for two token pools, following Curve's `get_D` and `get_y`.
+//!
+//! The invariant is
+//!
+//! `A * n^n * (x + y) + D = A * n^n * D + D^(n + 1) / (n^n * x * y)`
+//!
+//! with `n = 2`. Products of two balances and the constant term of `get_y` are
+//! computed with 256 bits. Functions return `None` on overflow
+//! or if Newton's method does not converge.
+
+pub use full_math::mul_div;
+use full_math::{
+    div_rem_wide,
+    full_mul,
+    to_u128,
+};
+
+/// The balance type of the default environment.
+pub type Balance = u128;
+
+/// The number of tokens in a pool.
+pub const N_COINS: u128 = 2;
+
+/// The maximum number of Newton iterations.
+pub const MAX_ITERATIONS: usize = 255;
+
+/// The denominator of the swap fee.
+pub const FEE_DENOMINATOR: u128 = 10_000;
+
+/// Returns `|a - b| <= 1`.
+fn converged(a: u128, b: u128) -> bool {
+    if a > b {
+        a - b <= 1
+    } else {
+        b - a <= 1
+    }
+}
+
+/// Returns the invariant `D` of the reserves `xp` for the amplification `amp`.
+pub fn get_d(xp: [Balance; 2], amp: u128) -> Option<Balance> {
+    let s = xp[0].checked_add(xp[1])?;
+    if s == 0 {
+        return Some(0)
+    }
+    if xp[0] == 0 || xp[1] == 0 || amp == 0 {
+        return None
+    }
+    let ann = amp.checked_mul(N_COINS)?;
+    let mut d = s;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for &x in &xp {
+            d_p = mul_div(d_p, d, x.checked_mul(N_COINS)?)?;
+        }
+        let d_prev = d;
+        let numerator = ann.checked_mul(s)?.checked_add(d_p.checked_mul(N_COINS)?)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)?
+            .checked_add((N_COINS + 1).checked_mul(d_p)?)?;
+        d = mul_div(numerator, d, denominator)?;
+        if converged(d, d_prev) {
+            return Some(d)
+        }
+    }
+    None
+}
+
+/// Returns the reserve of the other token that keeps the invariant `d` for
+/// the amplification `amp` when one reserve is `x`.
+pub fn get_y(x: Balance, d: Balance, amp: u128) -> Option<Balance> {
+    if x == 0 || amp == 0 {
+        return None
+    }
+    let ann = amp.checked_mul(N_COINS)?;
+    // `c = D^3 / (n^n * x * Ann)` only fits into 256 bits.
+    let c = mul_div(d, d, x.checked_mul(N_COINS)?)?;
+    let ((c_high, c_low), _) =
+        div_rem_wide(full_mul(c, d), ann.checked_mul(N_COINS)?)?;
+    let b = x.checked_add(d / ann)?;
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        // `y = (y^2 + c) / (2 * y + b - D)`
+        let (square_high, square_low) = full_mul(y, y);
+        let (low, carry) = square_low.overflowing_add(c_low);
+        let high = square_high.checked_add(c_high)?.checked_add(carry as u128)?;
+        let denominator = y.checked_mul(2)?.checked_add(b)?.checked_sub(d)?;
+        y = to_u128(div_rem_wide((high, low), denominator)?.0)?;
+        if converged(y, y_prev) {
+            return Some(y)
+        }
+    }
+    None
+}
+
+/// Returns the amount of the out token received for `amount_in` of the in token
+/// after the swap fee of `fee` / `FEE_DENOMINATOR`.
+pub fn get_amount_out(
+    amount_in: Balance,
+    reserve_in: Balance,
+    reserve_out: Balance,
+    amp: u128,
+    fee: u128,
+) -> Option<Balance> {
+    let d = get_d([reserve_in, reserve_out], amp)?;
+    let y = get_y(reserve_in.checked_add(amount_in)?, d, amp)?;
+    // Round against the swapper.
+    let dy = reserve_out.checked_sub(y)?.checked_sub(1)?;
+    Some(dy - dy * fee / FEE_DENOMINATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E: u128 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn get_d_matches_reference() {
+        // Balanced reserves always have `D = x + y`.
+        for &amp in &[1, 10, 100, 1_000] {
+            assert_eq!(get_d([1_000 * E, 1_000 * E], amp), Some(2_000 * E));
+        }
+        assert_eq!(get_d([500 * E, 1_500 * E], 1), Some(1_858_889_071_871_241_812_902));
+        assert_eq!(get_d([500 * E, 1_500 * E], 10), Some(1_970_941_422_194_581_026_122));
+        assert_eq!(get_d([500 * E, 1_500 * E], 100), Some(1_996_715_821_544_259_128_824));
+        assert_eq!(get_d([500 * E, 1_500 * E], 1_000), Some(1_999_667_165_807_183_436_754));
+        assert_eq!(get_d([0, 0], 100), Some(0));
+        assert_eq!(get_d([0, E], 100), None);
+    }
+
+    #[test]
+    fn get_y_inverts_get_d() {
+        let d = get_d([500 * E, 1_500 * E], 100).unwrap();
+        assert_eq!(get_y(500 * E, d, 100), Some(1_499_999_999_999_999_999_999));
+    }
+
+    #[test]
+    fn get_amount_out_matches_reference() {
+        let cases = [
+            (1, 1_000, 1_000, 9_946_267_425_773_348_596),
+            (10, 1_000, 1_000, 9_986_920_231_901_108_720),
+            (100, 1_000, 1_000, 9_995_010_298_009_604_961),
+            (1_000, 1_000, 1_000, 9_995_900_130_890_966_828),
+            (1, 500, 1_500, 18_134_594_041_578_699_152),
+            (100, 500, 1_500, 10_167_278_146_829_613_416),
+            (1, 1_500, 500, 5_422_704_469_388_849_816),
+            (100, 1_500, 500, 9_819_795_496_641_997_967),
+        ];
+        for &(amp, reserve_in, reserve_out, expected) in &cases {
+            assert_eq!(
+                get_amount_out(10 * E, reserve_in * E, reserve_out * E, amp, 4),
+                Some(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn amplification_flattens_the_curve() {
+        // The constant product curve returns 9.90 for 10 in a balanced pool.
+        let constant_product = 1_000 * 10 * E / 1_010;
+        let mut previous = 0;
+        for &amp in &[1, 10, 100, 1_000] {
+            let out = get_amount_out(10 * E, 1_000 * E, 1_000 * E, amp, 0).unwrap();
+            assert!(out > constant_product && out > previous && out < 10 * E);
+            previous = out;
+        }
+    }
+
+    #[test]
+    fn large_balances_do_not_overflow() {
+        // `c` and `y^2` exceed 128 bits.
+        let reserve = 100_000_000_000_000_000 * E;
+        assert_eq!(get_d([reserve, reserve], 100), Some(2 * reserve));
+        assert_eq!(
+            get_amount_out(1_000 * E, reserve, reserve, 100, 4),
+            Some(999_599_999_999_999_901_030)
+        );
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Stop Loss
+//!
+//! Sells a fixed amount of a token on a uniswap pair once its price drops to a
+//! trigger.
+//!
+//! ## Warning
+//!
+//! This contract is an *example*. It is neither audited nor endorsed for production use.
+//! Do **not** rely on it to keep anything of value secure.
+//!
+//! ## Overview
+//!
+//! The owner creates an order and funds it by transferring `amount_in` of
+//! `token_in` to it. Any keeper may then call `execute`, which quotes the swap
+//! of the order on the pair and performs it if the output has dropped to
+//! `trigger_amount_out` or below. The keeper earns `keeper_fee_bps` of
+//! `amount_in`, the rest is swapped and the output is sent to `recipient`.
+//!
+//! The pair has no square root price, so the trigger is the output of the
+//! whole order after the fee and the price impact, which is the price the
+//! order really executes at. The trigger price is implied as
+//! `trigger_amount_out / amount_in`. Since the quote and the swap happen in
+//! the same call, the quoted output is also the minimum the swap accepts.
+//!
+//! Until the order executes the owner can `cancel` it and get the tokens back.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::stop_loss::StopLossOrder;
+use ink_lang as ink;
+
+#[ink::contract]
+pub mod stop_loss {
+    use erc20::{
+        BaseErc20,
+        Erc20,
+    };
+    use ink_env::call::{
+        build_call,
+        utils::ReturnType,
+        ExecutionInput,
+        FromAccountId,
+        Selector,
+    };
+
+    /// The selector of the `execution_price` message of a uniswap pair.
+    const EXECUTION_PRICE_SELECTOR: [u8; 4] = [0xad, 0xca, 0x07, 0xb6];
+
+    /// The selector of the `swap_exact_in` message of a uniswap pair.
+    const SWAP_EXACT_IN_SELECTOR: [u8; 4] = [0xcc, 0x6d, 0xdf, 0x0d];
+
+    /// The largest keeper fee in basis points.
+    pub const MAX_KEEPER_FEE_BPS: u16 = 1_000;
+
+    /// The stop loss error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the caller is not the owner of the order.
+        NotOwner,
+        /// Returned if the order has already been executed or cancelled.
+        Inactive,
+        /// Returned if the order would still pay out more than the trigger.
+        NotTriggered { amount_out: Balance },
+    }
+
+    /// The stop loss result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Event emitted when a keeper executes the order.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Executed {
+        #[ink(topic)]
+        keeper: AccountId,
+        amount_out: Balance,
+        keeper_fee: Balance,
+    }
+
+    /// Event emitted when the owner cancels the order.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Cancelled {
+        #[ink(topic)]
+        owner: AccountId,
+    }
+
+    /// An order selling `amount_in` of `token_in` once the swap pays out at most
+    /// `trigger_amount_out`.
+    #[ink(storage)]
+    pub struct StopLossOrder {
+        /// The pair the order is swapped on.
+        pair: AccountId,
+        /// The token sold, token0 of the pair if `zero_for_one`, else token1.
+        token_in: AccountId,
+        /// Whether `token_in` is token0 of the pair.
+        zero_for_one: bool,
+        /// The amount of `token_in` locked in the order, including the keeper
+        /// fee.
+        amount_in: Balance,
+        /// The output of the swap at or below which the order executes.
+        trigger_amount_out: Balance,
+        /// The account receiving the output of the swap.
+        recipient: AccountId,
+        /// The account that can cancel the order.
+        owner: AccountId,
+        /// The share of `amount_in` paid to the keeper, in basis points.
+        keeper_fee_bps: u16,
+        /// Whether the order can still be executed or cancelled.
+        active: bool,
+    }
+
+    impl StopLossOrder {
+        /// Creates an order owned by the caller, see the crate documentation.
+        ///
+        /// # Panics
+        ///
+        /// If `keeper_fee_bps` exceeds `MAX_KEEPER_FEE_BPS`.
+        #[ink(constructor)]
+        pub fn new(
+            pair: AccountId,
+            token_in: AccountId,
+            zero_for_one: bool,
+            amount_in: Balance,
+            trigger_amount_out: Balance,
+            recipient: AccountId,
+            keeper_fee_bps: u16,
+        ) -> Self {
+            assert!(
+                keeper_fee_bps <= MAX_KEEPER_FEE_BPS,
+                "StopLoss: INVALID_KEEPER_FEE"
+            );
+            Self {
+                pair,
+                token_in,
+                zero_for_one,
+                amount_in,
+                trigger_amount_out,
+                recipient,
+                owner: Self::env().caller(),
+                keeper_fee_bps,
+                active: true,
+            }
+        }
+
+        /// Returns the pair the order is swapped on.
+        #[ink(message)]
+        pub fn pair(&self) -> AccountId {
+            self.pair
+        }
+
+        /// Returns the output of the swap at or below which the order executes.
+        #[ink(message)]
+        pub fn trigger_amount_out(&self) -> Balance {
+            self.trigger_amount_out
+        }
+
+        /// Returns the account that can cancel the order.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Returns whether the order can still be executed or cancelled.
+        #[ink(message)]
+        pub fn active(&self) -> bool {
+            self.active
+        }
+
+        /// Swaps the order if its output has dropped to the trigger, pays the
+        /// keeper fee to the caller and sends the output to the recipient.
+        ///
+        /// On success an `Executed` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `Inactive` if the order was executed or cancelled.
+        /// - Returns `NotTriggered` if the swap would pay out more than
+        ///   `trigger_amount_out`.
+        ///
+        /// # Panics
+        ///
+        /// If a call into the pair or the token fails.
+        #[ink(message)]
+        pub fn execute(&mut self) -> Result<()> {
+            self.ensure_active()?;
+            let (keeper_fee, swap_amount) = self.split_amount_in();
+            let (amount_out, _) = build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(self.pair)
+                .gas_limit(0)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(EXECUTION_PRICE_SELECTOR))
+                        .push_arg(&swap_amount)
+                        .push_arg(&self.zero_for_one),
+                )
+                .returns::<ReturnType<(Balance, Balance)>>()
+                .fire()
+                .expect("StopLoss: QUOTE_FAILED");
+            self.ensure_triggered(amount_out)?;
+            self.active = false;
+
+            let keeper = self.env().caller();
+            let mut token_in: Erc20 = FromAccountId::from_account_id(self.token_in);
+            if keeper_fee > 0 {
+                assert!(
+                    token_in.transfer(keeper, keeper_fee).is_ok(),
+                    "StopLoss: TRANSFER_FAILED"
+                );
+            }
+            assert!(
+                token_in.approve(self.pair, swap_amount).is_ok(),
+                "StopLoss: APPROVE_FAILED"
+            );
+            // The swap returns errors instead of reverting, so it only counts
+            // as done once the pair has taken the tokens.
+            let swapped = build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(self.pair)
+                .gas_limit(0)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(SWAP_EXACT_IN_SELECTOR))
+                        .push_arg(&swap_amount)
+                        .push_arg(&self.zero_for_one)
+                        .push_arg(&amount_out)
+                        .push_arg(&self.recipient),
+                )
+                .returns::<()>()
+                .fire();
+            let self_account_id = self.env().account_id();
+            assert!(
+                swapped.is_ok() && token_in.allowance(self_account_id, self.pair) == 0,
+                "StopLoss: SWAP_FAILED"
+            );
+            self.env().emit_event(Executed {
+                keeper,
+                amount_out,
+                keeper_fee,
+            });
+            Ok(())
+        }
+
+        /// Cancels the order and returns the locked tokens to the owner.
+        ///
+        /// On success a `Cancelled` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotOwner` if the caller is not the owner.
+        /// - Returns `Inactive` if the order was executed or cancelled.
+        ///
+        /// # Panics
+        ///
+        /// If the token transfer fails.
+        #[ink(message)]
+        pub fn cancel(&mut self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            self.ensure_active()?;
+            self.active = false;
+            let mut token_in: Erc20 = FromAccountId::from_account_id(self.token_in);
+            let balance = token_in.balance_of(self.env().account_id());
+            assert!(
+                token_in.transfer(self.owner, balance).is_ok(),
+                "StopLoss: TRANSFER_FAILED"
+            );
+            self.env().emit_event(Cancelled { owner: self.owner });
+            Ok(())
+        }
+
+        /// Returns `Inactive` if the order was executed or cancelled.
+        fn ensure_active(&self) -> Result<()> {
+            if !self.active {
+                return Err(Error::Inactive)
+            }
+            Ok(())
+        }
+
+        /// Returns `NotTriggered` if `amount_out` exceeds the trigger.
+        fn ensure_triggered(&self, amount_out: Balance) -> Result<()> {
+            if amount_out > self.trigger_amount_out {
+                return Err(Error::NotTriggered { amount_out })
+            }
+            Ok(())
+        }
+
+        /// Returns the keeper fee and the rest of `amount_in` that is swapped.
+        fn split_amount_in(&self) -> (Balance, Balance) {
+            let keeper_fee = self.amount_in * self.keeper_fee_bps as Balance / 10_000;
+            (keeper_fee, self.amount_in - keeper_fee)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .unwrap_or([0x0; 32].into());
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                caller,
+                callee,
+                1000000,
+                1000000,
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])),
+            );
+        }
+
+        /// Returns an order of Alice selling 10_000 token0 for at most 19_000
+        /// token1 with a keeper fee of 0.5%.
+        fn order() -> StopLossOrder {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            StopLossOrder::new(
+                accounts.django,
+                accounts.eve,
+                true,
+                10_000,
+                19_000,
+                accounts.bob,
+                50,
+            )
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let order = order();
+            assert_eq!(order.pair(), accounts.django);
+            assert_eq!(order.owner(), accounts.alice);
+            assert_eq!(order.trigger_amount_out(), 19_000);
+            assert!(order.active());
+            assert_eq!(order.split_amount_in(), (50, 9_950));
+        }
+
+        #[ink::test]
+        fn execute_waits_for_trigger() {
+            let order = order();
+            assert_eq!(
+                order.ensure_triggered(19_001),
+                Err(Error::NotTriggered { amount_out: 19_001 })
+            );
+            assert_eq!(order.ensure_triggered(19_000), Ok(()));
+            assert_eq!(order.ensure_triggered(12_345), Ok(()));
+        }
+
+        #[ink::test]
+        fn only_owner_cancels_active_orders() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let mut order = order();
+            set_caller(accounts.bob);
+            assert_eq!(order.cancel(), Err(Error::NotOwner));
+            ink_env::test::pop_execution_context();
+
+            // Executed or cancelled orders cannot be used again.
+            order.active = false;
+            assert_eq!(order.cancel(), Err(Error::Inactive));
+            assert_eq!(order.execute(), Err(Error::Inactive));
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "StopLoss: INVALID_KEEPER_FEE")]
+        fn new_fails_above_max_keeper_fee() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            StopLossOrder::new(
+                accounts.django,
+                accounts.eve,
+                true,
+                10_000,
+                19_000,
+                accounts.bob,
+                MAX_KEEPER_FEE_BPS + 1,
+            );
+        }
+    }
+}
@@ -0,0 +1,484 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Timelock Controller
+//!
+//! Delays calls to other contracts so that users can react to scheduled changes.
+//!
+//! ## Warning
+//!
+//! This contract is an *example*. It is neither audited nor endorsed for production use.
+//! Do **not** rely on it to keep anything of value secure.
+//!
+//! ## Overview
+//!
+//! Proposers `schedule` a call of `selector` with the SCALE encoded `input` on a
+//! `target` contract for a given `eta`. The `eta` has to lie at least `min_delay`
+//! in the future. Once the block timestamp has reached the `eta` an executor can
+//! `execute` the call, which is then dispatched with the timelock as the caller.
+//! Until then proposers can `cancel` it.
+//!
+//! Contracts governed by the timelock, for example the protocol parameters of a
+//! factory, should make the timelock the only account allowed to change them.
+//!
+//! ## Changing the Delay
+//!
+//! `update_delay` can only be called by the timelock itself and therefore has to
+//! go through the usual schedule and execute cycle.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::timelock::Timelock;
+use ink_lang as ink;
+
+#[ink::contract]
+mod timelock {
+    use ink_env::{
+        call::{
+            build_call,
+            ExecutionInput,
+        },
+        hash::Blake2x256,
+    };
+    use ink_prelude::vec::Vec;
+    #[cfg(not(feature = "ink-as-dependency"))]
+    use ink_storage::{
+        collections::HashMap as StorageHashMap,
+        Lazy,
+    };
+    use scale::Output;
+
+    /// The identifier of a scheduled call.
+    ///
+    /// This is the BLAKE2b-256 hash of the SCALE encoded `(target, selector, input, eta)`.
+    pub type OperationId = [u8; 32];
+
+    /// A wrapper that allows us to encode a blob of bytes.
+    ///
+    /// We use this to pass the set of untyped (bytes) parameters to the `CallBuilder`.
+    struct CallInput<'a>(&'a [u8]);
+
+    impl<'a> scale::Encode for CallInput<'a> {
+        fn encode_to<T: Output>(&self, dest: &mut T) {
+            dest.write(self.0);
+        }
+    }
+
+    /// The timelock error types.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the caller is not a proposer.
+        NotProposer,
+        /// Returned if the caller is not an executor.
+        NotExecutor,
+        /// Returned if the caller is not the timelock itself.
+        NotTimelock,
+        /// Returned if the `eta` lies less than `min_delay` in the future.
+        InsufficientDelay,
+        /// Returned if the same call has already been scheduled for `eta`.
+        AlreadyScheduled,
+        /// Returned if the call has not been scheduled or was already executed
+        /// or cancelled.
+        NotScheduled,
+        /// Returned if the `eta` of the call has not been reached yet.
+        NotReady,
+        /// Returned if the scheduled call failed.
+        CallFailed,
+    }
+
+    /// The timelock result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    #[ink(storage)]
+    pub struct Timelock {
+        /// The minimum time between scheduling and executing a call.
+        min_delay: Lazy<Timestamp>,
+        /// The accounts allowed to schedule and cancel calls.
+        proposers: StorageHashMap<AccountId, ()>,
+        /// The accounts allowed to execute calls once they are ready.
+        executors: StorageHashMap<AccountId, ()>,
+        /// Mapping from pending operation to its `eta`.
+        operations: StorageHashMap<OperationId, Timestamp>,
+    }
+
+    /// Emitted when a proposer schedules a call.
+    #[ink(event)]
+    pub struct CallScheduled {
+        /// The scheduled operation.
+        #[ink(topic)]
+        id: OperationId,
+        /// The contract that will be called.
+        #[ink(topic)]
+        target: AccountId,
+        /// The selector of the called message.
+        selector: [u8; 4],
+        /// The SCALE encoded parameters of the called message.
+        input: Vec<u8>,
+        /// The earliest time the call can be executed.
+        eta: Timestamp,
+    }
+
+    /// Emitted when an executor executes a call.
+    #[ink(event)]
+    pub struct CallExecuted {
+        /// The executed operation.
+        #[ink(topic)]
+        id: OperationId,
+    }
+
+    /// Emitted when a proposer cancels a call.
+    #[ink(event)]
+    pub struct Cancelled {
+        /// The cancelled operation.
+        #[ink(topic)]
+        id: OperationId,
+    }
+
+    /// Emitted when the minimum delay is changed.
+    #[ink(event)]
+    pub struct MinDelayChanged {
+        /// The previous minimum delay.
+        old_delay: Timestamp,
+        /// The new minimum delay.
+        new_delay: Timestamp,
+    }
+
+    impl Timelock {
+        /// Creates a new timelock with the given `min_delay`, `proposers` and `executors`.
+        #[ink(constructor)]
+        pub fn new(
+            min_delay: Timestamp,
+            proposers: Vec<AccountId>,
+            executors: Vec<AccountId>,
+        ) -> Self {
+            Self {
+                min_delay: Lazy::new(min_delay),
+                proposers: proposers.into_iter().map(|account| (account, ())).collect(),
+                executors: executors.into_iter().map(|account| (account, ())).collect(),
+                operations: StorageHashMap::new(),
+            }
+        }
+
+        /// Schedules a call of `selector` with `input` on `target` for `eta`.
+        ///
+        /// Returns the identifier of the scheduled operation.
+        ///
+        /// On success a `CallScheduled` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotProposer` if the caller is not a proposer.
+        /// - Returns `InsufficientDelay` if `eta` lies less than `min_delay` in the future.
+        /// - Returns `AlreadyScheduled` if the same call is already pending for `eta`.
+        #[ink(message)]
+        pub fn schedule(
+            &mut self,
+            target: AccountId,
+            selector: [u8; 4],
+            input: Vec<u8>,
+            eta: Timestamp,
+        ) -> Result<OperationId> {
+            self.ensure_proposer()?;
+            if eta < self.env().block_timestamp().saturating_add(*self.min_delay) {
+                return Err(Error::InsufficientDelay)
+            }
+            let id = self.operation_id(target, selector, input.clone(), eta);
+            if self.operations.contains_key(&id) {
+                return Err(Error::AlreadyScheduled)
+            }
+            self.operations.insert(id, eta);
+            self.env().emit_event(CallScheduled {
+                id,
+                target,
+                selector,
+                input,
+                eta,
+            });
+            Ok(id)
+        }
+
+        /// Executes the call of `selector` with `input` on `target` scheduled for `eta`.
+        ///
+        /// On success a `CallExecuted` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotExecutor` if the caller is not an executor.
+        /// - Returns `NotScheduled` if no such call is pending.
+        /// - Returns `NotReady` if the block timestamp has not reached `eta` yet.
+        /// - Returns `CallFailed` if the call itself failed.
+        #[ink(message)]
+        pub fn execute(
+            &mut self,
+            target: AccountId,
+            selector: [u8; 4],
+            input: Vec<u8>,
+            eta: Timestamp,
+        ) -> Result<()> {
+            self.ensure_executor()?;
+            let id = self.operation_id(target, selector, input.clone(), eta);
+            if !self.operations.contains_key(&id) {
+                return Err(Error::NotScheduled)
+            }
+            if self.env().block_timestamp() < eta {
+                return Err(Error::NotReady)
+            }
+            self.operations.take(&id);
+            build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(target)
+                .gas_limit(0)
+                .transferred_value(0)
+                .exec_input(ExecutionInput::new(selector.into()).push_arg(CallInput(&input)))
+                .returns::<()>()
+                .fire()
+                .map_err(|_| Error::CallFailed)?;
+            self.env().emit_event(CallExecuted { id });
+            Ok(())
+        }
+
+        /// Cancels the pending operation `id`.
+        ///
+        /// On success a `Cancelled` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotProposer` if the caller is not a proposer.
+        /// - Returns `NotScheduled` if `id` is not pending.
+        #[ink(message)]
+        pub fn cancel(&mut self, id: OperationId) -> Result<()> {
+            self.ensure_proposer()?;
+            self.operations.take(&id).ok_or(Error::NotScheduled)?;
+            self.env().emit_event(Cancelled { id });
+            Ok(())
+        }
+
+        /// Changes the minimum delay to `new_delay`.
+        ///
+        /// On success a `MinDelayChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotTimelock` if not called by the timelock itself.
+        #[ink(message)]
+        pub fn update_delay(&mut self, new_delay: Timestamp) -> Result<()> {
+            if self.env().caller() != self.env().account_id() {
+                return Err(Error::NotTimelock)
+            }
+            let old_delay = *self.min_delay;
+            *self.min_delay = new_delay;
+            self.env().emit_event(MinDelayChanged {
+                old_delay,
+                new_delay,
+            });
+            Ok(())
+        }
+
+        /// Returns the identifier of the call of `selector` with `input` on `target`
+        /// for `eta`.
+        #[ink(message)]
+        pub fn operation_id(
+            &self,
+            target: AccountId,
+            selector: [u8; 4],
+            input: Vec<u8>,
+            eta: Timestamp,
+        ) -> OperationId {
+            self.env()
+                .hash_encoded::<Blake2x256, _>(&(target, selector, input, eta))
+        }
+
+        /// Returns the `eta` of the pending operation `id`.
+        #[ink(message)]
+        pub fn eta(&self, id: OperationId) -> Option<Timestamp> {
+            self.operations.get(&id).copied()
+        }
+
+        /// Returns the minimum time between scheduling and executing a call.
+        #[ink(message)]
+        pub fn min_delay(&self) -> Timestamp {
+            *self.min_delay
+        }
+
+        /// Returns `true` if `account` is allowed to schedule and cancel calls.
+        #[ink(message)]
+        pub fn is_proposer(&self, account: AccountId) -> bool {
+            self.proposers.contains_key(&account)
+        }
+
+        /// Returns `true` if `account` is allowed to execute calls.
+        #[ink(message)]
+        pub fn is_executor(&self, account: AccountId) -> bool {
+            self.executors.contains_key(&account)
+        }
+
+        fn ensure_proposer(&self) -> Result<()> {
+            if !self.is_proposer(self.env().caller()) {
+                return Err(Error::NotProposer)
+            }
+            Ok(())
+        }
+
+        fn ensure_executor(&self) -> Result<()> {
+            if !self.is_executor(self.env().caller()) {
+                return Err(Error::NotExecutor)
+            }
+            Ok(())
+        }
+    }
+
+    /// Unit tests.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+
+        const DELAY: Timestamp = 10;
+        const TARGET: [u8; 32] = [0x42; 32];
+        const SET_FEE: [u8; 4] = [0xCA, 0xFE, 0xBA, 0xBE];
+
+        fn default_accounts() -> ink_env::test::DefaultAccounts<ink_env::DefaultEnvironment> {
+            ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get accounts")
+        }
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .unwrap_or([0x0; 32].into());
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                caller,
+                callee,
+                1000000,
+                1000000,
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])),
+            );
+        }
+
+        /// Alice proposes, Bob executes.
+        fn new_timelock() -> Timelock {
+            let accounts = default_accounts();
+            Timelock::new(DELAY, vec![accounts.alice], vec![accounts.bob])
+        }
+
+        fn schedule_set_fee(timelock: &mut Timelock, eta: Timestamp) -> Result<OperationId> {
+            timelock.schedule(TARGET.into(), SET_FEE, 25u16.to_le_bytes().to_vec(), eta)
+        }
+
+        fn execute_set_fee(timelock: &mut Timelock, eta: Timestamp) -> Result<()> {
+            timelock.execute(TARGET.into(), SET_FEE, 25u16.to_le_bytes().to_vec(), eta)
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let accounts = default_accounts();
+            let timelock = new_timelock();
+            assert_eq!(timelock.min_delay(), DELAY);
+            assert!(timelock.is_proposer(accounts.alice));
+            assert!(!timelock.is_proposer(accounts.bob));
+            assert!(timelock.is_executor(accounts.bob));
+            assert!(!timelock.is_executor(accounts.alice));
+        }
+
+        #[ink::test]
+        fn schedule_works() {
+            let mut timelock = new_timelock();
+            let id = schedule_set_fee(&mut timelock, DELAY).expect("Cannot schedule");
+            assert_eq!(timelock.eta(id), Some(DELAY));
+            assert_eq!(
+                id,
+                timelock.operation_id(
+                    TARGET.into(),
+                    SET_FEE,
+                    25u16.to_le_bytes().to_vec(),
+                    DELAY
+                )
+            );
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+            assert_eq!(
+                schedule_set_fee(&mut timelock, DELAY),
+                Err(Error::AlreadyScheduled)
+            );
+        }
+
+        #[ink::test]
+        fn schedule_enforces_min_delay() {
+            let mut timelock = new_timelock();
+            assert_eq!(
+                schedule_set_fee(&mut timelock, DELAY - 1),
+                Err(Error::InsufficientDelay)
+            );
+        }
+
+        #[ink::test]
+        fn only_proposers_can_schedule_and_cancel() {
+            let accounts = default_accounts();
+            let mut timelock = new_timelock();
+            let id = schedule_set_fee(&mut timelock, DELAY).expect("Cannot schedule");
+            set_caller(accounts.bob);
+            assert_eq!(
+                schedule_set_fee(&mut timelock, DELAY + 1),
+                Err(Error::NotProposer)
+            );
+            assert_eq!(timelock.cancel(id), Err(Error::NotProposer));
+            ink_env::test::pop_execution_context();
+            assert_eq!(timelock.cancel(id), Ok(()));
+            assert_eq!(timelock.eta(id), None);
+            assert_eq!(timelock.cancel(id), Err(Error::NotScheduled));
+        }
+
+        #[ink::test]
+        fn early_execution_fails() {
+            let accounts = default_accounts();
+            let mut timelock = new_timelock();
+            let id = schedule_set_fee(&mut timelock, DELAY).expect("Cannot schedule");
+            set_caller(accounts.bob);
+            // One block of 5 time units is not enough.
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(execute_set_fee(&mut timelock, DELAY), Err(Error::NotReady));
+            assert_eq!(timelock.eta(id), Some(DELAY));
+        }
+
+        #[ink::test]
+        fn only_executors_can_execute() {
+            let mut timelock = new_timelock();
+            schedule_set_fee(&mut timelock, DELAY).expect("Cannot schedule");
+            assert_eq!(execute_set_fee(&mut timelock, DELAY), Err(Error::NotExecutor));
+        }
+
+        #[ink::test]
+        fn unscheduled_calls_cannot_be_executed() {
+            let accounts = default_accounts();
+            let mut timelock = new_timelock();
+            schedule_set_fee(&mut timelock, DELAY).expect("Cannot schedule");
+            set_caller(accounts.bob);
+            assert_eq!(
+                execute_set_fee(&mut timelock, DELAY + 1),
+                Err(Error::NotScheduled)
+            );
+        }
+
+        #[ink::test]
+        fn update_delay_requires_the_timelock() {
+            let mut timelock = new_timelock();
+            assert_eq!(timelock.update_delay(DELAY * 2), Err(Error::NotTimelock));
+            let timelock_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract account");
+            set_caller(timelock_account);
+            assert_eq!(timelock.update_delay(DELAY * 2), Ok(()));
+            assert_eq!(timelock.min_delay(), DELAY * 2);
+        }
+    }
+}
@@ -14,10 +14,14 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub use self::erc20::{
+    BaseErc20,
+    Erc20,
+};
 use ink_lang as ink;
 
 #[ink::contract]
-mod erc20 {
+pub mod erc20 {
     #[cfg(not(feature = "ink-as-dependency"))]
     use ink_lang as ink;
 
@@ -34,7 +38,7 @@ mod erc20 {
     };
 
     /// The ERC-20 error types.
-    #[derive(Debug, PartialEq, Eq, scale::Encode)]
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
         /// Returned if not enough balance to fulfill a request is available.
@@ -0,0 +1,208 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # TWAP Library
+//!
+//! Computes time-weighted average prices from the cumulative prices of a pair.
+//!
+//! ## Warning
+//!
+//! This contract is an *example*. It is neither audited nor endorsed for production use.
+//! Do **not** rely on it to keep anything of value secure.
+//!
+//! ## Overview
+//!
+//! A pair accumulates `price * seconds` for both of its tokens. Given two snapshots
+//! of such a cumulative price, the average price in between is their difference
+//! divided by the elapsed time. The cumulative prices are Q112 fixed-point numbers
+//! and so are the averages.
+//!
+//! Cumulative prices are expected to wrap around on overflow, which is why the
+//! difference of two snapshots is taken modulo `2^128`.
+//!
+//! `compute_twap_pair` reads both snapshots from a pair exposing
+//! `observe(seconds_ago: u32) -> (u128, u128)`, which returns the cumulative prices
+//! of token0 and token1 `seconds_ago` seconds in the past.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::twap_lib::TwapLib;
+use ink_lang as ink;
+
+#[ink::contract]
+mod twap_lib {
+    use ink_env::call::{
+        build_call,
+        utils::ReturnType,
+        ExecutionInput,
+        Selector,
+    };
+
+    /// The selector of the `observe` message of a pair.
+    const OBSERVE_SELECTOR: [u8; 4] = [0xc5, 0x2a, 0xe8, 0xc3];
+
+    /// Returns the average price between the snapshots `cumulative_start` and
+    /// `cumulative_end` taken `elapsed_seconds` apart.
+    ///
+    /// # Panics
+    ///
+    /// If `elapsed_seconds` is zero.
+    pub fn twap(
+        cumulative_start: u128,
+        cumulative_end: u128,
+        elapsed_seconds: u32,
+    ) -> u128 {
+        assert!(elapsed_seconds > 0, "TwapLib: ZERO_ELAPSED");
+        cumulative_end.wrapping_sub(cumulative_start) / u128::from(elapsed_seconds)
+    }
+
+    /// A stateless library contract computing time-weighted average prices.
+    #[ink(storage)]
+    pub struct TwapLib {}
+
+    impl TwapLib {
+        /// Creates a new TWAP library.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {}
+        }
+
+        /// Returns the average price of token0 between two snapshots of its
+        /// cumulative price taken `elapsed_seconds` apart.
+        ///
+        /// # Panics
+        ///
+        /// If `elapsed_seconds` is zero.
+        #[ink(message)]
+        pub fn compute_twap(
+            &self,
+            price0_cumulative_start: u128,
+            price0_cumulative_end: u128,
+            elapsed_seconds: u32,
+        ) -> u128 {
+            twap(
+                price0_cumulative_start,
+                price0_cumulative_end,
+                elapsed_seconds,
+            )
+        }
+
+        /// Returns the average prices of token0 and token1 of `pair` over the last
+        /// `elapsed_seconds` seconds.
+        ///
+        /// # Panics
+        ///
+        /// If `elapsed_seconds` is zero or if a call to `observe` on the pair fails.
+        #[ink(message)]
+        pub fn compute_twap_pair(
+            &self,
+            pair: AccountId,
+            elapsed_seconds: u32,
+        ) -> (u128, u128) {
+            let (price0_start, price1_start) = self.observe(pair, elapsed_seconds);
+            let (price0_end, price1_end) = self.observe(pair, 0);
+            (
+                twap(price0_start, price0_end, elapsed_seconds),
+                twap(price1_start, price1_end, elapsed_seconds),
+            )
+        }
+
+        /// Returns the cumulative prices of `pair` `seconds_ago` seconds in the past.
+        fn observe(&self, pair: AccountId, seconds_ago: u32) -> (u128, u128) {
+            build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(pair)
+                .gas_limit(0)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(OBSERVE_SELECTOR))
+                        .push_arg(&seconds_ago),
+                )
+                .returns::<ReturnType<(u128, u128)>>()
+                .fire()
+                .expect("TwapLib: OBSERVE_FAILED")
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+        use proptest::prelude::*;
+
+        #[ink::test]
+        fn compute_twap_works() {
+            let lib = TwapLib::new();
+            assert_eq!(lib.compute_twap(1_000, 4_000, 10), 300);
+            // The remainder of the division is dropped.
+            assert_eq!(lib.compute_twap(1_000, 4_009, 10), 300);
+        }
+
+        #[test]
+        #[should_panic(expected = "TwapLib: ZERO_ELAPSED")]
+        fn zero_elapsed_fails() {
+            twap(1_000, 4_000, 0);
+        }
+
+        #[test]
+        fn wrapped_cumulative_price_works() {
+            assert_eq!(twap(u128::MAX - 99, 200, 30), 10);
+        }
+
+        #[test]
+        fn maximum_elapsed_works() {
+            let elapsed = u32::MAX;
+            assert_eq!(twap(0, u128::from(elapsed) * 7, elapsed), 7);
+            assert_eq!(twap(0, u128::from(elapsed) - 1, elapsed), 0);
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(4_096))]
+
+            #[test]
+            fn constant_price_is_its_own_average(
+                start in any::<u128>(),
+                price in 0..(1u128 << 96),
+                elapsed in 1..=u32::MAX,
+            ) {
+                let end = start.wrapping_add(price * u128::from(elapsed));
+                prop_assert_eq!(twap(start, end, elapsed), price);
+            }
+
+            #[test]
+            fn average_is_monotone_in_end(
+                start in any::<u128>(),
+                delta in any::<u128>(),
+                extra in any::<u128>(),
+                elapsed in 1..=u32::MAX,
+            ) {
+                let delta = delta.min(u128::MAX - extra);
+                prop_assert!(
+                    twap(start, start.wrapping_add(delta), elapsed)
+                        <= twap(start, start.wrapping_add(delta + extra), elapsed)
+                );
+            }
+
+            #[test]
+            fn average_decreases_with_elapsed(
+                start in any::<u128>(),
+                delta in any::<u128>(),
+                elapsed in 1..u32::MAX,
+            ) {
+                let end = start.wrapping_add(delta);
+                prop_assert!(twap(start, end, elapsed + 1) <= twap(start, end, elapsed));
+                prop_assert!(twap(start, end, elapsed) <= delta);
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Role based access control shared by the uniswap contracts.
+//!
+//! Embed a [`Roles`] in the `#[ink(storage)]` struct of a contract and guard
+//! privileged messages with [`Roles::only_role`]. Since ink! events have to be
+//! defined by the contract itself, [`Roles::grant_role`] and
+//! [`Roles::revoke_role`] return whether the membership changed so that the
+//! host contract can emit its own `RoleGranted` and `RoleRevoked` events.
+
+use ink_env::AccountId;
+#[cfg(feature = "std")]
+use ink_storage::traits::StorageLayout;
+use ink_storage::{
+    collections::HashMap as StorageHashMap,
+    traits::SpreadLayout,
+};
+
+/// The identifier of a role.
+///
+/// Every role apart from [`DEFAULT_ADMIN_ROLE`] is the BLAKE2b-256 hash of its name.
+pub type RoleId = [u8; 32];
+
+/// The role that administers all roles which have no other admin set.
+pub const DEFAULT_ADMIN_ROLE: RoleId = [0x00; 32];
+
+/// `blake2x256("OWNER_ROLE")`
+pub const OWNER_ROLE: RoleId = [
+    0x38, 0xf0, 0xce, 0x2b, 0xbb, 0x62, 0x8a, 0x62, 0xd2, 0x43, 0x79, 0xeb, 0x8d, 0x78,
+    0x15, 0x7a, 0xd7, 0xae, 0x35, 0xf8, 0xbf, 0xa4, 0x17, 0x13, 0xcb, 0x66, 0xc1, 0xea,
+    0xe0, 0x03, 0x51, 0xe6,
+];
+
+/// `blake2x256("GUARDIAN_ROLE")`
+pub const GUARDIAN_ROLE: RoleId = [
+    0x09, 0x7b, 0xb3, 0x13, 0xa2, 0x28, 0x52, 0x6a, 0x5d, 0xe1, 0x19, 0x81, 0xbf, 0x42,
+    0x95, 0x0f, 0xe1, 0x4a, 0xd1, 0x16, 0x7e, 0x0f, 0x41, 0x92, 0xfe, 0x11, 0x0c, 0x6c,
+    0xb7, 0x36, 0x2f, 0x0c,
+];
+
+/// `blake2x256("FEE_TO_SETTER_ROLE")`
+pub const FEE_TO_SETTER_ROLE: RoleId = [
+    0x93, 0x9e, 0x8d, 0x9c, 0x3d, 0x07, 0x94, 0xb6, 0xd0, 0xb4, 0xac, 0xcb, 0xb9, 0x4f,
+    0x4c, 0x2a, 0xab, 0xdc, 0xfe, 0x06, 0x85, 0xa3, 0xe9, 0x2c, 0x6b, 0xe9, 0x36, 0x58,
+    0x69, 0x5c, 0x7e, 0xc0,
+];
+
+/// `blake2x256("MINTER_ROLE")`
+pub const MINTER_ROLE: RoleId = [
+    0xdd, 0x44, 0x72, 0x48, 0x09, 0x8c, 0x38, 0xa0, 0x3c, 0x57, 0x90, 0x24, 0x5a, 0x36,
+    0xba, 0x2c, 0x24, 0x6a, 0x69, 0xc3, 0xae, 0xf4, 0x4e, 0x27, 0x1f, 0xc8, 0x77, 0x17,
+    0xb2, 0x5e, 0xa9, 0xed,
+];
+
+/// Returned if an account lacks the role required for an operation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct MissingRole {
+    /// The required role.
+    pub role: RoleId,
+    /// The account lacking the role.
+    pub account: AccountId,
+}
+
+/// The role memberships and role admins of a contract.
+#[derive(SpreadLayout)]
+#[cfg_attr(feature = "std", derive(StorageLayout))]
+#[cfg_attr(test, derive(Debug))]
+pub struct Roles {
+    /// The set of `(role, account)` memberships.
+    members: StorageHashMap<(RoleId, AccountId), ()>,
+    /// Mapping from role to its admin role if it differs from `DEFAULT_ADMIN_ROLE`.
+    admins: StorageHashMap<RoleId, RoleId>,
+}
+
+impl Default for Roles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Roles {
+    /// Creates a role set without any members.
+    ///
+    /// Use [`Roles::setup_role`] in the constructor of the host contract to
+    /// grant the initial roles.
+    pub fn new() -> Self {
+        Self {
+            members: StorageHashMap::new(),
+            admins: StorageHashMap::new(),
+        }
+    }
+
+    /// Returns `true` if `account` has been granted `role`.
+    pub fn has_role(&self, role: RoleId, account: AccountId) -> bool {
+        self.members.contains_key(&(role, account))
+    }
+
+    /// Returns the admin role that can grant and revoke `role`.
+    pub fn role_admin(&self, role: RoleId) -> RoleId {
+        self.admins.get(&role).copied().unwrap_or(DEFAULT_ADMIN_ROLE)
+    }
+
+    /// Returns `Ok` if `account` has been granted `role`.
+    pub fn only_role(
+        &self,
+        role: RoleId,
+        account: AccountId,
+    ) -> core::result::Result<(), MissingRole> {
+        if !self.has_role(role, account) {
+            return Err(MissingRole { role, account })
+        }
+        Ok(())
+    }
+
+    /// Grants `role` to `account` on behalf of `sender`.
+    ///
+    /// Returns `Ok(true)` if `account` did not have `role` before.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MissingRole` if `sender` does not have the admin role of `role`.
+    pub fn grant_role(
+        &mut self,
+        role: RoleId,
+        account: AccountId,
+        sender: AccountId,
+    ) -> core::result::Result<bool, MissingRole> {
+        self.only_role(self.role_admin(role), sender)?;
+        Ok(self.setup_role(role, account))
+    }
+
+    /// Revokes `role` from `account` on behalf of `sender`.
+    ///
+    /// Returns `Ok(true)` if `account` had `role` before.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MissingRole` if `sender` does not have the admin role of `role`.
+    pub fn revoke_role(
+        &mut self,
+        role: RoleId,
+        account: AccountId,
+        sender: AccountId,
+    ) -> core::result::Result<bool, MissingRole> {
+        self.only_role(self.role_admin(role), sender)?;
+        Ok(self.members.take(&(role, account)).is_some())
+    }
+
+    /// Grants `role` to `account` without checking any admin role.
+    ///
+    /// Returns `true` if `account` did not have `role` before.
+    ///
+    /// # Note
+    ///
+    /// Only meant to be used while constructing the host contract.
+    pub fn setup_role(&mut self, role: RoleId, account: AccountId) -> bool {
+        self.members.insert((role, account), ()).is_none()
+    }
+
+    /// Makes `admin` the role that can grant and revoke `role`.
+    ///
+    /// # Note
+    ///
+    /// Only meant to be used while constructing the host contract.
+    pub fn set_role_admin(&mut self, role: RoleId, admin: RoleId) {
+        self.admins.insert(role, admin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ink_env::hash::Blake2x256;
+
+    fn role_id(name: &[u8]) -> RoleId {
+        let mut output = RoleId::default();
+        ink_env::hash_bytes::<Blake2x256>(name, &mut output);
+        output
+    }
+
+    #[test]
+    fn role_ids_are_name_hashes() {
+        assert_eq!(OWNER_ROLE, role_id(b"OWNER_ROLE"));
+        assert_eq!(GUARDIAN_ROLE, role_id(b"GUARDIAN_ROLE"));
+        assert_eq!(FEE_TO_SETTER_ROLE, role_id(b"FEE_TO_SETTER_ROLE"));
+        assert_eq!(MINTER_ROLE, role_id(b"MINTER_ROLE"));
+    }
+}
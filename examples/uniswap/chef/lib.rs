@@ -0,0 +1,775 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Chef
+//!
+//! Distributes a reward token to the liquidity providers of uniswap pairs.
+//!
+//! ## Warning
+//!
+//! This contract is an *example*. It is neither audited nor endorsed for production use.
+//! Do **not** rely on it to keep anything of value secure.
+//!
+//! ## Overview
+//!
+//! The owner adds pools, each staking the LP token of one pair and weighted by its
+//! allocation points. Every block after `start_block` emits `reward_per_block` of
+//! the reward token, which is split across the pools by their allocation points
+//! and within a pool by the stake of its users.
+//!
+//! Rather than paying every user on every block, each pool tracks the reward
+//! accumulated per staked token since it was added. A user is owed the
+//! difference between what their stake accumulated and their reward debt, which
+//! is reset whenever they deposit or withdraw.
+//!
+//! The chef pays rewards out of its own balance of the reward token, so whoever
+//! mints the reward token has to fund it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::chef::Chef;
+use ink_lang as ink;
+
+mod math;
+
+#[ink::contract]
+pub mod chef {
+    use crate::math;
+    use erc20::{
+        BaseErc20,
+        Erc20,
+    };
+    use ink_env::call::FromAccountId;
+    use ink_storage::{
+        collections::{
+            HashMap as StorageHashMap,
+            Vec as StorageVec,
+        },
+        lazy::Lazy,
+        traits::{
+            PackedLayout,
+            SpreadLayout,
+        },
+    };
+
+    /// The chef error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the caller is not the owner.
+        NotOwner,
+        /// Returned if there is no pool with the given id.
+        PoolNotFound,
+        /// Returned if a pool already stakes the given LP token.
+        PoolExists,
+        /// Returned if a user withdraws more than they staked.
+        InsufficientStake,
+        /// Returned if the reward accounting overflows.
+        Overflow,
+    }
+
+    /// The chef result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A pool staking one LP token.
+    #[derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct PoolInfo {
+        /// The LP token staked in the pool.
+        pub lp_token: AccountId,
+        /// The share of the emission of the pool.
+        pub alloc_points: u32,
+        /// The last block the reward of the pool has been accounted for.
+        pub last_reward_block: BlockNumber,
+        /// The reward accumulated per staked token, scaled by
+        /// `math::ACC_PRECISION`.
+        pub acc_reward_per_share: Balance,
+        /// The amount of LP tokens staked in the pool.
+        pub total_staked: Balance,
+    }
+
+    /// The stake of a user in a pool.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct UserInfo {
+        /// The amount of LP tokens staked by the user.
+        pub amount: Balance,
+        /// The reward accumulated by `amount` that has already been paid or
+        /// was never owed.
+        pub reward_debt: Balance,
+    }
+
+    /// Event emitted when a user stakes LP tokens.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Deposit {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        pool_id: u32,
+        amount: Balance,
+    }
+
+    /// Event emitted when a user unstakes LP tokens.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Withdraw {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        pool_id: u32,
+        amount: Balance,
+    }
+
+    /// Event emitted when a user unstakes LP tokens forfeiting the reward.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct EmergencyWithdraw {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        pool_id: u32,
+        amount: Balance,
+    }
+
+    #[ink(storage)]
+    pub struct Chef {
+        /// The account allowed to add and weight pools.
+        owner: AccountId,
+        /// The token the rewards are paid in.
+        reward_token: Lazy<Erc20>,
+        /// The reward emitted per block across all pools.
+        reward_per_block: Balance,
+        /// The first block emitting rewards.
+        start_block: BlockNumber,
+        /// The sum of the allocation points of all pools.
+        total_alloc_points: u32,
+        /// The pools, indexed by their id.
+        pools: StorageVec<PoolInfo>,
+        /// The stakes of the users, keyed by pool id and user.
+        users: StorageHashMap<(u32, AccountId), UserInfo>,
+    }
+
+    impl Chef {
+        /// Creates a chef emitting `reward_per_block` of `reward_token` from
+        /// `start_block` on.
+        ///
+        /// The caller becomes the owner.
+        #[ink(constructor)]
+        pub fn new(
+            reward_token: AccountId,
+            reward_per_block: Balance,
+            start_block: BlockNumber,
+        ) -> Self {
+            Self {
+                owner: Self::env().caller(),
+                reward_token: Lazy::new(FromAccountId::from_account_id(reward_token)),
+                reward_per_block,
+                start_block,
+                total_alloc_points: 0,
+                pools: StorageVec::new(),
+                users: StorageHashMap::new(),
+            }
+        }
+
+        /// Returns the account allowed to add and weight pools.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Returns the reward emitted per block across all pools.
+        #[ink(message)]
+        pub fn reward_per_block(&self) -> Balance {
+            self.reward_per_block
+        }
+
+        /// Returns the sum of the allocation points of all pools.
+        #[ink(message)]
+        pub fn total_alloc_points(&self) -> u32 {
+            self.total_alloc_points
+        }
+
+        /// Returns the number of pools.
+        #[ink(message)]
+        pub fn pool_length(&self) -> u32 {
+            self.pools.len()
+        }
+
+        /// Returns the pool with the id `pool_id`.
+        #[ink(message)]
+        pub fn pool_info(&self, pool_id: u32) -> Option<PoolInfo> {
+            self.pools.get(pool_id).copied()
+        }
+
+        /// Returns the stake of `user` in the pool with the id `pool_id`.
+        #[ink(message)]
+        pub fn user_info(&self, pool_id: u32, user: AccountId) -> UserInfo {
+            self.users.get(&(pool_id, user)).copied().unwrap_or_default()
+        }
+
+        /// Adds a pool staking `lp_token` with `alloc_points` and returns its id.
+        ///
+        /// The rewards of all pools are accounted for first, so that the new
+        /// weights only apply from now on.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotOwner` if the caller is not the owner.
+        /// - Returns `PoolExists` if a pool already stakes `lp_token`.
+        /// - Returns `Overflow` if the allocation points overflow.
+        #[ink(message)]
+        pub fn add_pool(
+            &mut self,
+            lp_token: AccountId,
+            alloc_points: u32,
+        ) -> Result<u32> {
+            self.ensure_owner()?;
+            if self.pools.iter().any(|pool| pool.lp_token == lp_token) {
+                return Err(Error::PoolExists)
+            }
+            let total_alloc_points = self
+                .total_alloc_points
+                .checked_add(alloc_points)
+                .ok_or(Error::Overflow)?;
+            self.mass_update_pools()?;
+            let pool_id = self.pools.len();
+            self.pools.push(PoolInfo {
+                lp_token,
+                alloc_points,
+                last_reward_block: core::cmp::max(
+                    self.env().block_number(),
+                    self.start_block,
+                ),
+                acc_reward_per_share: 0,
+                total_staked: 0,
+            });
+            self.total_alloc_points = total_alloc_points;
+            Ok(pool_id)
+        }
+
+        /// Sets the allocation points of the pool with the id `pool_id`.
+        ///
+        /// The rewards of all pools are accounted for first, so that the new
+        /// weights only apply from now on.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotOwner` if the caller is not the owner.
+        /// - Returns `PoolNotFound` if there is no such pool.
+        /// - Returns `Overflow` if the allocation points overflow.
+        #[ink(message)]
+        pub fn set_pool(&mut self, pool_id: u32, alloc_points: u32) -> Result<()> {
+            self.ensure_owner()?;
+            let pool = self.pools.get(pool_id).ok_or(Error::PoolNotFound)?;
+            let total_alloc_points = (self.total_alloc_points - pool.alloc_points)
+                .checked_add(alloc_points)
+                .ok_or(Error::Overflow)?;
+            self.mass_update_pools()?;
+            self.pools
+                .get_mut(pool_id)
+                .expect("the pool has been checked above")
+                .alloc_points = alloc_points;
+            self.total_alloc_points = total_alloc_points;
+            Ok(())
+        }
+
+        /// Returns the reward `user` could harvest from the pool with the id
+        /// `pool_id` now.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `PoolNotFound` if there is no such pool.
+        /// - Returns `Overflow` if the reward accounting overflows.
+        #[ink(message)]
+        pub fn pending_reward(&self, pool_id: u32, user: AccountId) -> Result<Balance> {
+            let pool = self.pools.get(pool_id).ok_or(Error::PoolNotFound)?;
+            let acc_reward_per_share = self.acc_reward_per_share_now(pool)?;
+            let user = self.user_info(pool_id, user);
+            Self::pending_of(&user, acc_reward_per_share)
+        }
+
+        /// Accounts for the rewards of all pools up to the current block.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Overflow` if the reward accounting overflows.
+        #[ink(message)]
+        pub fn mass_update_pools(&mut self) -> Result<()> {
+            for pool_id in 0..self.pools.len() {
+                self.update_pool(pool_id)?;
+            }
+            Ok(())
+        }
+
+        /// Stakes `amount` of the LP token of the pool with the id `pool_id` and
+        /// pays the pending reward of the caller.
+        ///
+        /// The chef has to be approved to spend `amount` of the LP token of the
+        /// caller. A deposit of zero only harvests the reward.
+        ///
+        /// On success a `Deposit` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `PoolNotFound` if there is no such pool.
+        /// - Returns `Overflow` if the reward accounting overflows.
+        ///
+        /// # Panics
+        ///
+        /// If a token transfer fails.
+        #[ink(message)]
+        pub fn deposit(&mut self, pool_id: u32, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            let (lp_token, pending) = self.stake(pool_id, caller, amount)?;
+            self.pay_reward(caller, pending);
+            if amount > 0 {
+                let mut lp_token: Erc20 = FromAccountId::from_account_id(lp_token);
+                assert!(
+                    lp_token
+                        .transfer_from(caller, self.env().account_id(), amount)
+                        .is_ok(),
+                    "Chef: TRANSFER_FAILED"
+                );
+            }
+            self.env().emit_event(Deposit {
+                user: caller,
+                pool_id,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Unstakes `amount` of the LP token of the pool with the id `pool_id`
+        /// and pays the pending reward of the caller.
+        ///
+        /// A withdrawal of zero only harvests the reward.
+        ///
+        /// On success a `Withdraw` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `PoolNotFound` if there is no such pool.
+        /// - Returns `InsufficientStake` if the caller staked less than `amount`.
+        /// - Returns `Overflow` if the reward accounting overflows.
+        ///
+        /// # Panics
+        ///
+        /// If a token transfer fails.
+        #[ink(message)]
+        pub fn withdraw(&mut self, pool_id: u32, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            let (lp_token, pending) = self.unstake(pool_id, caller, amount)?;
+            self.pay_reward(caller, pending);
+            if amount > 0 {
+                let mut lp_token: Erc20 = FromAccountId::from_account_id(lp_token);
+                assert!(
+                    lp_token.transfer(caller, amount).is_ok(),
+                    "Chef: TRANSFER_FAILED"
+                );
+            }
+            self.env().emit_event(Withdraw {
+                user: caller,
+                pool_id,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Unstakes all LP tokens of the caller from the pool with the id
+        /// `pool_id`, forfeiting the pending reward.
+        ///
+        /// Does not touch the reward accounting, so it keeps working if that
+        /// overflows or the chef runs out of rewards. The forfeited reward goes
+        /// to the remaining stakers of the pool.
+        ///
+        /// On success an `EmergencyWithdraw` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PoolNotFound` if there is no such pool.
+        ///
+        /// # Panics
+        ///
+        /// If the token transfer fails.
+        #[ink(message)]
+        pub fn emergency_withdraw(&mut self, pool_id: u32) -> Result<()> {
+            let caller = self.env().caller();
+            let (lp_token, amount) = self.forfeit(pool_id, caller)?;
+            if amount > 0 {
+                let mut lp_token: Erc20 = FromAccountId::from_account_id(lp_token);
+                assert!(
+                    lp_token.transfer(caller, amount).is_ok(),
+                    "Chef: TRANSFER_FAILED"
+                );
+            }
+            self.env().emit_event(EmergencyWithdraw {
+                user: caller,
+                pool_id,
+                amount,
+            });
+            Ok(())
+        }
+
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            Ok(())
+        }
+
+        /// Returns the reward accumulated per staked token of `pool` up to the
+        /// current block.
+        fn acc_reward_per_share_now(&self, pool: &PoolInfo) -> Result<Balance> {
+            let block = self.env().block_number();
+            if block <= pool.last_reward_block || pool.total_staked == 0 {
+                return Ok(pool.acc_reward_per_share)
+            }
+            let reward = math::pool_reward(
+                (block - pool.last_reward_block).into(),
+                self.reward_per_block,
+                pool.alloc_points.into(),
+                self.total_alloc_points.into(),
+            )
+            .ok_or(Error::Overflow)?;
+            math::reward_per_share(reward, pool.total_staked)
+                .and_then(|increase| pool.acc_reward_per_share.checked_add(increase))
+                .ok_or(Error::Overflow)
+        }
+
+        /// Accounts for the reward of the pool with the id `pool_id` up to the
+        /// current block and returns the updated pool.
+        ///
+        /// Blocks without any stake emit nothing.
+        fn update_pool(&mut self, pool_id: u32) -> Result<PoolInfo> {
+            let pool = *self.pools.get(pool_id).ok_or(Error::PoolNotFound)?;
+            let block = self.env().block_number();
+            if block <= pool.last_reward_block {
+                return Ok(pool)
+            }
+            let acc_reward_per_share = self.acc_reward_per_share_now(&pool)?;
+            let pool = self
+                .pools
+                .get_mut(pool_id)
+                .expect("the pool has been checked above");
+            pool.acc_reward_per_share = acc_reward_per_share;
+            pool.last_reward_block = block;
+            Ok(*pool)
+        }
+
+        /// Returns the reward `user` is owed at `acc_reward_per_share`.
+        fn pending_of(user: &UserInfo, acc_reward_per_share: Balance) -> Result<Balance> {
+            math::accumulated(user.amount, acc_reward_per_share)
+                .and_then(|accumulated| accumulated.checked_sub(user.reward_debt))
+                .ok_or(Error::Overflow)
+        }
+
+        /// Adds `amount` to the stake of `user` in the pool with the id
+        /// `pool_id`, leaving the transfers to the caller.
+        ///
+        /// Returns the LP token of the pool and the reward owed to `user`.
+        fn stake(
+            &mut self,
+            pool_id: u32,
+            user: AccountId,
+            amount: Balance,
+        ) -> Result<(AccountId, Balance)> {
+            let pool = self.update_pool(pool_id)?;
+            let mut info = self.user_info(pool_id, user);
+            let pending = Self::pending_of(&info, pool.acc_reward_per_share)?;
+            let total_staked =
+                pool.total_staked.checked_add(amount).ok_or(Error::Overflow)?;
+            info.amount += amount;
+            info.reward_debt = math::accumulated(info.amount, pool.acc_reward_per_share)
+                .ok_or(Error::Overflow)?;
+            self.pools
+                .get_mut(pool_id)
+                .expect("the pool has been updated above")
+                .total_staked = total_staked;
+            self.users.insert((pool_id, user), info);
+            Ok((pool.lp_token, pending))
+        }
+
+        /// Removes `amount` from the stake of `user` in the pool with the id
+        /// `pool_id`, leaving the transfers to the caller.
+        ///
+        /// Returns the LP token of the pool and the reward owed to `user`.
+        fn unstake(
+            &mut self,
+            pool_id: u32,
+            user: AccountId,
+            amount: Balance,
+        ) -> Result<(AccountId, Balance)> {
+            if self.pools.get(pool_id).is_none() {
+                return Err(Error::PoolNotFound)
+            }
+            let mut info = self.user_info(pool_id, user);
+            if info.amount < amount {
+                return Err(Error::InsufficientStake)
+            }
+            let pool = self.update_pool(pool_id)?;
+            let pending = Self::pending_of(&info, pool.acc_reward_per_share)?;
+            info.amount -= amount;
+            info.reward_debt = math::accumulated(info.amount, pool.acc_reward_per_share)
+                .ok_or(Error::Overflow)?;
+            self.pools
+                .get_mut(pool_id)
+                .expect("the pool has been updated above")
+                .total_staked -= amount;
+            self.users.insert((pool_id, user), info);
+            Ok((pool.lp_token, pending))
+        }
+
+        /// Removes the whole stake of `user` in the pool with the id `pool_id`
+        /// without paying the reward, leaving the transfer to the caller.
+        ///
+        /// Returns the LP token of the pool and the amount unstaked.
+        fn forfeit(
+            &mut self,
+            pool_id: u32,
+            user: AccountId,
+        ) -> Result<(AccountId, Balance)> {
+            let pool = self.pools.get_mut(pool_id).ok_or(Error::PoolNotFound)?;
+            let amount = self
+                .users
+                .take(&(pool_id, user))
+                .map(|info| info.amount)
+                .unwrap_or(0);
+            pool.total_staked -= amount;
+            Ok((pool.lp_token, amount))
+        }
+
+        fn pay_reward(&mut self, to: AccountId, amount: Balance) {
+            if amount > 0 {
+                assert!(
+                    self.reward_token.transfer(to, amount).is_ok(),
+                    "Chef: REWARD_TRANSFER_FAILED"
+                );
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        const REWARD_PER_BLOCK: Balance = 1_000;
+
+        fn default_accounts(
+        ) -> ink_env::test::DefaultAccounts<ink_env::DefaultEnvironment> {
+            ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get accounts")
+        }
+
+        fn advance_blocks(blocks: u32) {
+            for _ in 0..blocks {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+        }
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .unwrap_or([0x0; 32].into());
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                caller,
+                callee,
+                1000000,
+                1000000,
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])),
+            );
+        }
+
+        /// Creates a chef with a pool of Django's LP token weighted 1 and a
+        /// pool of Eve's LP token weighted 3.
+        fn two_pools() -> Chef {
+            let accounts = default_accounts();
+            let mut chef = Chef::new(accounts.frank, REWARD_PER_BLOCK, 0);
+            assert_eq!(chef.add_pool(accounts.django, 1), Ok(0));
+            assert_eq!(chef.add_pool(accounts.eve, 3), Ok(1));
+            chef
+        }
+
+        #[ink::test]
+        fn add_pool_works() {
+            let accounts = default_accounts();
+            let mut chef = two_pools();
+            assert_eq!(chef.pool_length(), 2);
+            assert_eq!(chef.total_alloc_points(), 4);
+            assert_eq!(
+                chef.pool_info(1),
+                Some(PoolInfo {
+                    lp_token: accounts.eve,
+                    alloc_points: 3,
+                    last_reward_block: 0,
+                    acc_reward_per_share: 0,
+                    total_staked: 0,
+                })
+            );
+            assert_eq!(chef.pool_info(2), None);
+            assert_eq!(chef.add_pool(accounts.eve, 1), Err(Error::PoolExists));
+            assert_eq!(
+                chef.add_pool(accounts.charlie, u32::MAX),
+                Err(Error::Overflow)
+            );
+        }
+
+        #[ink::test]
+        fn only_owner_can_manage_pools() {
+            let accounts = default_accounts();
+            let mut chef = two_pools();
+            set_caller(accounts.bob);
+            assert_eq!(chef.add_pool(accounts.charlie, 1), Err(Error::NotOwner));
+            assert_eq!(chef.set_pool(0, 2), Err(Error::NotOwner));
+            ink_env::test::pop_execution_context();
+            assert_eq!(chef.set_pool(2, 2), Err(Error::PoolNotFound));
+            assert_eq!(chef.set_pool(0, 2), Ok(()));
+            assert_eq!(chef.total_alloc_points(), 5);
+        }
+
+        #[ink::test]
+        fn rewards_are_split_by_pool_and_stake() {
+            let accounts = default_accounts();
+            let mut chef = two_pools();
+            let (alice, bob) = (accounts.alice, accounts.bob);
+
+            // Block 0: Alice stakes 100 in pool 0.
+            assert_eq!(chef.stake(0, alice, 100), Ok((accounts.django, 0)));
+            advance_blocks(2);
+            // Block 2: pool 0 has earned 2 * 1_000 / 4, all of it Alice's.
+            assert_eq!(chef.pending_reward(0, alice), Ok(500));
+            // Bob stakes 300 in pool 0 and Alice 50 in pool 1.
+            assert_eq!(chef.stake(0, bob, 300), Ok((accounts.django, 0)));
+            assert_eq!(chef.stake(1, alice, 50), Ok((accounts.eve, 0)));
+            advance_blocks(2);
+            // Block 4: pool 0 has earned another 500, shared 1:3.
+            assert_eq!(chef.pending_reward(0, alice), Ok(625));
+            assert_eq!(chef.pending_reward(0, bob), Ok(375));
+            // Pool 1 only earned while staked: 2 * 1_000 * 3 / 4.
+            assert_eq!(chef.pending_reward(1, alice), Ok(1_500));
+            assert_eq!(chef.pending_reward(1, bob), Ok(0));
+
+            // Harvesting resets the reward of Bob only.
+            assert_eq!(chef.stake(0, bob, 0), Ok((accounts.django, 375)));
+            assert_eq!(chef.pending_reward(0, bob), Ok(0));
+            assert_eq!(chef.pending_reward(0, alice), Ok(625));
+            advance_blocks(1);
+            assert_eq!(chef.pending_reward(0, alice), Ok(687));
+            assert_eq!(chef.pending_reward(0, bob), Ok(187));
+        }
+
+        #[ink::test]
+        fn unstake_pays_reward() {
+            let accounts = default_accounts();
+            let mut chef = two_pools();
+            let (alice, bob) = (accounts.alice, accounts.bob);
+            assert_eq!(chef.stake(1, alice, 100), Ok((accounts.eve, 0)));
+            advance_blocks(1);
+            assert_eq!(chef.stake(1, bob, 200), Ok((accounts.eve, 0)));
+            advance_blocks(1);
+            // Alice earned 750 alone and then a third of 750.
+            assert_eq!(chef.unstake(1, alice, 40), Ok((accounts.eve, 1_000)));
+            assert_eq!(chef.user_info(1, alice).amount, 60);
+            assert_eq!(chef.pool_info(1).unwrap().total_staked, 260);
+            assert_eq!(chef.pending_reward(1, alice), Ok(0));
+            assert_eq!(chef.unstake(1, alice, 61), Err(Error::InsufficientStake));
+            advance_blocks(1);
+            // 750 shared 60:200.
+            assert_eq!(chef.pending_reward(1, alice), Ok(173));
+            assert_eq!(chef.unstake(1, bob, 200), Ok((accounts.eve, 1_076)));
+            assert_eq!(chef.unstake(2, bob, 0), Err(Error::PoolNotFound));
+        }
+
+        #[ink::test]
+        fn rewards_start_at_start_block() {
+            let accounts = default_accounts();
+            let mut chef = Chef::new(accounts.frank, REWARD_PER_BLOCK, 3);
+            assert_eq!(chef.add_pool(accounts.django, 1), Ok(0));
+            assert_eq!(chef.stake(0, accounts.alice, 100), Ok((accounts.django, 0)));
+            advance_blocks(2);
+            assert_eq!(chef.pending_reward(0, accounts.alice), Ok(0));
+            advance_blocks(2);
+            assert_eq!(chef.pending_reward(0, accounts.alice), Ok(1_000));
+        }
+
+        #[ink::test]
+        fn set_pool_applies_from_now_on() {
+            let accounts = default_accounts();
+            let mut chef = two_pools();
+            assert_eq!(chef.stake(0, accounts.alice, 100), Ok((accounts.django, 0)));
+            advance_blocks(2);
+            assert_eq!(chef.set_pool(0, 3), Ok(()));
+            advance_blocks(2);
+            // 2 * 1_000 / 4 before and 2 * 1_000 / 2 after.
+            assert_eq!(chef.pending_reward(0, accounts.alice), Ok(1_500));
+        }
+
+        #[ink::test]
+        fn forfeit_drops_reward() {
+            let accounts = default_accounts();
+            let mut chef = two_pools();
+            assert_eq!(chef.stake(0, accounts.alice, 100), Ok((accounts.django, 0)));
+            assert_eq!(chef.stake(0, accounts.bob, 100), Ok((accounts.django, 0)));
+            advance_blocks(2);
+            assert_eq!(chef.forfeit(0, accounts.alice), Ok((accounts.django, 100)));
+            assert_eq!(chef.user_info(0, accounts.alice), UserInfo::default());
+            assert_eq!(chef.pending_reward(0, accounts.alice), Ok(0));
+            assert_eq!(chef.pool_info(0).unwrap().total_staked, 100);
+            // Bob gets the reward Alice forfeited as well.
+            advance_blocks(1);
+            assert_eq!(chef.pending_reward(0, accounts.bob), Ok(750));
+            assert_eq!(chef.forfeit(0, accounts.alice), Ok((accounts.django, 0)));
+            assert_eq!(chef.emergency_withdraw(2), Err(Error::PoolNotFound));
+        }
+
+        #[ink::test]
+        fn zero_deposits_do_not_transfer() {
+            let mut chef = two_pools();
+            // Nothing is staked or owed, so no token is called.
+            assert_eq!(chef.deposit(0, 0), Ok(()));
+            assert_eq!(chef.withdraw(0, 0), Ok(()));
+            assert_eq!(chef.emergency_withdraw(0), Ok(()));
+            assert_eq!(chef.withdraw(0, 1), Err(Error::InsufficientStake));
+            assert_eq!(ink_env::test::recorded_events().count(), 3);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reward accounting used by the chef.
+//!
+//! All functions return `None` instead of overflowing.
+
+/// The factor the accumulated reward per staked token is scaled by.
+pub const ACC_PRECISION: u128 = 1_000_000_000_000;
+
+/// Returns the reward of a pool with `alloc_points` out of `total_alloc_points`
+/// over `blocks` blocks emitting `reward_per_block` each.
+pub fn pool_reward(
+    blocks: u128,
+    reward_per_block: u128,
+    alloc_points: u128,
+    total_alloc_points: u128,
+) -> Option<u128> {
+    if total_alloc_points == 0 {
+        return Some(0)
+    }
+    blocks
+        .checked_mul(reward_per_block)?
+        .checked_mul(alloc_points)?
+        .checked_div(total_alloc_points)
+}
+
+/// Returns the increase of the accumulated reward per staked token, scaled by
+/// `ACC_PRECISION`, if `reward` is shared by `total_staked` tokens.
+pub fn reward_per_share(reward: u128, total_staked: u128) -> Option<u128> {
+    reward.checked_mul(ACC_PRECISION)?.checked_div(total_staked)
+}
+
+/// Returns the reward accumulated by `amount` staked tokens at
+/// `acc_reward_per_share`.
+pub fn accumulated(amount: u128, acc_reward_per_share: u128) -> Option<u128> {
+    Some(amount.checked_mul(acc_reward_per_share)? / ACC_PRECISION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_reward_works() {
+        assert_eq!(pool_reward(4, 1_000, 1, 4), Some(1_000));
+        assert_eq!(pool_reward(4, 1_000, 3, 4), Some(3_000));
+        // Rounds down.
+        assert_eq!(pool_reward(1, 1_000, 1, 3), Some(333));
+        assert_eq!(pool_reward(4, 1_000, 0, 0), Some(0));
+    }
+
+    #[test]
+    fn reward_is_shared_by_stake() {
+        let acc = reward_per_share(500, 100).unwrap();
+        assert_eq!(acc, 5 * ACC_PRECISION);
+        assert_eq!(accumulated(100, acc), Some(500));
+        assert_eq!(accumulated(40, acc), Some(200));
+        // Rounding loses at most one unit of the reward.
+        let acc = reward_per_share(1_000, 3_000_000).unwrap();
+        assert_eq!(accumulated(3_000_000, acc), Some(999));
+        assert_eq!(accumulated(1_000_000, acc), Some(333));
+        assert_eq!(reward_per_share(500, 0), None);
+    }
+
+    #[test]
+    fn overflow_returns_none() {
+        assert_eq!(pool_reward(u128::MAX, 2, 1, 1), None);
+        assert_eq!(reward_per_share(u128::MAX / ACC_PRECISION + 1, 1), None);
+        assert_eq!(accumulated(u128::MAX, 2), None);
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 256-bit intermediate arithmetic shared by the pool examples.
+//!
+//! Rust has no `u256`, so 256-bit values are passed around as their
+//! `(high, low)` `u128` halves. Tuples of this shape order like the values
+//! they represent. Functions return `None` on overflow instead of wrapping.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Returns the full 256-bit product of `a` and `b` as its `(high, low)` halves.
+///
+/// The product is built from 64-bit limbs, so no partial product overflows.
+pub fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+    // The second limb, at most three times `MASK`.
+    let mid = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+    let lo = (mid << 64) | (lo_lo & MASK);
+    let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+/// Returns the 256-bit value `(high, low)` as a `u128`, or `None` if it does
+/// not fit.
+pub fn to_u128((hi, lo): (u128, u128)) -> Option<u128> {
+    if hi == 0 {
+        Some(lo)
+    } else {
+        None
+    }
+}
+
+/// Divides the 256-bit value `(high, low)` by `denominator` and returns the
+/// 256-bit quotient as `(high, low)` halves together with the remainder.
+///
+/// Returns `None` if `denominator` is zero.
+pub fn div_rem_wide(
+    (hi, lo): (u128, u128),
+    denominator: u128,
+) -> Option<((u128, u128), u128)> {
+    if denominator == 0 {
+        return None
+    }
+    if hi == 0 {
+        return Some(((0, lo / denominator), lo % denominator))
+    }
+    // Long division of the low half one bit at a time, the remainder stays
+    // below `denominator`.
+    let mut quotient = 0;
+    let mut rem = hi % denominator;
+    for i in (0..128).rev() {
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+        if carry == 1 || rem >= denominator {
+            rem = rem.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+    Some(((hi / denominator, quotient), rem))
+}
+
+/// Returns `a * b / denominator` rounded down, computed without intermediate
+/// overflow.
+///
+/// Returns `None` if `denominator` is zero or the result does not fit into a
+/// `u128`.
+pub fn mul_div(a: u128, b: u128, denominator: u128) -> Option<u128> {
+    let (quotient, _) = div_rem_wide(full_mul(a, b), denominator)?;
+    to_u128(quotient)
+}
+
+/// Returns `a * b / denominator` rounded up, computed without intermediate
+/// overflow.
+///
+/// Returns `None` if `denominator` is zero or the result does not fit into a
+/// `u128`.
+pub fn mul_div_up(a: u128, b: u128, denominator: u128) -> Option<u128> {
+    let (quotient, rem) = div_rem_wide(full_mul(a, b), denominator)?;
+    let quotient = to_u128(quotient)?;
+    if rem > 0 {
+        quotient.checked_add(1)
+    } else {
+        Some(quotient)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitive_types::U256;
+    use proptest::prelude::*;
+
+    /// Values at and around the boundaries of the 64-bit limbs.
+    const LIMB_BOUNDARIES: [u128; 12] = [
+        0,
+        1,
+        2,
+        (1 << 63) - 1,
+        1 << 63,
+        (1 << 64) - 1,
+        1 << 64,
+        (1 << 64) + 1,
+        (1 << 127) - 1,
+        1 << 127,
+        u128::MAX - 1,
+        u128::MAX,
+    ];
+
+    fn to_u256((hi, lo): (u128, u128)) -> U256 {
+        (U256::from(hi) << 128) | U256::from(lo)
+    }
+
+    #[test]
+    fn full_mul_works_at_limb_boundaries() {
+        assert_eq!(full_mul(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(full_mul(u128::MAX, 2), (1, u128::MAX - 1));
+        assert_eq!(full_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        assert_eq!(
+            full_mul((1 << 64) - 1, (1 << 64) - 1),
+            (0, u128::MAX - (1 << 65) + 2)
+        );
+        for &a in LIMB_BOUNDARIES.iter() {
+            for &b in LIMB_BOUNDARIES.iter() {
+                assert_eq!(full_mul(a, b), full_mul(b, a));
+                assert_eq!(to_u256(full_mul(a, b)), U256::from(a) * U256::from(b));
+            }
+        }
+    }
+
+    #[test]
+    fn div_rem_wide_returns_the_full_quotient() {
+        assert_eq!(div_rem_wide((1, 0), 0), None);
+        assert_eq!(div_rem_wide((0, 7), 2), Some(((0, 3), 1)));
+        assert_eq!(div_rem_wide((4, 1), 2), Some(((2, 0), 1)));
+        assert_eq!(
+            div_rem_wide((u128::MAX, u128::MAX), 1),
+            Some(((u128::MAX, u128::MAX), 0))
+        );
+        assert_eq!(div_rem_wide((1, 0), 2), Some(((0, 1 << 127), 0)));
+    }
+
+    #[test]
+    fn mul_div_works_at_limb_boundaries() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+        assert_eq!(mul_div(u128::MAX, 2, 4), Some(u128::MAX / 2));
+        assert_eq!(mul_div_up(u128::MAX, 2, 4), Some(u128::MAX / 2 + 1));
+        assert_eq!(mul_div(1 << 100, 1 << 100, 1 << 90), Some(1 << 110));
+        assert_eq!(mul_div(u128::MAX, 2, 1), None);
+        assert_eq!(mul_div(1, 1, 0), None);
+        assert_eq!(mul_div_up(u128::MAX, u128::MAX, u128::MAX - 1), None);
+        // 7 * 5 / 3 = 11.67
+        assert_eq!(mul_div(7, 5, 3), Some(11));
+        assert_eq!(mul_div_up(7, 5, 3), Some(12));
+        assert_eq!(mul_div_up(6, 5, 3), Some(10));
+        assert_eq!(mul_div_up(7, 3, 2), Some(11));
+        assert_eq!(mul_div_up(8, 3, 2), Some(12));
+        for &a in LIMB_BOUNDARIES.iter() {
+            for &b in LIMB_BOUNDARIES.iter().filter(|&&b| b > 0) {
+                assert_eq!(mul_div(a, b, b), Some(a));
+                assert_eq!(mul_div_up(a, b, b), Some(a));
+            }
+        }
+    }
+
+    #[test]
+    fn to_u128_works() {
+        assert_eq!(to_u128((0, u128::MAX)), Some(u128::MAX));
+        assert_eq!(to_u128((1, 0)), None);
+        assert_eq!(
+            to_u128(full_mul(1 << 64, (1 << 64) - 1)),
+            Some(u128::MAX - (1 << 64) + 1)
+        );
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4_096))]
+
+        #[test]
+        fn wide_math_matches_u256(
+            a in any::<u128>(),
+            b in any::<u128>(),
+            denominator in 1..=u128::MAX,
+        ) {
+            let product = U256::from(a) * U256::from(b);
+            prop_assert_eq!(to_u256(full_mul(a, b)), product);
+            let (quotient, remainder) =
+                div_rem_wide(full_mul(a, b), denominator).unwrap();
+            prop_assert_eq!(to_u256(quotient), product / U256::from(denominator));
+            prop_assert_eq!(U256::from(remainder), product % U256::from(denominator));
+            let expected = to_u128(quotient);
+            prop_assert_eq!(mul_div(a, b, denominator), expected);
+            let expected_up = expected.and_then(|quotient| {
+                if remainder == 0 {
+                    Some(quotient)
+                } else {
+                    quotient.checked_add(1)
+                }
+            });
+            prop_assert_eq!(mul_div_up(a, b, denominator), expected_up);
+        }
+    }
+}
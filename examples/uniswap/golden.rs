@@ -0,0 +1,99 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Golden tests comparing the pair math to the Solidity implementation of
+//! UniswapV2.
+//!
+//! The vectors in `vectors` must match exactly. The vectors in `divergent`
+//! document where the pair knowingly differs.
+
+mod divergent;
+mod vectors;
+
+use crate::math::{self, Balance};
+
+/// The liquidity UniswapV2 locks on the first mint.
+const UNISWAP_V2_MINIMUM_LIQUIDITY: Balance = 1_000;
+
+fn first_mint(amount0: Balance, amount1: Balance) -> Balance {
+    math::sqrt(amount0 * amount1) - UNISWAP_V2_MINIMUM_LIQUIDITY
+}
+
+#[test]
+fn get_amount_out_matches_uniswap_v2() {
+    for &(amount_in, reserve_in, reserve_out, amount_out) in vectors::AMOUNT_OUT {
+        assert_eq!(
+            math::get_amount_out(amount_in, reserve_in, reserve_out, math::FEE_BPS),
+            amount_out,
+            "getAmountOut({}, {}, {})",
+            amount_in,
+            reserve_in,
+            reserve_out,
+        );
+    }
+}
+
+#[test]
+fn first_mint_matches_uniswap_v2() {
+    for &(amount0, amount1, liquidity) in vectors::FIRST_MINT {
+        assert_eq!(
+            first_mint(amount0, amount1),
+            liquidity,
+            "mint({}, {})",
+            amount0,
+            amount1,
+        );
+    }
+}
+
+#[test]
+fn mint_matches_uniswap_v2() {
+    for &(amount0, amount1, reserve0, reserve1, total_supply, liquidity) in vectors::MINT
+    {
+        assert_eq!(
+            math::mint_liquidity(amount0, amount1, reserve0, reserve1, total_supply),
+            liquidity,
+            "mint({}, {}, {}, {}, {})",
+            amount0,
+            amount1,
+            reserve0,
+            reserve1,
+            total_supply,
+        );
+    }
+}
+
+#[test]
+fn burn_matches_uniswap_v2() {
+    for &(liquidity, balance0, balance1, total_supply, amount0, amount1) in vectors::BURN
+    {
+        assert_eq!(
+            math::burn_amounts(liquidity, balance0, balance1, total_supply),
+            (amount0, amount1),
+            "burn({}, {}, {}, {})",
+            liquidity,
+            balance0,
+            balance1,
+            total_supply,
+        );
+    }
+}
+
+#[test]
+fn u128_overflows_diverge_from_uniswap_v2() {
+    for &(amount0, amount1, liquidity) in divergent::U128_OVERFLOW_FIRST_MINT {
+        let result = std::panic::catch_unwind(|| first_mint(amount0, amount1));
+        assert_ne!(result.ok(), Some(liquidity));
+    }
+}
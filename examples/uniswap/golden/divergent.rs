@@ -0,0 +1,37 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Vectors on which the pair intentionally diverges from UniswapV2.
+//!
+//! UniswapV2 computes in `uint256`, the pair in `Balance`, a `u128`. Swaps
+//! multiply in 256 bits as well, but inputs whose intermediate products exceed
+//! `u128::MAX` elsewhere, such as `amount0 * amount1` of the first mint,
+//! succeed in Solidity and overflow in the pair. All other results are
+//! identical.
+
+use crate::math::Balance;
+
+/// `(amount0, amount1, liquidity)` of a first `mint` that overflows `u128`.
+pub const U128_OVERFLOW_FIRST_MINT: &[(Balance, Balance, Balance)] = &[
+    (
+        1_180_591_620_717_411_303_424,
+        1_180_591_620_717_411_303_424,
+        1_180_591_620_717_411_302_424,
+    ),
+    (
+        100_000_000_000_000_000_000,
+        100_000_000_000_000_000_000,
+        99_999_999_999_999_999_000,
+    ),
+];
@@ -0,0 +1,230 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Input and output vectors computed with the Solidity implementation of
+//! UniswapV2, `UniswapV2Library.getAmountOut`, `UniswapV2Pair.mint` and
+//! `UniswapV2Pair.burn`, with the default fee of 0.3%.
+
+use crate::math::Balance;
+
+/// `(amount_in, reserve_in, reserve_out, amount_out)` of `getAmountOut`.
+pub const AMOUNT_OUT: &[(Balance, Balance, Balance, Balance)] = &[
+    (1, 1, 1, 0),
+    (1, 1_000, 1_000, 0),
+    (1, 1_000_000_000_000_000_000, 1, 0),
+    (1, 1, 1_000_000_000_000_000_000, 499_248_873_309_964_947),
+    (1_000, 1_000_000, 2_000_000, 1_992),
+    (1_000_000, 1_000_000, 1_000_000, 499_248),
+    (3, 5, 7, 2),
+    (
+        100_000_000_000_000_000_000,
+        100_000_000_000_000_000_000,
+        100_000_000_000_000,
+        49_924_887_330_996,
+    ),
+    (997, 1_000, 1_000, 498),
+    (
+        18_446_744_073_709_551_616,
+        18_446_744_073_709_551_616,
+        1_099_511_627_776,
+        548_929_941_358,
+    ),
+    (
+        1_000_000_000,
+        1_000_000_000_000_000_000_000_000_000,
+        1_000_000_000,
+        0,
+    ),
+    (1, 1_152_921_504_606_846_976, 1_152_921_504_606_846_976, 0),
+    (
+        1_000_000_000_000_000,
+        1_000_000_000_000_000,
+        1_000_000_000_000_000,
+        499_248_873_309_964,
+    ),
+    (12, 370, 771_721, 24_172),
+    (
+        9_670_476_633,
+        599_525,
+        2_921_648_143_070_537_334,
+        2_921_466_480_620_391_208,
+    ),
+    (9_570_785_122_284, 302_011_371_715_440, 4, 0),
+    (358_560_757_022_442, 9_250_861_616_692, 587_738, 572_912),
+    (30_950_968, 3, 17_925_427_231, 17_925_425_488),
+    (
+        97_202_770_041_654_081,
+        513_785_187_448_986_291,
+        73_941_016_231_887,
+        11_733_670_575_004,
+    ),
+    (
+        651_932_001_981,
+        178_008_222_268,
+        4_396_029_404_276,
+        3_450_927_838_835,
+    ),
+    (993_030_873, 9_284_480_199_499_450, 973_504_526_890, 103_809),
+    (
+        639_178_539_557_042,
+        874_994_697_236_374_777_153_161,
+        741_837_431_267_549,
+        540_282,
+    ),
+    (
+        97_988_320_179,
+        919_283,
+        42_603_919_947_179_619_419,
+        42_603_519_057_171_984_335,
+    ),
+    (8_202_490, 89_821_963_965_026_582, 804, 0),
+    (3_117_928_480, 1_781_018, 74, 73),
+    (35, 2_517_978_597_327_212_384, 262_886_336, 0),
+    (
+        10_000_000_000,
+        1_000_000_000_000_000_000_000_000_000_000,
+        1_000_000_000_000_000_000_000_000_000_000,
+        9_969_999_999,
+    ),
+    (
+        1_267_650_600_228_229_401_496_703_205_376,
+        1_267_650_600_228_229_401_496_703_205_376,
+        1_267_650_600_228_229_401_496_703_205_376,
+        632_873_133_914_644_323_130_802_752_007,
+    ),
+    (
+        100_000_000_000_000_000_000,
+        10_000_000_000,
+        1_000_000_000_000_000_000_000_000_000_000,
+        999_999_999_899_699_097_301_935_897_963,
+    ),
+];
+
+/// `(amount0, amount1, liquidity)` of the first `mint` of a pair, after
+/// locking the minimum liquidity.
+pub const FIRST_MINT: &[(Balance, Balance, Balance)] = &[
+    (1_001, 1_001, 1),
+    (4_000, 1_000_000, 62_245),
+    (
+        1_000_000_000_000_000_000,
+        1_000_000_000_000_000_000,
+        999_999_999_999_999_000,
+    ),
+    (1_000_000, 1_000_000_000_000, 999_999_000),
+    (1, 1_000_000_000, 30_622),
+    (
+        9_223_372_036_854_775_808,
+        9_223_372_036_854_775_808,
+        9_223_372_036_854_774_808,
+    ),
+    (12_345, 6_789_012, 288_500),
+    (10_000_000_000, 3, 172_205),
+];
+
+/// `(amount0, amount1, reserve0, reserve1, total_supply, liquidity)` of a
+/// later `mint`.
+pub const MINT: &[(Balance, Balance, Balance, Balance, Balance, Balance)] = &[
+    (1_000, 2_000, 1_000_000, 2_000_000, 1_414_213, 1_414),
+    (1, 1, 3, 3, 1, 0),
+    (
+        1_000_000_000_000_000_000,
+        1_000_000_000_000_000_000,
+        100_000_000_000_000_000_000,
+        100_000_000_000_000_000_000,
+        10_000_000_000_000_000_000,
+        100_000_000_000_000_000,
+    ),
+    (500, 400, 1_000, 1_000, 1_000, 400),
+    (7, 11, 13, 17, 19, 10),
+    (
+        1_000_000_000_000,
+        1_000_000_000,
+        1_000_000_000_000_000,
+        1_000_000_000_000,
+        10_000_000_000_000,
+        10_000_000_000,
+    ),
+    (
+        3_039_670,
+        193_519_888_582_470,
+        8_104_714,
+        807_230_075_123_939,
+        59,
+        14,
+    ),
+    (27_213_682, 0, 722_376_394, 11, 8_843, 0),
+    (44, 8_316_154, 48, 4_019_940_339, 8_776_018, 18_155),
+    (
+        5_521_675_408_809,
+        30_364_050,
+        6_075_615_460_430,
+        162_586_097,
+        5,
+        0,
+    ),
+    (
+        17_002_848_000,
+        5,
+        65_872_831_581,
+        40,
+        8_516_834_907_708_702,
+        1_064_604_363_463_587,
+    ),
+    (105_591_580_691, 25, 115_270_056_443, 58, 543_463, 234_251),
+];
+
+/// `(liquidity, balance0, balance1, total_supply, amount0, amount1)` of
+/// `burn`.
+pub const BURN: &[(Balance, Balance, Balance, Balance, Balance, Balance)] = &[
+    (1, 1_000, 1_000, 1_000, 1, 1),
+    (999, 1_000_000, 2_000_000, 1_000, 999_000, 1_998_000),
+    (
+        1,
+        1,
+        1_000_000_000_000_000_000,
+        3,
+        0,
+        333_333_333_333_333_333,
+    ),
+    (
+        1_000_000_000_000_000_000,
+        1_000_000_000_000_000_000,
+        1_000_000_000_000_000_000,
+        1_000_000_000_000_000_000,
+        1_000_000_000_000_000_000,
+        1_000_000_000_000_000_000,
+    ),
+    (3, 10, 10, 7, 4, 4),
+    (
+        24_575_317_363_454_317,
+        281_145_763,
+        2_536_836_669_618,
+        47_264_133_028_650_001,
+        146_183_710,
+        1_319_046_013_545,
+    ),
+    (59_540, 36_030_746, 21_124, 703_135, 3_051_008, 1_788),
+    (950_828, 68_217_392, 31, 1_608_465, 40_326_029, 18),
+    (477_490_260, 256, 605, 766_509_364, 159, 376),
+    (
+        669_917_711,
+        752_721_405_293,
+        74_055_023_284_489,
+        670_191_044,
+        752_414_412_829,
+        74_024_820_431_346,
+    ),
+    (2, 2_819_254_012_799, 8_389, 20, 281_925_401_279, 838),
+    (418, 324, 787_045, 3_947, 34, 83_350),
+];
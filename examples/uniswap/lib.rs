@@ -16,24 +16,459 @@
 
 use ink_lang as ink;
 
+pub mod access_control;
+pub mod math;
+
+#[cfg(test)]
+mod golden;
+#[cfg(test)]
+mod mock_erc20;
+
 #[ink::contract]
 pub mod uniswap_pair {
 
     #[cfg(not(feature = "ink-as-dependency"))]
     use ink_storage::{
-        collections::HashMap as StorageHashMap,
+        collections::{
+            HashMap as StorageHashMap,
+            Vec as StorageVec,
+        },
         lazy::Lazy,
     };
+    use ink_storage::traits::{
+        PackedLayout,
+        SpreadLayout,
+    };
+
+    #[cfg(test)]
+    use crate::mock_erc20::MockErc20 as Erc20;
+    #[cfg(not(test))]
+    use erc20::{
+        BaseErc20,
+        Erc20,
+    };
+    use ink_env::{
+        call::{
+            build_call,
+            utils::ReturnType,
+            ExecutionInput,
+            FromAccountId,
+            Selector,
+        },
+        hash::Keccak256,
+    };
+    #[cfg(not(feature = "ink-as-dependency"))]
+    use ink_lang::EmitEvent;
+    use ink_lang::ToAccountId;
+    use ink_prelude::{
+        format,
+        string::String,
+        vec::Vec,
+    };
+
+    use crate::{
+        access_control::{
+            MissingRole,
+            RoleId,
+            Roles,
+            DEFAULT_ADMIN_ROLE,
+            GUARDIAN_ROLE,
+            OWNER_ROLE,
+        },
+        math::{
+            self,
+            FeeGrowth,
+        },
+    };
+
+    /// The recommended LP tokens locked by the first mint, see
+    /// `Uniswap_pair::new`.
+    pub const MINIMUM_LIQUIDITY: Balance = 1_000;
+
+    /// The smallest `minimum_liquidity` a pair can be deployed with.
+    pub const MIN_MINIMUM_LIQUIDITY: Balance = 100;
+
+    /// The largest `minimum_liquidity` a pair can be deployed with.
+    pub const MAX_MINIMUM_LIQUIDITY: Balance = 1_000_000_000;
+
+    /// The number of decimals of the price returned by `latest_round_data`.
+    pub const ORACLE_DECIMALS: u32 = 8;
+
+    /// The share of the swap fee in percent added to the output of swaps by
+    /// holders of at least `rebate_threshold` governance tokens.
+    pub const HOLDER_REBATE_PERCENT: Balance = 20;
+
+    /// The number of blocks LP holders can vote on a fee proposal for.
+    pub const VOTING_PERIOD_BLOCKS: BlockNumber = 14_400;
+
+    /// The number of blocks a passed fee proposal can be executed for after the
+    /// vote has ended.
+    pub const EXECUTION_PERIOD_BLOCKS: BlockNumber = 14_400;
+
+    /// The share of the LP supply in basis points that has to vote on a fee
+    /// proposal for it to pass.
+    pub const QUORUM_BPS: Balance = 1_000;
+
+    /// The recommended gas limit of calls into other contracts.
+    ///
+    /// Enough for a token, price feed or flash borrower doing a few storage
+    /// reads and writes, while keeping a malicious callee from burning all the
+    /// gas of the caller. Much lower limits make honest callees run out of gas.
+    pub const CALLBACK_GAS_LIMIT: u64 = 5_000_000;
+
+    /// Mints of fewer LP tokens than this lose more than 0.1% of the deposit to
+    /// rounding, which only happens if donations inflated the LP token price.
+    const DONATION_LIQUIDITY_THRESHOLD: Balance = 1_000;
+
+    /// `keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)")`
+    const DOMAIN_TYPE_HASH: [u8; 32] = [
+        0x8b, 0x73, 0xc3, 0xc6, 0x9b, 0xb8, 0xfe, 0x3d, 0x51, 0x2e, 0xcc, 0x4c, 0xf7,
+        0x59, 0xcc, 0x79, 0x23, 0x9f, 0x7b, 0x17, 0x9b, 0x0f, 0xfa, 0xca, 0xa9, 0xa7,
+        0x5d, 0x52, 0x2b, 0x39, 0x40, 0x0f,
+    ];
+
+    /// The selector of `IChainlinkAggregator::latest_answer`.
+    const LATEST_ANSWER_SELECTOR: [u8; 4] = [0x3d, 0x1e, 0x8c, 0xc7];
+
+    /// The selector of `IChainlinkAggregator::decimals`.
+    const DECIMALS_SELECTOR: [u8; 4] = [0xdd, 0x02, 0xcb, 0xc5];
+
+    /// The EIP-712 domain name of the LP token.
+    const DOMAIN_NAME: &[u8] = b"Uniswap V2";
+
+    /// The EIP-712 domain version of the LP token.
+    const DOMAIN_VERSION: &[u8] = b"1";
+
+    /// The selector of the `symbol` message of a token.
+    const SYMBOL_SELECTOR: [u8; 4] = [0x57, 0x17, 0x8a, 0x4a];
+
+    /// The symbol used in place of the symbol of a token without metadata.
+    const UNKNOWN_SYMBOL: &str = "UNKNOWN";
+
+    /// The selector of the `FlashBorrower::on_flash_loan` message of a borrower.
+    const ON_FLASH_LOAN_SELECTOR: [u8; 4] = [0x59, 0xaa, 0x54, 0x48];
+
+    /// The selector of the `on_flash_mint` message of a flash mint receiver.
+    const ON_FLASH_MINT_SELECTOR: [u8; 4] = [0x93, 0x10, 0xb3, 0xd5];
+
+    /// The selector of the `PairObserver::on_sync` message of a reserve
+    /// observer, see the `reserve_observer` example.
+    #[cfg_attr(test, allow(dead_code))]
+    const ON_SYNC_SELECTOR: [u8; 4] = [0x17, 0xc8, 0xf0, 0x95];
+
+    /// The largest number of observers notified of reserve updates.
+    pub const MAX_OBSERVERS: u32 = 5;
+
+    /// The largest number of allowances `batch_approve` sets at once.
+    pub const MAX_BATCH_APPROVALS: u32 = 16;
 
-    use erc20::erc20;
+    /// The selector of the `on_token_approval` message of an approval receiver.
+    const ON_TOKEN_APPROVAL_SELECTOR: [u8; 4] = [0x9d, 0xe7, 0xc1, 0x46];
 
-    const MINIMUM_LIQUIDITY: Balance = 10**3;
+    /// The selector of the `LpTransferHook::on_lp_transfer` message of a
+    /// transfer hook, see the `lp_gauge` example.
+    const ON_LP_TRANSFER_SELECTOR: [u8; 4] = [0xf9, 0x36, 0x12, 0xef];
+
+    /// The decimals of the LP token.
+    const LP_DECIMALS: u8 = 18;
+
+    /// The most decimals `spot_price0_fixed` returns prices with.
+    pub const MAX_PRICE_PRECISION: u32 = 18;
+
+    /// The uniswap pair error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if not enough balance to fulfill a request is available.
+        InsufficientBalance { required: Balance, available: Balance },
+        /// Returned if not enough allowance to fulfill a request is available.
+        InsufficientAllowance { required: Balance, available: Balance },
+        /// Returned if the caller lacks the role required for a request.
+        MissingRole(MissingRole),
+        /// Returned if locked liquidity is withdrawn before its unlock time.
+        LiquidityStillLocked {
+            unlock_timestamp: Timestamp,
+            now: Timestamp,
+        },
+        /// Returned if there is no locked liquidity to withdraw.
+        NoLockedLiquidity,
+        /// Returned if a lock would end earlier than it currently does.
+        LockShortened {
+            current: Timestamp,
+            requested: Timestamp,
+        },
+        /// Returned if the imbalance refund exceeds 100% of the fee.
+        InvalidRefundBps { bps: u8 },
+        /// Returned if a transfer would overflow the balance of the recipient.
+        BalanceOverflow,
+        /// Returned if LP tokens are transferred before `unlock_block`, too soon
+        /// after they were minted.
+        TransferLocked { unlock_block: BlockNumber },
+        /// Returned if liquidity is minted to an account missing from the
+        /// enabled mint whitelist.
+        NotWhitelisted,
+        /// Returned if sweeping the native balance of the pair failed.
+        NativeTransferFailed,
+        /// Returned if the pair has no liquidity to take the token ratio from.
+        EmptyPool,
+        /// Returned if an emergency withdrawal is attempted while not paused.
+        NotPaused,
+        /// Returned if the caller holds no LP tokens to withdraw.
+        NoLiquidity,
+        /// Returned if the maximum price impact exceeds 100%.
+        InvalidPriceImpactBps { bps: u16 },
+        /// Returned if a swap would move the price by more than
+        /// `max_price_impact_bps`.
+        PriceImpactTooHigh { impact_bps: u16, max_bps: u16 },
+        /// Returned if a referral exceeds the largest allowed share.
+        ReferralBpsTooHigh { bps: u16, max_bps: u16 },
+        /// Returned if the swap fee exceeds `math::MAX_FEE_BPS`.
+        InvalidFeeBps { bps: u16 },
+        /// Returned if a request arrives after its deadline.
+        Expired { deadline: Timestamp, now: Timestamp },
+        /// Returned if a flash loan is requested for a token other than the
+        /// two tokens of the pair.
+        UnsupportedToken,
+        /// Returned if a fixed-point price is requested with more than
+        /// `MAX_PRICE_PRECISION` decimals.
+        InvalidPrecision { precision: u32 },
+        /// Returned if a fixed-point price does not fit into a `Balance`.
+        PriceOverflow,
+        /// Returned if a reserve would exceed `math::RESERVE_MAX`.
+        ReserveOverflow,
+        /// Returned if a snapshot has already been restored into the pair.
+        AlreadyRestored,
+        /// Returned if a snapshot is restored into a pair that already has
+        /// liquidity or swaps.
+        PairInUse,
+        /// Returned if there is no fee proposal with the given id.
+        ProposalNotFound,
+        /// Returned if the caller held no LP tokens at the snapshot.
+        NoVotingPower,
+        /// Returned if the caller has already voted on the proposal.
+        AlreadyVoted,
+        /// Returned if a vote arrives after the voting period.
+        VotingClosed,
+        /// Returned if a proposal is executed before the voting period ended.
+        VotingOpen,
+        /// Returned if fewer than `QUORUM_BPS` of the LP supply voted.
+        QuorumNotReached,
+        /// Returned if a proposal got no more votes for than against it.
+        ProposalRejected,
+        /// Returned if a proposal is executed after its execution period.
+        ProposalExpired,
+        /// Returned if a proposal has already been executed.
+        AlreadyExecuted,
+        /// Returned if `skim` is called again before `next_block`.
+        RateLimited { next_block: BlockNumber },
+        /// Returned if `MAX_OBSERVERS` observers are already registered.
+        TooManyObservers,
+        /// Returned if the observer is already registered.
+        ObserverExists,
+        /// Returned if the observer is not registered.
+        ObserverNotFound,
+        /// Returned if liquidity would be added with less than the minimum
+        /// amount of a token.
+        InsufficientAmount { amount: Balance, min: Balance },
+        /// Returned if a swap arrives before `trading_start`.
+        TradingNotStarted {
+            trading_start: Timestamp,
+            now: Timestamp,
+        },
+        /// Returned if the trading start would be moved later.
+        TradingStartPostponed {
+            current: Timestamp,
+            requested: Timestamp,
+        },
+        /// Returned if a blacklisted account sends or receives LP tokens.
+        Blacklisted { account: AccountId },
+        /// Returned if more than `MAX_BATCH_APPROVALS` allowances are set at
+        /// once.
+        TooManyApprovals,
+    }
+
+    impl From<MissingRole> for Error {
+        fn from(missing: MissingRole) -> Self {
+            Error::MissingRole(missing)
+        }
+    }
+
+    /// The uniswap pair result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A summary of whether the pair is usable, as returned by `status`.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct PoolStatus {
+        /// The pair has been set up with its tokens.
+        pub initialized: bool,
+        /// Minting and swapping have been paused by a guardian.
+        pub paused: bool,
+        /// A call into the pair is currently in progress.
+        pub locked: bool,
+        /// LP tokens have been minted.
+        pub has_liquidity: bool,
+    }
+
+    /// The liquidity position of an account, as returned by `position`.
+    ///
+    /// The pair does not store a router, so the position has no allowance to
+    /// one. Tools can read it with `allowance(account, router)`.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Position {
+        /// The LP tokens held by the account.
+        pub lp_balance: Balance,
+        /// The share of the total supply in basis points, rounded down.
+        pub share_bps: u16,
+        /// The amount of token0 the LP tokens are worth.
+        pub underlying0: Balance,
+        /// The amount of token1 the LP tokens are worth.
+        pub underlying1: Balance,
+    }
+
+    /// A deposit minted by `mint_position`, as returned by `position_of`.
+    #[derive(
+        Debug,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct LiquidityPosition {
+        /// The account the LP tokens were minted to.
+        pub owner: AccountId,
+        /// The LP tokens minted for the deposit.
+        pub liquidity: Balance,
+        /// The amount of token0 deposited.
+        pub token0_deposited: Balance,
+        /// The amount of token1 deposited.
+        pub token1_deposited: Balance,
+    }
+
+    /// The all-time trading statistics of the pair, as returned by `get_stats`.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct PairStats {
+        /// The amount of token0 swapped into the pair.
+        pub volume0: Balance,
+        /// The amount of token1 swapped into the pair.
+        pub volume1: Balance,
+        /// The fees collected in token0.
+        pub fees0: Balance,
+        /// The fees collected in token1.
+        pub fees1: Balance,
+        /// The number of swaps.
+        pub swap_count: u64,
+    }
+
+    /// The intermediate values of a swap quote, as returned by
+    /// `debug_swap_state`.
+    #[cfg(feature = "debug")]
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct SwapDebugState {
+        /// The reserve of the token swapped in.
+        pub reserve_in: Balance,
+        /// The reserve of the token swapped out.
+        pub reserve_out: Balance,
+        /// The swap fee in basis points.
+        pub fee_bps: Balance,
+        /// The fee taken from the input.
+        pub fee: Balance,
+        /// The output of the swap.
+        pub amount_out: Balance,
+        /// The reserve of token0 after the swap.
+        pub reserve0: Balance,
+        /// The reserve of token1 after the swap.
+        pub reserve1: Balance,
+        /// The price impact of the swap in basis points.
+        pub price_impact_bps: Balance,
+    }
+
+    /// A proposal of LP holders to change the swap fee.
+    #[derive(
+        Debug,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct FeeProposal {
+        /// The proposed swap fee in basis points.
+        pub fee_bps: u16,
+        /// The block at whose start the voting power is taken.
+        pub snapshot_block: BlockNumber,
+        /// The LP tokens voting for the proposal.
+        pub votes_for: Balance,
+        /// The LP tokens voting against the proposal.
+        pub votes_against: Balance,
+        /// Whether the proposal has been executed.
+        pub executed: bool,
+    }
+
+    /// The reserves and statistics of a pair, as returned by `take_snapshot`.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct PairSnapshot {
+        /// The block the snapshot was taken in.
+        pub block: BlockNumber,
+        /// The reserve of token0.
+        pub reserve0: Balance,
+        /// The reserve of token1.
+        pub reserve1: Balance,
+        /// The swap fee in basis points.
+        pub fee_bps: u16,
+        /// The all-time trading statistics.
+        pub stats: PairStats,
+    }
+
+    /// The reserves of the pair compared to its token balances, as returned by
+    /// `reconcile`.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ReconcileReport {
+        /// The recorded reserve of token0.
+        pub reserve0: Balance,
+        /// The recorded reserve of token1.
+        pub reserve1: Balance,
+        /// The token0 balance of the pair.
+        pub balance0: Balance,
+        /// The token1 balance of the pair.
+        pub balance1: Balance,
+        /// The amount of token0 the balance exceeds the reserve by.
+        pub surplus0: Balance,
+        /// The amount of token1 the balance exceeds the reserve by.
+        pub surplus1: Balance,
+        /// The amount of token0 the balance falls short of the reserve by.
+        pub deficit0: Balance,
+        /// The amount of token1 the balance falls short of the reserve by.
+        pub deficit1: Balance,
+    }
 
     #[ink(storage)]
+    #[allow(non_camel_case_types)]
     pub struct Uniswap_pair {
-        owner:    AccountId,
-        token0:   Lazy<erc20>,
-        token1:   Lazy<erc20>,
+        /// The roles guarding the privileged messages.
+        roles:    Roles,
+        token0:   Lazy<Erc20>,
+        token1:   Lazy<Erc20>,
 
         reserve0: Balance,
         reserve1: Balance,
@@ -45,9 +480,128 @@ pub mod uniswap_pair {
         /// Mapping of the token amount which an account is allowed to withdraw
         /// from another account.
         allowances: StorageHashMap<(AccountId, AccountId), Balance>,
+        /// Mapping from owner to the amount of LP tokens held in custody by the
+        /// pair and the time they are released.
+        liquidity_locks: StorageHashMap<AccountId, (Balance, Timestamp)>,
+        /// Whether the pair has been set up with its tokens.
+        initialized: bool,
+        /// Whether minting and swapping have been paused.
+        paused: bool,
+        /// The reentrancy guard of `mint`, `burn` and `swap`.
+        locked: bool,
+        /// The share of the swap fee in percent refunded to swaps that move the
+        /// reserves closer to equal.
+        imbalance_refund_bps: Lazy<u8>,
+        /// The amount of each token the first mint has to deposit at least.
+        initial_mint_minimum: Lazy<Balance>,
+        /// The LP tokens locked in the pair by the first mint.
+        minimum_liquidity: Lazy<Balance>,
+        /// The all-time fees collected in token0.
+        fees_collected0: Balance,
+        /// The all-time fees collected in token1.
+        fees_collected1: Balance,
+        /// The all-time fee in token0 per LP token as a Q128.128 number. Wraps
+        /// around on overflow.
+        fee_growth_global0: Lazy<FeeGrowth>,
+        /// The all-time fee in token1 per LP token as a Q128.128 number. Wraps
+        /// around on overflow.
+        fee_growth_global1: Lazy<FeeGrowth>,
+        /// The fee growth of both tokens at the last LP balance change of each
+        /// account.
+        fee_growth_inside_last: StorageHashMap<AccountId, (FeeGrowth, FeeGrowth)>,
+        /// The fees of both tokens earned by each account until its last LP
+        /// balance change.
+        fees_owed: StorageHashMap<AccountId, (Balance, Balance)>,
+        /// The all-time amount of token0 swapped into the pair.
+        volume0_cumulative: Balance,
+        /// The all-time amount of token1 swapped into the pair.
+        volume1_cumulative: Balance,
+        /// The all-time number of swaps.
+        swap_count: u64,
+        /// The smallest input a swap may pay, or zero if any input is accepted.
+        min_swap_amount: Lazy<Balance>,
+        /// The swap fee in basis points.
+        fee_bps: Lazy<u16>,
+        /// The largest price impact in basis points a swap may have, zero if
+        /// unlimited.
+        max_price_impact_bps: Lazy<u16>,
+        /// The largest share of the output in basis points paid to referrers.
+        max_referral_bps: Lazy<u16>,
+        /// The block in which each account last received minted LP tokens.
+        mint_block: StorageHashMap<AccountId, BlockNumber>,
+        /// The number of blocks freshly minted LP tokens cannot be transferred for.
+        transfer_lock_blocks: Lazy<BlockNumber>,
+        /// The number of blocks between two skims, zero if skims are not
+        /// limited.
+        skim_interval_blocks: Lazy<BlockNumber>,
+        /// The block of the last skim, if any.
+        last_skim_block: Option<BlockNumber>,
+        /// Whether only whitelisted accounts may receive minted liquidity.
+        mint_whitelist_enabled: Lazy<bool>,
+        /// The accounts that may receive minted liquidity while the whitelist
+        /// is enabled.
+        mint_whitelist: StorageHashMap<AccountId, bool>,
+        /// The accounts that may neither send nor receive LP tokens.
+        blacklisted: StorageHashMap<AccountId, bool>,
+        /// The block the pair was created in.
+        created_at: BlockNumber,
+        /// The time the pair was created at.
+        created_timestamp: Timestamp,
+        /// The chain the domain separator was computed for.
+        chain_id: Lazy<u64>,
+        /// The cached EIP-712 domain separator.
+        domain_separator: Lazy<[u8; 32]>,
+        /// The cached name and symbol of the LP token, composed on first query.
+        lp_metadata: Lazy<Option<(String, String)>>,
+        /// The number of accounts other than the pair holding LP tokens.
+        holder_count: u32,
+        /// The gas limit of calls into other contracts.
+        callback_gas_limit: Lazy<u64>,
+        /// The contract notified of every LP token movement, if any.
+        transfer_hook: Lazy<Option<AccountId>>,
+        /// The contracts notified of every update of the reserves.
+        observers: StorageVec<AccountId>,
+        /// The fee of flash mints in basis points.
+        flash_mint_fee_bps: Lazy<u16>,
+        /// The token whose holders get part of the swap fee as a rebate.
+        governance_token: Lazy<AccountId>,
+        /// The governance tokens a swapper has to hold for a rebate, zero if
+        /// rebates are disabled.
+        rebate_threshold: Lazy<Balance>,
+        /// Whether swaps sync the reserves down to the balances after a token
+        /// rebased down instead of reverting.
+        auto_sync: Lazy<bool>,
+        /// The time swaps are allowed from, `None` if swaps are always allowed.
+        trading_start: Lazy<Option<Timestamp>>,
+        /// Whether swaps also emit `SwapLegacy` for indexers of the previous
+        /// event layout.
+        legacy_events: bool,
+        /// The balance history of each account, or of the total supply under
+        /// `None`, as `(block, balance)` keyed by position.
+        checkpoints: StorageHashMap<(Option<AccountId>, u32), (BlockNumber, Balance)>,
+        /// The number of entries in the balance history of each account, or
+        /// of the total supply under `None`.
+        checkpoint_counts: StorageHashMap<Option<AccountId>, u32>,
+        /// Whether a snapshot has been restored into the pair.
+        snapshot_restored: bool,
+        /// The fee proposals, keyed by id.
+        fee_proposals: StorageHashMap<u32, FeeProposal>,
+        /// The number of fee proposals.
+        fee_proposal_count: u32,
+        /// Whether each account voted for or against each proposal.
+        fee_votes: StorageHashMap<(u32, AccountId), bool>,
+        /// The time of the last update of the reserves.
+        reserves_updated_at: Timestamp,
+        /// The number of updates of the reserves, the round of the price feed.
+        price_round: u128,
+        /// The deposits minted by `mint_position`, keyed by id.
+        positions: StorageHashMap<u128, LiquidityPosition>,
+        /// The id of the next deposit minted by `mint_position`.
+        next_position_id: u128,
     }
 
     #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub struct Sync {
         #[ink(topic)]
         reserve0: Balance,
@@ -56,6 +610,7 @@ pub mod uniswap_pair {
     }
 
     #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub struct Mint {
         #[ink(topic)]
         sender: AccountId,
@@ -66,6 +621,7 @@ pub mod uniswap_pair {
     }
 
     #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub struct Burn {
         #[ink(topic)]
         sender: AccountId,
@@ -77,332 +633,6905 @@ pub mod uniswap_pair {
         to: AccountId,
     }
 
+    /// Event emitted next to `Swap` by pairs created with `legacy_events`.
+    ///
+    /// Lays out the fields like the `Swap` event of the previous deployment,
+    /// with the recipient right after the sender.
     #[ink(event)]
-    pub struct Swap {
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[allow(non_snake_case)]
+    pub struct SwapLegacy {
         #[ink(topic)]
         sender: AccountId,
         #[ink(topic)]
+        to: AccountId,
         amount0In: Balance,
-        #[ink(topic)]
         amount1In: Balance,
-        #[ink(topic)]
         amount0Out: Balance,
+        amount1Out: Balance,
+    }
+
+    /// Event emitted by every swap.
+    ///
+    /// Like in UniswapV2 only the sender and the recipient are topics.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[allow(non_snake_case)]
+    pub struct Swap {
         #[ink(topic)]
+        sender: AccountId,
+        amount0In: Balance,
+        amount1In: Balance,
+        amount0Out: Balance,
         amount1Out: Balance,
         #[ink(topic)]
         to: AccountId,
     }
 
-    /// Event emitted when a token transfer occurs.
+    /// Event emitted when `owner` locks `amount` more LP tokens, or extends the
+    /// lock with an `amount` of zero, until `unlock_timestamp`.
     #[ink(event)]
-    pub struct Transfer {
-        #[ink(topic)]
-        from: Option<AccountId>,
-        #[ink(topic)]
-        to: Option<AccountId>,
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct LiquidityLocked {
         #[ink(topic)]
-        value: Balance,
+        owner: AccountId,
+        amount: Balance,
+        unlock_timestamp: Timestamp,
     }
 
-    /// up to the amount of `value` tokens from `owner`.
+    /// Event emitted when `owner` withdraws `amount` LP tokens locked until
+    /// `unlock_timestamp`.
     #[ink(event)]
-    pub struct Approval {
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct LiquidityUnlocked {
         #[ink(topic)]
         owner: AccountId,
+        amount: Balance,
+        unlock_timestamp: Timestamp,
+    }
+
+    /// Event emitted when `role` is granted to `account` by `sender`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct RoleGranted {
         #[ink(topic)]
-        spender: AccountId,
+        role: RoleId,
         #[ink(topic)]
-        value: Balance,
+        account: AccountId,
+        sender: AccountId,
     }
 
-    impl Uniswap_pair {
-        /// Creates a new uniswap_pair smart contract initialized with the given value.
-        #[ink(constructor)]
-        pub fn new(token0: AccountId, token1: AccountId) -> Self {
-            Self { owner : Self.env().caller(),
-                   token0: erc20::new(token0),  
-                   token1: erc20::new(token1), 
-                   //lp_token: mpa20::new(lp_token),
-                   total_supply: Lazy::new(0),
-                   balances:StorageHashMap::new(),
-                   allowances: StorageHashMap::new(),
-            }
-        }
-
-        #[ink(message)]
-        pub fn mint(&mut self, to: AccountId) {
-            let self_account_id = self.env().account_id();
-
-            let balance0 = self.token0.balance_of_or_zero(self_account_id);
-            let balance1 = self.token1.balance_of_or_zero(self_account_id);
+    /// Event emitted when `role` is revoked from `account` by `sender`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: RoleId,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
 
-            let amount0  = balance0 - self.reserve0;
-            let amount1  = balance1 - self.reserve1;
+    /// Event emitted when an owner adds `account` to or removes it from the
+    /// mint whitelist.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct WhitelistUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        allowed: bool,
+    }
 
-            let total_supply = self.lp_token.total_supply();
+    /// Event emitted when an owner freezes the LP tokens of `account`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Blacklisted {
+        #[ink(topic)]
+        account: AccountId,
+    }
 
-            let mut liquidity: Balance = 0;
+    /// Event emitted when an owner unfreezes the LP tokens of `account`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Unblacklisted {
+        #[ink(topic)]
+        account: AccountId,
+    }
 
-            if total_supply == 0 {
-                liquidity = math::sqrt(amount0 * amount1) - MINIMUM_LIQUIDITY;
-                _mint(self, self_account_id, MINIMUM_LIQUIDITY)
-            } else {
-                liquidity = math::min(amount0 * total_supply / self.reserve0, amount1 * total_supply / self.reserve1);
-            }
+    /// Event emitted when an owner sweeps `amount` of stranded native currency
+    /// to `to`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct NativeSwept {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
 
-            assert!(liquidity > 0, "Uniswap: INSUFFICIENT_LIQUIDITY_MINTED");
+    /// Event emitted when a guardian pauses the pair.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+    }
 
-            _mint(self, to, liquidity);
-            update(balance0, balance1, self.reserve0, self.reserve1);
+    /// Event emitted when a guardian unpauses the pair.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+    }
 
-            self.env().emit_event(Mint(self.env().caller(), amount0, amount1));           
-        }
+    /// Event emitted when `account` burns `liquidity` LP tokens for `amount0`
+    /// and `amount1` while the pair is paused.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct EmergencyWithdraw {
+        #[ink(topic)]
+        account: AccountId,
+        liquidity: Balance,
+        amount0: Balance,
+        amount1: Balance,
+    }
 
-        #[ink(message)]
-        pub fn burn(&mut self, to: AccountId) {
+    /// Event emitted when a swap to `to` pays `amount` of its output to
+    /// `referrer`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Referral {
+        #[ink(topic)]
+        referrer: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
 
-            assert!(self.env().caller() == to, "Uniswap: auth mismatch"); 
+    /// Event emitted when `receiver` repaid a flash loan of `amount` of `token`
+    /// together with `fee`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FlashLoan {
+        #[ink(topic)]
+        receiver: AccountId,
+        #[ink(topic)]
+        token: AccountId,
+        amount: Balance,
+        fee: Balance,
+    }
 
-            let self_account_id = self.env().account_id();
+    /// Event emitted when `receiver` repaid a flash mint of `amount` LP tokens
+    /// together with `fee`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FlashMint {
+        #[ink(topic)]
+        receiver: AccountId,
+        amount: Balance,
+        fee: Balance,
+    }
 
-            let mut balance0 = self.token0.balance_of_or_zero(self_account_id);
-            let mut balance1 = self.token1.balance_of_or_zero(self_account_id);
+    /// Event emitted when the call of the transfer hook or observer `hook`
+    /// failed.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct HookFailed {
+        #[ink(topic)]
+        hook: AccountId,
+    }
 
-            let liquidity    = self.lp_token.balance_of_or_zero(to);
-            let total_supply = self.lp_token.total_supply();
+    /// Event emitted when a swap by a holder of governance tokens pays `amount`
+    /// more of its output token to `to`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct HolderRebate {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
 
-            let amount0 = liquidity * balance0 / total_supply;
-            let amount1 = liquidity * balance1 / total_supply;   
+    /// Event emitted when a swap that reduced the reserve imbalance is refunded
+    /// `amount` of its input token.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ImbalanceRefund {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
 
-            assert!(amount0 > 0 && amount1 > 0, "Uniswap: INSUFFICIENT_LIQUIDITY_BURNED"); 
+    /// Event emitted when an owner changes the smallest accepted swap input.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct MinSwapAmountChanged {
+        min_swap_amount: Balance,
+    }
 
-            _burn(self, to, liquidity);   
+    /// Event emitted when an owner changes the swap fee.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FeeBpsChanged {
+        fee_bps: u16,
+    }
 
-            self.token0.transfer_from(self, to, amount0);
-            self.token1.transfer_from(self, to, amount1);
+    /// Event emitted when an LP holder proposes to change the swap fee.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FeeProposed {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        proposer: AccountId,
+        fee_bps: u16,
+    }
 
-            balance0 = self.token0.balance_of_or_zero(self_account_id);
-            balance1 = self.token1.balance_of_or_zero(self_account_id);
+    /// Event emitted when an LP holder votes on a fee proposal.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FeeVoted {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        voter: AccountId,
+        support: bool,
+        weight: Balance,
+    }
 
-            update(balance0, balance1, self.reserve0, self.reserve1);
+    /// Event emitted when a passed fee proposal is executed.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FeeProposalExecuted {
+        #[ink(topic)]
+        proposal_id: u32,
+    }
 
-            self.env().emit_event(Burn(self.env().caller(), amount0, amount1, to));       
-        }
+    /// Event emitted when an owner changes the largest accepted price impact.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct MaxPriceImpactChanged {
+        max_price_impact_bps: u16,
+    }
 
-        #[ink(message)]
-        pub fn swap(&mut self, amount0Out: Balance, amount1Out: Balance, to: AccountId) {
+    /// Event emitted when a token transfer occurs.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        #[ink(topic)]
+        value: Balance,
+    }
 
-            assert!(amount0Out > 0 || amount1Out > 0, "Uniswap: INSUFFICIENT_OUTPUT_AMOUNT"); 
-            assert!(amount0Out < self.reserve0 && amount1Out < self.reserve1, "Uniswap: INSUFFICIENT_LIQUIDITY"); 
-            //assert!(to != self.token0.get_address() && to != self.token1.get_address(), "Uniswap: INVALID_TO"); 
-            let self_account_id = self.env().account_id();
+    /// up to the amount of `value` tokens from `owner`.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        #[ink(topic)]
+        value: Balance,
+    }
 
-            if amount0Out {
-                self.token0.transfer_from(self, to, amount0Out);
-            }  
+    /// The events of one operation, such as `Sync` and `Mint` of a mint,
+    /// collected to be emitted together by `emit_batch`.
+    ///
+    /// The runtime still emits the events one by one, but a batch emits all of
+    /// them in the order they were collected, after the operation completed.
+    #[cfg(not(feature = "ink-as-dependency"))]
+    #[derive(Default)]
+    pub struct EventBatch {
+        events: Vec<<Uniswap_pair as ink_lang::BaseEvent>::Type>,
+        /// Whether `push_sync` changed the reserves for the batch.
+        synced: bool,
+    }
 
-            if amount1Out {
-                self.token1.transfer_from(self, to, amount1Out);
-            } 
+    #[cfg(not(feature = "ink-as-dependency"))]
+    impl<'a> EmitEvent<Uniswap_pair> for &'a mut EventBatch {
+        fn emit_event<E>(self, event: E)
+        where
+            E: Into<<Uniswap_pair as ink_lang::BaseEvent>::Type>,
+        {
+            self.events.push(event.into());
+        }
+    }
 
-            let mut balance0 = self.token0.balance_of_or_zero(self_account_id);
-            let mut balance1 = self.token1.balance_of_or_zero(self_account_id);
+    /// Returns `keccak256(abi.encode(DOMAIN_TYPE_HASH, keccak256(name),
+    /// keccak256(version), chain_id, verifying_contract))`.
+    ///
+    /// The 32 bytes of `verifying_contract` take the place of the left padded
+    /// 20 byte address.
+    fn domain_separator_of(
+        name: &[u8],
+        version: &[u8],
+        chain_id: u64,
+        verifying_contract: AccountId,
+    ) -> [u8; 32] {
+        let mut name_hash = [0x0; 32];
+        ink_env::hash_bytes::<Keccak256>(name, &mut name_hash);
+        let mut version_hash = [0x0; 32];
+        ink_env::hash_bytes::<Keccak256>(version, &mut version_hash);
+        let mut encoded = [0x0; 160];
+        encoded[..32].copy_from_slice(&DOMAIN_TYPE_HASH);
+        encoded[32..64].copy_from_slice(&name_hash);
+        encoded[64..96].copy_from_slice(&version_hash);
+        encoded[120..128].copy_from_slice(&chain_id.to_be_bytes());
+        scale::Encode::using_encoded(&verifying_contract, |bytes| {
+            encoded[128..].copy_from_slice(bytes)
+        });
+        let mut output = [0x0; 32];
+        ink_env::hash_bytes::<Keccak256>(&encoded, &mut output);
+        output
+    }
 
-            let amount0In = if balance0 > self.reserve0 - amount0Out {
-                 balance0 - (self.reserve0 - amount0Out)
-            } else {
-                0
-            };
+    /// Returns the name and symbol of the LP token of a pair of tokens with the
+    /// symbols `symbol0` and `symbol1`.
+    ///
+    /// A token without metadata is named `UNKNOWN`.
+    fn lp_metadata_of(
+        symbol0: Option<String>,
+        symbol1: Option<String>,
+    ) -> (String, String) {
+        let symbol0 = symbol0.unwrap_or_else(|| String::from(UNKNOWN_SYMBOL));
+        let symbol1 = symbol1.unwrap_or_else(|| String::from(UNKNOWN_SYMBOL));
+        (
+            format!("Uniswap V2 {}/{}", symbol0, symbol1),
+            format!("{}-{}-LP", symbol0, symbol1),
+        )
+    }
 
-            let amount1In = if balance1 > self.reserve1 - amount1Out {
-                 balance1 - (self.reserve1 - amount1Out)
-            } else {
-                0
-            };
+    impl Uniswap_pair {
+        /// Creates a new uniswap_pair smart contract initialized with the given value.
+        ///
+        /// The contract cannot query the chain it runs on, so `chain_id` is
+        /// passed in to compute the EIP-712 domain separator.
+        ///
+        /// Calls into other contracts may use up to `max_callback_gas`, see
+        /// `CALLBACK_GAS_LIMIT` for the recommended value. Zero forwards all the
+        /// remaining gas.
+        ///
+        /// Swappers holding at least `rebate_threshold` of `governance_token`
+        /// get `HOLDER_REBATE_PERCENT` of the swap fee added to their output. A
+        /// threshold of zero disables the rebates.
+        ///
+        /// The first mint locks `minimum_liquidity` LP tokens in the pair, see
+        /// `MINIMUM_LIQUIDITY` for the recommended value. Tokens with few
+        /// decimals need a smaller, tokens with many decimals a larger amount.
+        ///
+        /// Swaps are rejected before `trading_start`, so liquidity can be added
+        /// before the pair can be traded. `None` allows swaps right away.
+        ///
+        /// With `legacy_events` every swap emits a `SwapLegacy` event after the
+        /// `Swap` event. The setting cannot be changed later.
+        ///
+        /// # Panics
+        ///
+        /// If `minimum_liquidity` is outside of
+        /// `[MIN_MINIMUM_LIQUIDITY, MAX_MINIMUM_LIQUIDITY]`.
+        #[ink(constructor)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn new(
+            token0: AccountId,
+            token1: AccountId,
+            chain_id: u64,
+            max_callback_gas: u64,
+            governance_token: AccountId,
+            rebate_threshold: Balance,
+            minimum_liquidity: Balance,
+            trading_start: Option<Timestamp>,
+            legacy_events: bool,
+        ) -> Self {
+            let mut pair = Self::default();
+            pair.init(
+                token0,
+                token1,
+                chain_id,
+                max_callback_gas,
+                governance_token,
+                rebate_threshold,
+                minimum_liquidity,
+                trading_start,
+                legacy_events,
+            );
+            pair
+        }
 
-            assert!(amount0In > 0 || amount1Out > 0, "Uniswap: INSUFFICIENT_INPUT_AMOUNT"); 
-            { 
-                let balance0Adjusted = balance0 * 1000 - amount0In * 3;
-                let balance1Adjusted = balance1 * 1000 - amount1In * 3;
-                assert!(balance0Adjusted * balance1Adjusted >= self.reserve0 * self.reserve1 * 1000 * 1000, "Uniswap: K"); 
+        /// Creates a pair with all storage set to zero values that is not
+        /// initialized yet.
+        ///
+        /// The caller becomes the owner and has to call `initialize` before
+        /// liquidity can be added or swapped.
+        #[ink(constructor)]
+        pub fn default() -> Self {
+            let caller = Self::env().caller();
+            let zero_account = AccountId::from([0x0; 32]);
+            let mut roles = Roles::new();
+            roles.setup_role(DEFAULT_ADMIN_ROLE, caller);
+            roles.setup_role(OWNER_ROLE, caller);
+            Self { roles,
+                   token0: Lazy::new(FromAccountId::from_account_id(zero_account)),
+                   token1: Lazy::new(FromAccountId::from_account_id(zero_account)),
+                   reserve0: 0,
+                   reserve1: 0,
+                   total_supply: Lazy::new(0),
+                   balances:StorageHashMap::new(),
+                   allowances: StorageHashMap::new(),
+                   liquidity_locks: StorageHashMap::new(),
+                   initialized: false,
+                   paused: false,
+                   locked: false,
+                   imbalance_refund_bps: Lazy::new(0),
+                   initial_mint_minimum: Lazy::new(0),
+                   minimum_liquidity: Lazy::new(MINIMUM_LIQUIDITY),
+                   fees_collected0: 0,
+                   fees_collected1: 0,
+                   fee_growth_global0: Lazy::new((0, 0)),
+                   fee_growth_global1: Lazy::new((0, 0)),
+                   fee_growth_inside_last: StorageHashMap::new(),
+                   fees_owed: StorageHashMap::new(),
+                   volume0_cumulative: 0,
+                   volume1_cumulative: 0,
+                   swap_count: 0,
+                   min_swap_amount: Lazy::new(0),
+                   fee_bps: Lazy::new(math::FEE_BPS as u16),
+                   max_price_impact_bps: Lazy::new(0),
+                   max_referral_bps: Lazy::new(0),
+                   mint_block: StorageHashMap::new(),
+                   transfer_lock_blocks: Lazy::new(0),
+                   skim_interval_blocks: Lazy::new(0),
+                   last_skim_block: None,
+                   mint_whitelist_enabled: Lazy::new(false),
+                   mint_whitelist: StorageHashMap::new(),
+                   blacklisted: StorageHashMap::new(),
+                   created_at: Self::env().block_number(),
+                   created_timestamp: Self::env().block_timestamp(),
+                   chain_id: Lazy::new(0),
+                   domain_separator: Lazy::new([0x0; 32]),
+                   lp_metadata: Lazy::new(None),
+                   holder_count: 0,
+                   callback_gas_limit: Lazy::new(0),
+                   transfer_hook: Lazy::new(None),
+                   observers: StorageVec::new(),
+                   flash_mint_fee_bps: Lazy::new(0),
+                   governance_token: Lazy::new(zero_account),
+                   rebate_threshold: Lazy::new(0),
+                   auto_sync: Lazy::new(false),
+                   trading_start: Lazy::new(None),
+                   legacy_events: false,
+                   checkpoints: StorageHashMap::new(),
+                   checkpoint_counts: StorageHashMap::new(),
+                   snapshot_restored: false,
+                   fee_proposals: StorageHashMap::new(),
+                   fee_proposal_count: 0,
+                   fee_votes: StorageHashMap::new(),
+                   reserves_updated_at: 0,
+                   price_round: 0,
+                   positions: StorageHashMap::new(),
+                   next_position_id: 0,
             }
-
-            update(balance0, balance1, self.reserve0, self.reserve1);
-
-            self.env().emit_event(Swap(self.env().caller(), amount0In, amount1In, amount0Out, amount1Out, to));       
         }
 
+        /// Initializes a pair created by `default`, see `new` for the
+        /// parameters.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        ///
+        /// # Panics
+        ///
+        /// - If the pair is already initialized.
+        /// - If `minimum_liquidity` is outside of
+        ///   `[MIN_MINIMUM_LIQUIDITY, MAX_MINIMUM_LIQUIDITY]`.
         #[ink(message)]
-        fn skim(&mut self, to: AccountId){ 
-            assert!(self.env().caller() == self.owner, "Uniswap: auth mismatch"); 
-            self.token0.transfer_from(self, to, self.token0.balance_of_or_zero(self) - self.reserve0);
-            self.token1.transfer_from(self, to, self.token1.balance_of_or_zero(self) - self.reserve1);
+        #[allow(clippy::too_many_arguments)]
+        pub fn initialize(
+            &mut self,
+            token0: AccountId,
+            token1: AccountId,
+            chain_id: u64,
+            max_callback_gas: u64,
+            governance_token: AccountId,
+            rebate_threshold: Balance,
+            minimum_liquidity: Balance,
+            trading_start: Option<Timestamp>,
+            legacy_events: bool,
+        ) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            self.init(
+                token0,
+                token1,
+                chain_id,
+                max_callback_gas,
+                governance_token,
+                rebate_threshold,
+                minimum_liquidity,
+                trading_start,
+                legacy_events,
+            );
+            Ok(())
         }
 
-        #[ink(message)]
-        fn sync(&mut self){ 
-            assert!(self.env().caller() == self.owner, "Uniswap: auth mismatch"); 
-            update(self.token0.balance_of_or_zero(self), 
-                   self.token1.balance_of_or_zero(self), 
-                   self.reserve0, 
-                   self.reserve1);
+        /// Returns `a` and `b` in ascending byte order, the canonical order of
+        /// the tokens of a pair.
+        ///
+        /// # Panics
+        ///
+        /// If `a` and `b` are identical or one of them is the zero address.
+        pub fn sort_tokens(a: AccountId, b: AccountId) -> (AccountId, AccountId) {
+            assert!(a != b, "Uniswap: IDENTICAL_ADDRESSES");
+            let (token0, token1) = if a < b { (a, b) } else { (b, a) };
+            assert!(token0 != AccountId::from([0x0; 32]), "Uniswap: ZERO_ADDRESS");
+            (token0, token1)
         }
 
-
-        /// Returns the total token supply.
+        /// Mints LP tokens to `to` for the token balances of the pair above its
+        /// reserves, which the caller transferred to the pair beforehand.
+        ///
+        /// On success a `Mint` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotWhitelisted` if the mint whitelist is enabled and `to`
+        ///   is not on it.
+        /// - Returns `Blacklisted` if `to` is blacklisted.
+        /// - Returns `ReserveOverflow` if a reserve would exceed
+        ///   `math::RESERVE_MAX`.
+        ///
+        /// # Panics
+        ///
+        /// - If the pair is paused.
+        /// - If the deposit mints no LP tokens.
         #[ink(message)]
-        pub fn total_supply(&self) -> Balance {
-            *self.total_supply
+        pub fn mint(&mut self, to: AccountId) -> Result<()> {
+            self.ensure_not_paused();
+            self.ensure_whitelisted(to)?;
+            self.ensure_not_blacklisted(to)?;
+            self.lock();
+            let self_account_id = self.env().account_id();
+            let amount0 = self.token0.balance_of(self_account_id) - self.reserve0;
+            let amount1 = self.token1.balance_of(self_account_id) - self.reserve1;
+            let minted = self.mint_deposit(self.env().caller(), to, amount0, amount1);
+            self.unlock();
+            minted.map(|_| ())
         }
 
-         /// Returns the account balance for the specified `owner`.
+        /// Deposits `amount0` of token0 and `amount1` of token1 from the caller,
+        /// mints LP tokens for them to `to` and records the deposit as a
+        /// position.
         ///
-        /// Returns `0` if the account is non-existent.
-        #[ink(message)]
-        pub fn balance_of(&self, owner: AccountId) -> Balance {
-            self.balances.get(&owner).copied().unwrap_or(0)
-        }
-           
-        /// Returns the amount which `spender` is still allowed to withdraw from `owner`.
+        /// The caller must have approved the pair to spend both amounts. The LP
+        /// tokens are fungible as for `mint`, the position only records the
+        /// deposit for tracking it separately later.
         ///
-        /// Returns `0` if no allowance has been set `0`.
+        /// Returns the id of the position.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotWhitelisted` if the mint whitelist is enabled and `to`
+        ///   is not on it.
+        /// - Returns `ReserveOverflow` if a reserve would exceed
+        ///   `math::RESERVE_MAX` after the deposit.
+        ///
+        /// # Panics
+        ///
+        /// - If a token transfer fails or the deposit mints no LP tokens.
+        /// - If a reserve exceeds `math::RESERVE_MAX` after the transfers.
         #[ink(message)]
-        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
-            self.allowances.get(&(owner, spender)).copied().unwrap_or(0)
+        pub fn mint_position(
+            &mut self,
+            to: AccountId,
+            amount0: Balance,
+            amount1: Balance,
+        ) -> Result<u128> {
+            self.ensure_not_paused();
+            self.ensure_whitelisted(to)?;
+            self.ensure_not_blacklisted(to)?;
+            self.lock();
+            let sender = self.env().caller();
+            let self_account_id = self.env().account_id();
+            if let Err(error) = self.ensure_deposit_fits(amount0, amount1) {
+                self.unlock();
+                return Err(error)
+            }
+            assert!(
+                self.token0
+                    .transfer_from(sender, self_account_id, amount0)
+                    .is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            assert!(
+                self.token1
+                    .transfer_from(sender, self_account_id, amount1)
+                    .is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            // Fee on transfer tokens deliver less than the amounts sent.
+            let amount0 = self.token0.balance_of(self_account_id) - self.reserve0;
+            let amount1 = self.token1.balance_of(self_account_id) - self.reserve1;
+            let id = self.mint_for_position(sender, to, amount0, amount1);
+            assert!(id.is_ok(), "Uniswap: RESERVE_OVERFLOW");
+            self.unlock();
+            id
         }
 
-        /// Transfers `value` amount of tokens from the caller's account to account `to`.
+        /// Adds liquidity from the caller at the ratio of the reserves and mints
+        /// the LP tokens to `to`.
         ///
-        /// On success a `Transfer` event is emitted.
+        /// Uses all of one desired amount and as much of the other as the ratio
+        /// allows. Only the used amounts are transferred from the caller, who must
+        /// have approved the pair to spend them.
+        ///
+        /// Returns the LP tokens minted.
         ///
         /// # Errors
         ///
-        /// Returns `InsufficientBalance` error if there are not enough tokens on
-        /// the caller's account balance.
+        /// - Returns `InsufficientAmount` if less than `amount0_min` of token0 or
+        ///   `amount1_min` of token1 would be used.
+        /// - Returns `NotWhitelisted` if the mint whitelist is enabled and `to` is
+        ///   not on it.
+        /// - Returns `ReserveOverflow` if a reserve would exceed
+        ///   `math::RESERVE_MAX` after the deposit.
+        ///
+        /// # Panics
+        ///
+        /// - If a token transfer fails or the deposit mints no LP tokens.
+        /// - If a reserve exceeds `math::RESERVE_MAX` after the transfers.
         #[ink(message)]
-        pub fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
-            let from = self.env().caller();
-            self.transfer_from_to(from, to, value)
+        pub fn add_liquidity(
+            &mut self,
+            to: AccountId,
+            amount0_desired: Balance,
+            amount1_desired: Balance,
+            amount0_min: Balance,
+            amount1_min: Balance,
+        ) -> Result<Balance> {
+            self.ensure_not_paused();
+            self.ensure_whitelisted(to)?;
+            self.ensure_not_blacklisted(to)?;
+            let (amount0, amount1) = self.add_liquidity_amounts(
+                amount0_desired,
+                amount1_desired,
+                amount0_min,
+                amount1_min,
+            )?;
+            self.lock();
+            let sender = self.env().caller();
+            let self_account_id = self.env().account_id();
+            if let Err(error) = self.ensure_deposit_fits(amount0, amount1) {
+                self.unlock();
+                return Err(error)
+            }
+            assert!(
+                self.token0
+                    .transfer_from(sender, self_account_id, amount0)
+                    .is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            assert!(
+                self.token1
+                    .transfer_from(sender, self_account_id, amount1)
+                    .is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            // Fee on transfer tokens deliver less than the amounts sent.
+            let amount0 = self.token0.balance_of(self_account_id) - self.reserve0;
+            let amount1 = self.token1.balance_of(self_account_id) - self.reserve1;
+            let liquidity = self.mint_deposit(sender, to, amount0, amount1);
+            assert!(liquidity.is_ok(), "Uniswap: RESERVE_OVERFLOW");
+            self.unlock();
+            liquidity
         }
 
-        /// Allows `spender` to withdraw from the caller's account multiple times, up to
-        /// the `value` amount.
+        /// Returns the deposit minted by `mint_position` with the id `id`.
+        #[ink(message)]
+        pub fn position_of(&self, id: u128) -> Option<LiquidityPosition> {
+            self.positions.get(&id).copied()
+        }
+
+        /// Returns the id the next position minted by `mint_position` gets.
+        #[ink(message)]
+        pub fn next_position_id(&self) -> u128 {
+            self.next_position_id
+        }
+
+        /// Burns all LP tokens of `to` and sends the tokens they are worth to
+        /// `to`, who has to be the caller.
         ///
-        /// If this function is called again it overwrites the current allowance with `value`.
+        /// On success a `Burn` event is emitted.
         ///
-        /// An `Approval` event is emitted.
+        /// # Errors
+        ///
+        /// Returns `ReserveOverflow` if a balance left would exceed
+        /// `math::RESERVE_MAX`. The LP tokens are not burned then.
+        ///
+        /// # Panics
+        ///
+        /// - If the caller is not `to`.
+        /// - If `to` is blacklisted.
+        /// - If the burn pays out nothing of a token.
+        /// - If a token transfer fails.
+        /// - If a balance left after the transfers exceeds `math::RESERVE_MAX`.
         #[ink(message)]
-        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
-            let owner = self.env().caller();
-            self.allowances.insert((owner, spender), value);
-            self.env().emit_event(Approval {
-                owner,
-                spender,
-                value,
+        pub fn burn(&mut self, to: AccountId) -> Result<()> {
+            self.lock();
+            assert!(self.env().caller() == to, "Uniswap: auth mismatch");
+            assert!(
+                self.ensure_not_blacklisted(to).is_ok(),
+                "Uniswap: BLACKLISTED"
+            );
+            let self_account_id = self.env().account_id();
+            let balance0 = self.token0.balance_of(self_account_id);
+            let balance1 = self.token1.balance_of(self_account_id);
+            let liquidity = self.balance_of(to);
+            let (amount0, amount1) =
+                math::burn_amounts(liquidity, balance0, balance1, *self.total_supply);
+            assert!(
+                amount0 > 0 && amount1 > 0,
+                "Uniswap: INSUFFICIENT_LIQUIDITY_BURNED"
+            );
+            // An error after the transfers would not revert them.
+            if let Err(error) =
+                self.ensure_reserves_fit(balance0 - amount0, balance1 - amount1)
+            {
+                self.unlock();
+                return Err(error)
+            }
+            self._burn(to, liquidity);
+            assert!(
+                self.token0.transfer(to, amount0).is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            assert!(
+                self.token1.transfer(to, amount1).is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            let balance0 = self.token0.balance_of(self_account_id);
+            let balance1 = self.token1.balance_of(self_account_id);
+            assert!(
+                self.update(balance0, balance1).is_ok(),
+                "Uniswap: RESERVE_OVERFLOW"
+            );
+            self.env().emit_event(Burn {
+                sender: to,
+                amount0,
+                amount1,
+                to,
             });
+            self.unlock();
             Ok(())
         }
 
-        /// Transfers `value` tokens on the behalf of `from` to the account `to`.
+        /// Burns the fewest LP tokens of the caller that pay out at least
+        /// `amount0_out` of token0 and `amount1_out` of token1, and sends the
+        /// payout to `to`.
         ///
-        /// This can be used to allow a contract to transfer tokens on ones behalf and/or
-        /// to charge fees in sub-currencies, for example.
+        /// The burn is proportional, so one of the tokens usually pays out more
+        /// than requested. The excess is sent to `to` as well.
         ///
-        /// On success a `Transfer` event is emitted.
+        /// Returns the LP tokens burned. On success a `Burn` event is emitted.
         ///
         /// # Errors
         ///
-        /// Returns `InsufficientAllowance` error if there are not enough tokens allowed
-        /// for the caller to withdraw from `from`.
+        /// - Returns `Expired` if the block timestamp is past `deadline`.
+        /// - Returns `EmptyPool` if the pair has no liquidity.
+        /// - Returns `InsufficientBalance` if the caller holds fewer LP tokens
+        ///   than required.
         ///
-        /// Returns `InsufficientBalance` error if there are not enough tokens on
-        /// the the account balance of `from`.
+        /// # Panics
+        ///
+        /// If a token transfer fails.
         #[ink(message)]
-        pub fn transfer_from(
+        pub fn burn_exact_output(
             &mut self,
-            from: AccountId,
+            amount0_out: Balance,
+            amount1_out: Balance,
+            to: AccountId,
+            deadline: Timestamp,
+        ) -> Result<Balance> {
+            let owner = self.env().caller();
+            self.ensure_not_blacklisted(owner)?;
+            self.lock();
+            let burned =
+                self.burn_for_amounts(owner, amount0_out, amount1_out, to, deadline);
+            if let Ok((_, amount0, amount1)) = burned {
+                assert!(
+                    self.token0.transfer(to, amount0).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+                assert!(
+                    self.token1.transfer(to, amount1).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+            }
+            self.unlock();
+            burned.map(|(liquidity, _, _)| liquidity)
+        }
+
+        /// Sends `amount0_out` of token0 and `amount1_out` of token1 to `to`,
+        /// in exchange for the token balances of the pair above its reserves,
+        /// which the caller transferred to the pair beforehand.
+        ///
+        /// If the trade moves the reserves closer to 1:1, the caller is refunded
+        /// `imbalance_refund_bps` percent of the fee on its input, like in
+        /// `swap_exact_in`. A caller holding governance tokens gets the holder
+        /// rebate added to the output sent to `to`.
+        ///
+        /// On success a `Swap` event is emitted, preceded by an `ImbalanceRefund`
+        /// or `HolderRebate` event for each refunded or rebated token.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `TradingNotStarted` if swaps are not allowed yet.
+        /// - Returns `ReserveOverflow` if a balance left after the output would
+        ///   exceed `math::RESERVE_MAX`.
+        /// - Returns `PriceImpactTooHigh` if the trade would move the price by
+        ///   more than `max_price_impact_bps`.
+        ///
+        /// # Panics
+        ///
+        /// - If the pair is paused.
+        /// - If both amounts are zero or exceed the reserves.
+        /// - If `to` is a token of the pair.
+        /// - If the input does not keep the constant product after the fee.
+        /// - If a token transfer fails.
+        /// - If a balance after the transfers exceeds `math::RESERVE_MAX`.
+        #[ink(message)]
+        pub fn swap(
+            &mut self,
+            amount0_out: Balance,
+            amount1_out: Balance,
             to: AccountId,
-            value: Balance,
         ) -> Result<()> {
-            let caller = self.env().caller();
-            let allowance = self.allowance(from, caller);
-            if allowance < value {
-                return Err(Error::InsufficientAllowance)
+            self.ensure_not_paused();
+            self.ensure_trading_started()?;
+            self.lock();
+            let mut batch = EventBatch::default();
+            if let Err(error) = self.ensure_no_rebase(&mut batch) {
+                self.unlock();
+                return Err(error)
             }
-            self.transfer_from_to(from, to, value)?;
-            self.allowances.insert((from, caller), allowance - value);
+            assert!(
+                amount0_out > 0 || amount1_out > 0,
+                "Uniswap: INSUFFICIENT_OUTPUT_AMOUNT"
+            );
+            assert!(
+                amount0_out < self.reserve0 && amount1_out < self.reserve1,
+                "Uniswap: INSUFFICIENT_LIQUIDITY"
+            );
+            assert!(
+                to != self.token0.to_account_id() && to != self.token1.to_account_id(),
+                "Uniswap: INVALID_TO"
+            );
+            let self_account_id = self.env().account_id();
+            // The input was transferred beforehand, so the balances left after
+            // the output are known before it is sent.
+            let balance0 = self.token0.balance_of(self_account_id) - amount0_out;
+            let balance1 = self.token1.balance_of(self_account_id) - amount1_out;
+            // A rebase synced into `batch` stays, so it is emitted on errors too.
+            let checked = self
+                .ensure_reserves_fit(balance0, balance1)
+                .and_then(|()| self.ensure_reserves_impact(balance0, balance1));
+            if let Err(error) = checked {
+                self.emit_batch_and_notify(batch);
+                self.unlock();
+                return Err(error)
+            }
+            if amount0_out > 0 {
+                assert!(
+                    self.token0.transfer(to, amount0_out).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+            }
+            if amount1_out > 0 {
+                assert!(
+                    self.token1.transfer(to, amount1_out).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+            }
+            let balance0 = self.token0.balance_of(self_account_id);
+            let balance1 = self.token1.balance_of(self_account_id);
+            let amount0_in = balance0.saturating_sub(self.reserve0 - amount0_out);
+            let amount1_in = balance1.saturating_sub(self.reserve1 - amount1_out);
+            assert!(
+                amount0_in > 0 || amount1_in > 0,
+                "Uniswap: INSUFFICIENT_INPUT_AMOUNT"
+            );
+            self.ensure_not_dust(amount0_in + amount1_in);
+            {
+                let fee_bps = self.swap_fee_bps();
+                let balance0_adjusted = balance0 * 10_000 - amount0_in * fee_bps;
+                let balance1_adjusted = balance1 * 10_000 - amount1_in * fee_bps;
+                // Both products are compared with all 256 bits.
+                assert!(
+                    math::full_mul(balance0_adjusted, balance1_adjusted)
+                        >= math::full_mul(self.reserve0 * 10_000, self.reserve1 * 10_000),
+                    "Uniswap: K"
+                );
+            }
+            let sender = self.env().caller();
+            let (fee0, fee1) = (self.fee_of(amount0_in), self.fee_of(amount1_in));
+            let refund0 = self.imbalance_refund(amount0_in, balance0, balance1);
+            let refund1 = self.imbalance_refund(amount1_in, balance0, balance1);
+            let (balance0, balance1) = (balance0 - refund0, balance1 - refund1);
+            // The rebate on the fee of one token is paid in the other.
+            let (rebate0, rebate0_out) =
+                self.holder_rebate(sender, fee0, refund0, balance0, balance1);
+            let (rebate1, rebate1_out) =
+                self.holder_rebate(sender, fee1, refund1, balance1, balance0);
+            let amount0_out = amount0_out + rebate1_out;
+            let amount1_out = amount1_out + rebate0_out;
+            if refund0 > 0 {
+                assert!(
+                    self.token0.transfer(sender, refund0).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+            }
+            if rebate1_out > 0 {
+                assert!(
+                    self.token0.transfer(to, rebate1_out).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+            }
+            if refund1 > 0 {
+                assert!(
+                    self.token1.transfer(sender, refund1).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+            }
+            if rebate0_out > 0 {
+                assert!(
+                    self.token1.transfer(to, rebate0_out).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+            }
+            let (reserve0, reserve1) = (balance0 - rebate1_out, balance1 - rebate0_out);
+            assert!(
+                self.push_update(&mut batch, reserve0, reserve1).is_ok(),
+                "Uniswap: RESERVE_OVERFLOW"
+            );
+            // The refunds and the rebates are paid out of the fee.
+            self.record_swap(
+                amount0_in,
+                amount1_in,
+                fee0 - refund0 - rebate0,
+                fee1 - refund1 - rebate1,
+            );
+            if refund0 > 0 {
+                batch.emit_event(ImbalanceRefund {
+                    to: sender,
+                    amount: refund0,
+                });
+            }
+            if refund1 > 0 {
+                batch.emit_event(ImbalanceRefund {
+                    to: sender,
+                    amount: refund1,
+                });
+            }
+            if rebate0_out > 0 {
+                batch.emit_event(HolderRebate {
+                    to,
+                    amount: rebate0_out,
+                });
+            }
+            if rebate1_out > 0 {
+                batch.emit_event(HolderRebate {
+                    to,
+                    amount: rebate1_out,
+                });
+            }
+            self.push_swap(
+                &mut batch,
+                Swap {
+                    sender,
+                    amount0In: amount0_in,
+                    amount1In: amount1_in,
+                    amount0Out: amount0_out,
+                    amount1Out: amount1_out,
+                    to,
+                },
+            );
+            self.emit_batch_and_notify(batch);
+            self.unlock();
             Ok(())
         }
 
-        /// Transfers `value` amount of tokens from the caller's account to account `to`.
+        /// Swaps exactly `amount_in` of token0 (`zero_for_one`) or token1 for the
+        /// other token and sends the output to `to`.
         ///
-        /// On success a `Transfer` event is emitted.
+        /// The caller must have approved the pair to spend `amount_in`. Unlike
+        /// `swap` the output amount is computed by the pair from the amount it
+        /// actually received, which is less than `amount_in` for tokens that take
+        /// a fee on transfer.
+        ///
+        /// If the trade moves the reserves closer to 1:1, the caller is refunded
+        /// `imbalance_refund_bps` percent of the fee on its input. A caller
+        /// holding at least `rebate_threshold` governance tokens gets
+        /// `HOLDER_REBATE_PERCENT` of the fee added to the output, converted at
+        /// the price after the swap.
+        ///
+        /// Returns the amount of the other token sent to `to`.
         ///
         /// # Errors
         ///
-        /// Returns `InsufficientBalance` error if there are not enough tokens on
-        /// the caller's account balance.
-        fn transfer_from_to(
+        /// - Returns `PriceImpactTooHigh` if swapping `amount_in` would move the
+        ///   price by more than `max_price_impact_bps`.
+        /// - Returns `ReserveOverflow` if the input reserve would exceed
+        ///   `math::RESERVE_MAX`.
+        ///
+        /// # Panics
+        ///
+        /// If the output is less than `amount_out_min`.
+        #[ink(message)]
+        pub fn swap_exact_in(
             &mut self,
-            from: AccountId,
+            amount_in: Balance,
+            zero_for_one: bool,
+            amount_out_min: Balance,
             to: AccountId,
-            value: Balance,
+        ) -> Result<Balance> {
+            self.swap_exact_in_to(amount_in, zero_for_one, amount_out_min, to, None)
+        }
+
+        /// Swaps like `swap_exact_in` and pays `referral_bps` of the output to
+        /// `referrer` instead of `to`.
+        ///
+        /// Returns the amount of the other token sent to `to`. On success a
+        /// `Referral` event is emitted if the referral is not zero.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `ReferralBpsTooHigh` if `referral_bps` exceeds
+        ///   `max_referral_bps`.
+        /// - Returns `PriceImpactTooHigh` if swapping `amount_in` would move the
+        ///   price by more than `max_price_impact_bps`.
+        /// - Returns `ReserveOverflow` if the input reserve would exceed
+        ///   `math::RESERVE_MAX`.
+        ///
+        /// # Panics
+        ///
+        /// If the output sent to `to` is less than `amount_out_min`.
+        #[ink(message)]
+        pub fn swap_with_referral(
+            &mut self,
+            amount_in: Balance,
+            zero_for_one: bool,
+            amount_out_min: Balance,
+            to: AccountId,
+            referrer: AccountId,
+            referral_bps: u16,
+        ) -> Result<Balance> {
+            let max_bps = *self.max_referral_bps;
+            if referral_bps > max_bps {
+                return Err(Error::ReferralBpsTooHigh {
+                    bps: referral_bps,
+                    max_bps,
+                })
+            }
+            self.swap_exact_in_to(
+                amount_in,
+                zero_for_one,
+                amount_out_min,
+                to,
+                Some((referrer, referral_bps)),
+            )
+        }
+
+        /// Returns the largest share of the output in basis points a swap may
+        /// pay to a referrer.
+        #[ink(message)]
+        pub fn max_referral_bps(&self) -> u16 {
+            *self.max_referral_bps
+        }
+
+        /// Sets the largest share of the output in basis points a swap may pay
+        /// to a referrer. Zero disables referrals.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `ReferralBpsTooHigh` if `bps` exceeds 10 000.
+        #[ink(message)]
+        pub fn set_max_referral_bps(&mut self, bps: u16) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            if bps > 10_000 {
+                return Err(Error::ReferralBpsTooHigh {
+                    bps,
+                    max_bps: 10_000,
+                })
+            }
+            *self.max_referral_bps = bps;
+            Ok(())
+        }
+
+        /// Swaps exactly `amount_in` of token0 (`zero_for_one`) or token1 for the
+        /// other token, pays the `referral` share of the output in basis points
+        /// to its referrer and sends the rest to `to`.
+        ///
+        /// Returns the amount sent to `to`.
+        fn swap_exact_in_to(
+            &mut self,
+            amount_in: Balance,
+            zero_for_one: bool,
+            amount_out_min: Balance,
+            to: AccountId,
+            referral: Option<(AccountId, u16)>,
+        ) -> Result<Balance> {
+            self.ensure_not_paused();
+            self.ensure_trading_started()?;
+            // Fee on transfer tokens deliver less than `amount_in`, so this
+            // bound is conservative and can be checked before the transfer.
+            self.ensure_price_impact(amount_in, zero_for_one)?;
+            self.lock();
+            // Under the lock, so a token calling back into the pair cannot sync
+            // the reserves in between.
+            let mut batch = EventBatch::default();
+            if let Err(error) = self.ensure_no_rebase(&mut batch) {
+                self.unlock();
+                return Err(error)
+            }
+            // The pair receives at most `amount_in`, so the input reserve fits
+            // if this does.
+            let (reserve0, reserve1) = if zero_for_one {
+                (self.reserve0.saturating_add(amount_in), self.reserve1)
+            } else {
+                (self.reserve0, self.reserve1.saturating_add(amount_in))
+            };
+            if let Err(error) = self.ensure_reserves_fit(reserve0, reserve1) {
+                self.emit_batch_and_notify(batch);
+                self.unlock();
+                return Err(error)
+            }
+            let sender = self.env().caller();
+            let self_account_id = self.env().account_id();
+            let token_in = if zero_for_one {
+                &mut *self.token0
+            } else {
+                &mut *self.token1
+            };
+            let balance_before = token_in.balance_of(self_account_id);
+            assert!(
+                token_in.transfer_from(sender, self_account_id, amount_in).is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            let amount_in = token_in.balance_of(self_account_id) - balance_before;
+            self.ensure_not_dust(amount_in);
+            let (amount_out, mut reserve0, mut reserve1) =
+                self.quote_exact_in(amount_in, zero_for_one, amount_out_min);
+            #[cfg(feature = "debug")]
+            ink_env::debug_println(&format!(
+                "swap: amount_in={}, zero_for_one={}, amount_out={}, reserves=({}, {})",
+                amount_in, zero_for_one, amount_out, reserve0, reserve1
+            ));
+            let fee_taken = self.fee_of(amount_in);
+            let refund = self.imbalance_refund(amount_in, reserve0, reserve1);
+            let (reserve_in, reserve_out) = if zero_for_one {
+                (&mut reserve0, &mut reserve1)
+            } else {
+                (&mut reserve1, &mut reserve0)
+            };
+            *reserve_in -= refund;
+            let (rebate, rebate_out) =
+                self.holder_rebate(sender, fee_taken, refund, *reserve_in, *reserve_out);
+            *reserve_out -= rebate_out;
+            assert!(
+                self.push_sync(&mut batch, reserve0, reserve1).is_ok(),
+                "Uniswap: RESERVE_OVERFLOW"
+            );
+            let (token_in, token_out) = if zero_for_one {
+                (&mut *self.token0, &mut *self.token1)
+            } else {
+                (&mut *self.token1, &mut *self.token0)
+            };
+            let (amount_to, referral_amount) = match referral {
+                Some((_, referral_bps)) => math::referral_split(amount_out, referral_bps),
+                None => (amount_out, 0),
+            };
+            // The rebate is added to the output sent to `to`, the referrer does
+            // not share in it.
+            let amount_to = amount_to + rebate_out;
+            assert!(
+                amount_to >= amount_out_min,
+                "Uniswap: INSUFFICIENT_OUTPUT_AMOUNT"
+            );
+            assert!(
+                token_out.transfer(to, amount_to).is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            let referrer = referral
+                .filter(|_| referral_amount > 0)
+                .map(|(referrer, _)| referrer);
+            if let Some(referrer) = referrer {
+                assert!(
+                    token_out.transfer(referrer, referral_amount).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+            }
+            if refund > 0 {
+                assert!(
+                    token_in.transfer(sender, refund).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+                batch.emit_event(ImbalanceRefund {
+                    to: sender,
+                    amount: refund,
+                });
+            }
+            if rebate_out > 0 {
+                batch.emit_event(HolderRebate {
+                    to,
+                    amount: rebate_out,
+                });
+            }
+            if let Some(referrer) = referrer {
+                batch.emit_event(Referral {
+                    referrer,
+                    to,
+                    amount: referral_amount,
+                });
+            }
+            let amount_out = amount_out + rebate_out;
+            let (amount0_in, amount1_in, amount0_out, amount1_out) = if zero_for_one {
+                (amount_in, 0, 0, amount_out)
+            } else {
+                (0, amount_in, amount_out, 0)
+            };
+            // The refund and the rebate are paid out of the fee.
+            let fee = fee_taken - refund - rebate;
+            let (fee0, fee1) = if zero_for_one { (fee, 0) } else { (0, fee) };
+            self.record_swap(amount0_in, amount1_in, fee0, fee1);
+            self.push_swap(
+                &mut batch,
+                Swap {
+                    sender,
+                    amount0In: amount0_in,
+                    amount1In: amount1_in,
+                    amount0Out: amount0_out,
+                    amount1Out: amount1_out,
+                    to,
+                },
+            );
+            self.emit_batch_and_notify(batch);
+            self.unlock();
+            Ok(amount_to)
+        }
+
+        /// Returns the largest amount of `token` that can be flash loaned, zero
+        /// for tokens other than the two tokens of the pair.
+        #[ink(message)]
+        pub fn max_flash_loan(&self, token: AccountId) -> Balance {
+            match self.is_token0(token) {
+                Ok(true) => self.reserve0,
+                Ok(false) => self.reserve1,
+                Err(_) => 0,
+            }
+        }
+
+        /// Returns the fee charged for a flash loan of `amount` of `token`.
+        ///
+        /// The fee is the swap fee and accrues to the LPs.
+        ///
+        /// # Errors
+        ///
+        /// Returns `UnsupportedToken` if `token` is not a token of the pair.
+        #[ink(message)]
+        pub fn flash_fee(&self, token: AccountId, amount: Balance) -> Result<Balance> {
+            self.is_token0(token)?;
+            Ok(self.fee_of(amount))
+        }
+
+        /// Lends `amount` of `token` to `receiver` for the duration of a call to
+        /// its `FlashBorrower::on_flash_loan` message, passing `data` along.
+        ///
+        /// The receiver has to send the loan plus `flash_fee` back to the pair
+        /// before returning. The pair is locked during the call.
+        ///
+        /// On success a `FlashLoan` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `UnsupportedToken` if `token` is not a token of the pair.
+        /// - Returns `InsufficientBalance` if `amount` exceeds the reserve.
+        /// - Returns `ReserveOverflow` if the reserve plus the fee would exceed
+        ///   `math::RESERVE_MAX`.
+        ///
+        /// # Panics
+        ///
+        /// - If the receiver rejects the loan or does not repay it with the fee.
+        /// - If the repaid reserve exceeds `math::RESERVE_MAX`.
+        #[ink(message)]
+        pub fn flash_loan(
+            &mut self,
+            receiver: AccountId,
+            token: AccountId,
+            amount: Balance,
+            data: Vec<u8>,
         ) -> Result<()> {
-            let from_balance = self.balance_of(from);
-            if from_balance < value {
-                return Err(Error::InsufficientBalance)
+            self.ensure_not_paused();
+            let zero = self.is_token0(token)?;
+            let available = self.max_flash_loan(token);
+            if amount > available {
+                return Err(Error::InsufficientBalance {
+                    required: amount,
+                    available,
+                })
             }
-            self.balances.insert(from, from_balance - value);
-            let to_balance = self.balance_of(to);
-            self.balances.insert(to, to_balance + value);
-            self.env().emit_event(Transfer {
-                from: Some(from),
-                to: Some(to),
-                value,
+            let fee = self.fee_of(amount);
+            if zero {
+                self.ensure_reserves_fit(self.reserve0 + fee, self.reserve1)?;
+            } else {
+                self.ensure_reserves_fit(self.reserve0, self.reserve1 + fee)?;
+            }
+            self.lock();
+            let initiator = self.env().caller();
+            let self_account_id = self.env().account_id();
+            let lent = if zero {
+                &mut *self.token0
+            } else {
+                &mut *self.token1
+            };
+            let balance_before = lent.balance_of(self_account_id);
+            assert!(
+                lent.transfer(receiver, amount).is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            let accepted = build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(receiver)
+                .gas_limit(*self.callback_gas_limit)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ON_FLASH_LOAN_SELECTOR))
+                        .push_arg(&initiator)
+                        .push_arg(&token)
+                        .push_arg(&amount)
+                        .push_arg(&fee)
+                        .push_arg(&data),
+                )
+                .returns::<ReturnType<bool>>()
+                .fire();
+            assert!(accepted == Ok(true), "Uniswap: FLASH_LOAN_REJECTED");
+            let lent = if zero {
+                &mut *self.token0
+            } else {
+                &mut *self.token1
+            };
+            let balance_after = lent.balance_of(self_account_id);
+            assert!(
+                self.settle_flash_loan(zero, balance_before, balance_after, fee)
+                    .is_ok(),
+                "Uniswap: RESERVE_OVERFLOW"
+            );
+            self.env().emit_event(FlashLoan {
+                receiver,
+                token,
+                amount,
+                fee,
             });
+            self.unlock();
             Ok(())
         }
 
-        fn _burn(&mut self, to: AddressId, value: Balance) {
-            let to_balance = self.balance_of(to);
-            self.balances.insert(to, to_balance - value);
+        /// Returns the fee of flash mints in basis points.
+        #[ink(message)]
+        pub fn flash_mint_fee_bps(&self) -> u16 {
+            *self.flash_mint_fee_bps
+        }
 
-            self.total_supply -= value;
+        /// Sets the fee of flash mints in basis points.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `InvalidFeeBps` if `bps` exceeds `math::MAX_FEE_BPS`.
+        #[ink(message)]
+        pub fn set_flash_mint_fee_bps(&mut self, bps: u16) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            Self::ensure_valid_fee_bps(bps)?;
+            *self.flash_mint_fee_bps = bps;
+            Ok(())
         }
 
-        fn _mint(&mut self, to: AddressId, value: Balance) {
-            let to_balance = self.balance_of(to);
-            self.balances.insert(to, to_balance + value);
+        /// Returns the fee charged for a flash mint of `amount` LP tokens.
+        #[ink(message)]
+        pub fn flash_mint_fee(&self, amount: Balance) -> Balance {
+            math::fee_of(amount, Balance::from(*self.flash_mint_fee_bps))
+        }
 
-            self.total_supply += value;
+        /// Mints `amount` LP tokens to `receiver` for the duration of a call to
+        /// its `on_flash_mint(amount, fee, data)` message.
+        ///
+        /// Afterwards `amount` plus `flash_mint_fee` LP tokens are burned from
+        /// the receiver, so the total supply ends up lower by the fee and the
+        /// fee accrues to all LPs. The pair is locked during the call.
+        ///
+        /// On success a `FlashMint` event is emitted.
+        ///
+        /// # Panics
+        ///
+        /// - If the receiver is blacklisted.
+        /// - If the receiver rejects the flash mint or does not hold the LP
+        ///   tokens to repay it with the fee.
+        #[ink(message)]
+        pub fn flash_mint(
+            &mut self,
+            amount: Balance,
+            receiver: AccountId,
+            data: Vec<u8>,
+        ) {
+            self.ensure_not_paused();
+            assert!(
+                self.ensure_not_blacklisted(receiver).is_ok(),
+                "Uniswap: BLACKLISTED"
+            );
+            let fee = self.flash_mint_fee(amount);
+            self.lock();
+            self.begin_flash_mint(receiver, amount);
+            let accepted = build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(receiver)
+                .gas_limit(*self.callback_gas_limit)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ON_FLASH_MINT_SELECTOR))
+                        .push_arg(&amount)
+                        .push_arg(&fee)
+                        .push_arg(&data),
+                )
+                .returns::<ReturnType<bool>>()
+                .fire();
+            assert!(accepted == Ok(true), "Uniswap: FLASH_MINT_REJECTED");
+            self.settle_flash_mint(receiver, amount, fee);
+            self.env().emit_event(FlashMint {
+                receiver,
+                amount,
+                fee,
+            });
+            self.unlock();
         }
 
-        fn update(&mut self, balance0:Balance, balance1:Balance, reserve0:Balance, reserve1:`Balance){
-            self.reserve0 = balance0;
-            self.reserve1 = balance1;
+        /// Returns the spot price of token0 in token1, `reserve1 / reserve0`,
+        /// with `precision` decimals, rounded down.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `InvalidPrecision` if `precision` exceeds
+        ///   `MAX_PRICE_PRECISION`.
+        /// - Returns `EmptyPool` if `reserve0` is zero.
+        /// - Returns `PriceOverflow` if the scaled price does not fit.
+        #[ink(message)]
+        pub fn spot_price0_fixed(&self, precision: u32) -> Result<Balance> {
+            if precision > MAX_PRICE_PRECISION {
+                return Err(Error::InvalidPrecision { precision })
+            }
+            if self.reserve0 == 0 {
+                return Err(Error::EmptyPool)
+            }
+            math::fixed_price(self.reserve0, self.reserve1, precision)
+                .ok_or(Error::PriceOverflow)
+        }
 
-            self.env().emit_event(Sync(self.reserve0, self.reserve1));
+        /// Returns the spot price of token0 in token1 like Chainlink's
+        /// `latestRoundData`, as `(round_id, answer, started_at, updated_at,
+        /// answered_in_round)`.
+        ///
+        /// `answer` has `ORACLE_DECIMALS` decimals. Every update of the reserves
+        /// starts a new round that is answered at once, so `started_at` equals
+        /// `updated_at` and `answered_in_round` equals `round_id`.
+        ///
+        /// The spot price can be moved within a single block, consumers should
+        /// not rely on it alone to value collateral.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `EmptyPool` if `reserve0` is zero.
+        /// - Returns `PriceOverflow` if the scaled price does not fit.
+        #[ink(message)]
+        pub fn latest_round_data(
+            &self,
+        ) -> Result<(u128, i128, Timestamp, Timestamp, u128)> {
+            let price = self.spot_price0_fixed(ORACLE_DECIMALS)?;
+            if price > i128::MAX as Balance {
+                return Err(Error::PriceOverflow)
+            }
+            let round_id = self.price_round;
+            let updated_at = self.reserves_updated_at;
+            Ok((round_id, price as i128, updated_at, updated_at, round_id))
         }
 
+        /// Returns `(amount_out, amount_in)` for swapping `amount_in` of token0
+        /// (`zero_for_one`) or token1 against the current reserves.
+        ///
+        /// The effective price `amount_out / amount_in` includes the fee and the
+        /// slippage of a trade of this size and is left to the caller to compute
+        /// at the precision it needs.
+        #[ink(message)]
+        pub fn execution_price(&self, amount_in: Balance, zero_for_one: bool) -> (Balance, Balance) {
+            let (reserve_in, reserve_out) = if zero_for_one {
+                (self.reserve0, self.reserve1)
+            } else {
+                (self.reserve1, self.reserve0)
+            };
+            let fee_bps = self.swap_fee_bps();
+            let amount_out =
+                math::get_amount_out(amount_in, reserve_in, reserve_out, fee_bps);
+            (amount_out, amount_in)
+        }
 
-    }
+        /// Returns the intermediate values of quoting a swap of `amount_in` of
+        /// token0 (`zero_for_one`) or token1 against the current reserves.
+        ///
+        /// Only built with the `debug` feature. This is not a message, since the
+        /// dispatch of this ink! version cannot leave out a message by feature.
+        ///
+        /// # Panics
+        ///
+        /// If the pair has no liquidity.
+        #[cfg(feature = "debug")]
+        pub fn debug_swap_state(
+            &self,
+            amount_in: Balance,
+            zero_for_one: bool,
+        ) -> SwapDebugState {
+            let (reserve_in, reserve_out) = if zero_for_one {
+                (self.reserve0, self.reserve1)
+            } else {
+                (self.reserve1, self.reserve0)
+            };
+            let (amount_out, reserve0, reserve1) =
+                self.quote_exact_in(amount_in, zero_for_one, 0);
+            let (new_reserve_in, new_reserve_out) = if zero_for_one {
+                (reserve0, reserve1)
+            } else {
+                (reserve1, reserve0)
+            };
+            SwapDebugState {
+                reserve_in,
+                reserve_out,
+                fee_bps: self.swap_fee_bps(),
+                fee: self.fee_of(amount_in),
+                amount_out,
+                reserve0,
+                reserve1,
+                price_impact_bps: math::price_impact_bps(
+                    reserve_in,
+                    reserve_out,
+                    new_reserve_in,
+                    new_reserve_out,
+                ),
+            }
+        }
 
-    // #[cfg(test)]
-    // mod tests {
-    //     use super::*;
+        /// Returns the least output of swapping `amount_in` of token0
+        /// (`zero_for_one`) or token1 if the price moves against the swap by up
+        /// to `slippage_bps` basis points before it executes.
+        ///
+        /// This is the output after the fee reduced by the slippage and rounded
+        /// down, ready to be passed as `amount_out_min`.
+        ///
+        /// # Panics
+        ///
+        /// If `slippage_bps` exceeds 10_000 or the pair has no liquidity.
+        #[ink(message)]
+        pub fn min_received(
+            &self,
+            amount_in: Balance,
+            zero_for_one: bool,
+            slippage_bps: u16,
+        ) -> Balance {
+            assert!(slippage_bps <= 10_000, "Uniswap: INVALID_SLIPPAGE");
+            let (amount_out, _) = self.execution_price(amount_in, zero_for_one);
+            amount_out * (10_000 - slippage_bps as Balance) / 10_000
+        }
 
-    //     #[test]
-    //     fn default_works() {
-    //         let flipper = Flipper::default();
-    //         assert_eq!(flipper.get(), false);
-    //     }
+        /// Returns the largest amount of token0 (`zero_for_one`) or token1 whose
+        /// swap keeps the marginal price of that token, in units of the other
+        /// token, at or above `target_price_num / target_price_den`.
+        ///
+        /// Returns zero if the price is already at or below the target.
+        ///
+        /// # Panics
+        ///
+        /// If `target_price_num` or `target_price_den` is zero.
+        #[ink(message)]
+        pub fn max_input_to_price(
+            &self,
+            target_price_num: Balance,
+            target_price_den: Balance,
+            zero_for_one: bool,
+        ) -> Balance {
+            let (reserve_in, reserve_out) = if zero_for_one {
+                (self.reserve0, self.reserve1)
+            } else {
+                (self.reserve1, self.reserve0)
+            };
+            math::max_input_to_price(
+                reserve_in,
+                reserve_out,
+                target_price_num,
+                target_price_den,
+                self.swap_fee_bps(),
+            )
+        }
 
-    //     #[test]
-    //     fn it_works() {
-    //         let mut flipper = Flipper::new(false);
-    //         assert_eq!(flipper.get(), false);
-    //         flipper.flip();
-    //         assert_eq!(flipper.get(), true);
-    //     }
-    // }
+        /// Sends the token balances of the pair above its reserves to `to`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `RateLimited` if the last skim was less than
+        /// `skim_interval_blocks` blocks ago.
+        ///
+        /// # Panics
+        ///
+        /// - If the caller is not an owner.
+        /// - If a token transfer fails.
+        #[ink(message)]
+        pub fn skim(&mut self, to: AccountId) -> Result<()> {
+            assert!(
+                self.roles.has_role(OWNER_ROLE, self.env().caller()),
+                "Uniswap: auth mismatch"
+            );
+            self.ensure_skim_allowed()?;
+            let (excess0, excess1) = self.skimmable();
+            assert!(
+                self.token0.transfer(to, excess0).is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            assert!(
+                self.token1.transfer(to, excess1).is_ok(),
+                "Uniswap: TRANSFER_FAILED"
+            );
+            Ok(())
+        }
+
+        /// Returns the token balances of the pair above its reserves, that is the
+        /// amounts of token0 and token1 `skim` would send.
+        ///
+        /// Lets keepers check whether a skim is worth its fees.
+        #[ink(message)]
+        pub fn skimmable(&self) -> (Balance, Balance) {
+            let self_account_id = self.env().account_id();
+            self.skimmable_with(
+                self.token0.balance_of(self_account_id),
+                self.token1.balance_of(self_account_id),
+            )
+        }
+
+        /// Returns the number of blocks between two skims, zero if skims are not
+        /// limited.
+        #[ink(message)]
+        pub fn skim_interval_blocks(&self) -> BlockNumber {
+            *self.skim_interval_blocks
+        }
+
+        /// Sets the number of blocks between two skims. Zero disables the limit.
+        ///
+        /// This keeps bots from skimming fee on transfer tokens every block.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn set_skim_interval_blocks(&mut self, blocks: BlockNumber) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            *self.skim_interval_blocks = blocks;
+            Ok(())
+        }
+
+        /// Sets the reserves to the token balances of the pair.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ReserveOverflow` if a balance exceeds `math::RESERVE_MAX`.
+        ///
+        /// # Panics
+        ///
+        /// - If the caller is not an owner.
+        /// - If both balances are zero while LP tokens exist.
+        #[ink(message)]
+        pub fn sync(&mut self) -> Result<()> {
+            assert!(
+                self.roles.has_role(OWNER_ROLE, self.env().caller()),
+                "Uniswap: auth mismatch"
+            );
+            let self_account_id = self.env().account_id();
+            let balance0 = self.token0.balance_of(self_account_id);
+            let balance1 = self.token1.balance_of(self_account_id);
+            self.update(balance0, balance1)
+        }
+
+        /// Returns whether swaps sync the reserves down to the balances after a
+        /// token rebased down instead of reverting.
+        #[ink(message)]
+        pub fn auto_sync(&self) -> bool {
+            *self.auto_sync
+        }
+
+        /// Sets whether swaps sync the reserves down to the balances after a
+        /// token rebased down instead of reverting.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn set_auto_sync(&mut self, auto_sync: bool) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            *self.auto_sync = auto_sync;
+            Ok(())
+        }
+
+        /// Returns whether swaps also emit `SwapLegacy` events.
+        #[ink(message)]
+        pub fn legacy_events(&self) -> bool {
+            self.legacy_events
+        }
+
+        /// Returns the time swaps are allowed from, `None` if swaps are always
+        /// allowed.
+        #[ink(message)]
+        pub fn trading_start(&self) -> Option<Timestamp> {
+            *self.trading_start
+        }
+
+        /// Moves the time swaps are allowed from to `trading_start`.
+        ///
+        /// The start can only be moved earlier, so LPs who added liquidity in
+        /// advance can rely on it. A time in the past allows swaps right away.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `TradingStartPostponed` if `trading_start` is later than the
+        ///   current start or swaps are already always allowed.
+        #[ink(message)]
+        pub fn set_trading_start(&mut self, trading_start: Timestamp) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            let current = self.trading_start.unwrap_or(0);
+            if trading_start > current {
+                return Err(Error::TradingStartPostponed {
+                    current,
+                    requested: trading_start,
+                })
+            }
+            *self.trading_start = Some(trading_start);
+            Ok(())
+        }
+
+        /// Returns the share of the swap fee in percent refunded to swaps that
+        /// move the reserves closer to equal.
+        #[ink(message)]
+        pub fn imbalance_refund_bps(&self) -> u8 {
+            *self.imbalance_refund_bps
+        }
+
+        /// Returns the token whose holders get part of the swap fee as a rebate.
+        #[ink(message)]
+        pub fn governance_token(&self) -> AccountId {
+            *self.governance_token
+        }
+
+        /// Returns the governance tokens a swapper has to hold for a rebate,
+        /// zero if rebates are disabled.
+        #[ink(message)]
+        pub fn rebate_threshold(&self) -> Balance {
+            *self.rebate_threshold
+        }
+
+        /// Sets the share of the swap fee in percent refunded to swaps that move
+        /// the reserves closer to equal.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `InvalidRefundBps` if `bps` exceeds 100.
+        #[ink(message)]
+        pub fn set_imbalance_refund_bps(&mut self, bps: u8) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            if bps > 100 {
+                return Err(Error::InvalidRefundBps { bps })
+            }
+            *self.imbalance_refund_bps = bps;
+            Ok(())
+        }
+
+        /// Returns the amount of each token the first mint has to deposit at least.
+        #[ink(message)]
+        pub fn initial_mint_minimum(&self) -> Balance {
+            *self.initial_mint_minimum
+        }
+
+        /// Returns the LP tokens locked in the pair by the first mint.
+        #[ink(message)]
+        pub fn minimum_liquidity(&self) -> Balance {
+            *self.minimum_liquidity
+        }
+
+        /// Sets the amount of each token the first mint has to deposit at least.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn set_initial_mint_minimum(&mut self, minimum: Balance) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            *self.initial_mint_minimum = minimum;
+            Ok(())
+        }
+
+        /// Returns the value of one LP token with 18 decimals, priced by the
+        /// Chainlink aggregators `price_feed0` and `price_feed1` of the tokens.
+        ///
+        /// `decimals0` and `decimals1` are the decimals of token0 and token1, the
+        /// reserves are normalized to 18 decimals before they are priced.
+        ///
+        /// Returns `None` if a price feed call fails or reports a negative
+        /// price, if there is no liquidity or on overflow.
+        #[ink(message)]
+        pub fn virtual_price(
+            &self,
+            price_feed0: AccountId,
+            price_feed1: AccountId,
+            decimals0: u8,
+            decimals1: u8,
+        ) -> Option<u128> {
+            let price0 = self.latest_price(price_feed0)?;
+            let price1 = self.latest_price(price_feed1)?;
+            self.lp_value(price0, price1, decimals0, decimals1)
+        }
+
+        /// Returns the name of the LP token, e.g. `Uniswap V2 DOT/USDT`.
+        ///
+        /// The name is composed of the token symbols on the first query and
+        /// cached afterwards.
+        #[ink(message)]
+        pub fn lp_name(&mut self) -> String {
+            self.lp_metadata().0
+        }
+
+        /// Returns the symbol of the LP token, e.g. `DOT-USDT-LP`.
+        ///
+        /// The symbol is composed of the token symbols on the first query and
+        /// cached afterwards.
+        #[ink(message)]
+        pub fn lp_symbol(&mut self) -> String {
+            self.lp_metadata().1
+        }
+
+        /// Returns the decimals of the LP token.
+        #[ink(message)]
+        pub fn lp_decimals(&self) -> u8 {
+            LP_DECIMALS
+        }
+
+        /// Returns the EIP-712 domain separator of the LP token.
+        #[ink(message)]
+        pub fn domain_separator(&self) -> [u8; 32] {
+            *self.domain_separator
+        }
+
+        /// Returns the chain the domain separator was computed for.
+        #[ink(message)]
+        pub fn chain_id(&self) -> u64 {
+            *self.chain_id
+        }
+
+        /// Recomputes the domain separator for `chain_id`.
+        ///
+        /// Signatures for the old domain become invalid, which protects
+        /// against replays on both sides of a chain fork.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn rebuild_domain_separator(&mut self, chain_id: u64) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            *self.chain_id = chain_id;
+            *self.domain_separator = domain_separator_of(
+                DOMAIN_NAME,
+                DOMAIN_VERSION,
+                chain_id,
+                self.env().account_id(),
+            );
+            Ok(())
+        }
+
+        /// Sends the native balance of the pair above the existential deposit
+        /// to `to` and returns the amount sent.
+        ///
+        /// The pair never holds native currency on purpose, so anything sent to
+        /// its account would be stranded otherwise.
+        ///
+        /// On success a `NativeSwept` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `NativeTransferFailed` if the transfer failed.
+        #[ink(message)]
+        pub fn sweep_native(&mut self, to: AccountId) -> Result<Balance> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            let amount = self
+                .env()
+                .balance()
+                .saturating_sub(self.env().minimum_balance());
+            if amount == 0 {
+                return Ok(0)
+            }
+            self.env()
+                .transfer(to, amount)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            self.env().emit_event(NativeSwept { to, amount });
+            Ok(amount)
+        }
+
+        /// Returns whether only whitelisted accounts may receive minted liquidity.
+        #[ink(message)]
+        pub fn mint_whitelist_enabled(&self) -> bool {
+            *self.mint_whitelist_enabled
+        }
+
+        /// Returns whether `account` is on the mint whitelist.
+        #[ink(message)]
+        pub fn is_whitelisted(&self, account: AccountId) -> bool {
+            self.mint_whitelist.get(&account).copied().unwrap_or(false)
+        }
+
+        /// Restricts minting to whitelisted recipients.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn enable_mint_whitelist(&mut self) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            *self.mint_whitelist_enabled = true;
+            Ok(())
+        }
+
+        /// Allows minting to any recipient again. The whitelist is kept.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn disable_mint_whitelist(&mut self) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            *self.mint_whitelist_enabled = false;
+            Ok(())
+        }
+
+        /// Adds `account` to the mint whitelist.
+        ///
+        /// On success a `WhitelistUpdated` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn add_to_whitelist(&mut self, account: AccountId) -> Result<()> {
+            self.update_whitelist(account, true)
+        }
+
+        /// Removes `account` from the mint whitelist.
+        ///
+        /// On success a `WhitelistUpdated` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn remove_from_whitelist(&mut self, account: AccountId) -> Result<()> {
+            self.update_whitelist(account, false)
+        }
+
+        /// Returns whether the LP tokens of `account` are frozen.
+        #[ink(message)]
+        pub fn is_blacklisted(&self, account: AccountId) -> bool {
+            self.blacklisted.get(&account).copied().unwrap_or(false)
+        }
+
+        /// Freezes the LP tokens of `account`, which can then neither send nor
+        /// receive LP tokens, nor mint or burn liquidity.
+        ///
+        /// On success a `Blacklisted` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn blacklist(&mut self, account: AccountId) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            self.blacklisted.insert(account, true);
+            self.env().emit_event(Blacklisted { account });
+            Ok(())
+        }
+
+        /// Unfreezes the LP tokens of `account`.
+        ///
+        /// On success an `Unblacklisted` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn unblacklist(&mut self, account: AccountId) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            self.blacklisted.take(&account);
+            self.env().emit_event(Unblacklisted { account });
+            Ok(())
+        }
+
+        /// Returns the number of blocks freshly minted LP tokens cannot be
+        /// transferred for.
+        #[ink(message)]
+        pub fn transfer_lock_blocks(&self) -> BlockNumber {
+            *self.transfer_lock_blocks
+        }
+
+        /// Sets the number of blocks after a mint during which the recipient
+        /// cannot transfer LP tokens. Zero disables the lock.
+        ///
+        /// This prevents adding and removing liquidity around a swap in the same
+        /// block.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn set_transfer_lock_blocks(&mut self, blocks: BlockNumber) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            *self.transfer_lock_blocks = blocks;
+            Ok(())
+        }
+
+        /// Returns the smallest input a swap may pay, or zero if any input is accepted.
+        #[ink(message)]
+        pub fn min_swap_amount(&self) -> Balance {
+            *self.min_swap_amount
+        }
+
+        /// Sets the smallest input a swap may pay. Zero accepts any input.
+        ///
+        /// Swaps this small round the fee down to zero, which allows to grind
+        /// the invariant with many tiny trades.
+        ///
+        /// On success a `MinSwapAmountChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn set_min_swap_amount(&mut self, min_swap_amount: Balance) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            *self.min_swap_amount = min_swap_amount;
+            self.env().emit_event(MinSwapAmountChanged { min_swap_amount });
+            Ok(())
+        }
+
+        /// Returns the swap fee in basis points.
+        #[ink(message)]
+        pub fn fee_bps(&self) -> u16 {
+            *self.fee_bps
+        }
+
+        /// Sets the swap fee in basis points.
+        ///
+        /// Without a fee swaps are exact up to integer rounding and the constant
+        /// product is checked on the raw balances.
+        ///
+        /// On success a `FeeBpsChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `InvalidFeeBps` if `bps` exceeds `math::MAX_FEE_BPS`.
+        #[ink(message)]
+        pub fn set_fee_bps(&mut self, bps: u16) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            Self::ensure_valid_fee_bps(bps)?;
+            self.apply_fee_bps(bps);
+            Ok(())
+        }
+
+        /// Returns the LP tokens `owner` held at the start of `block`.
+        #[ink(message)]
+        pub fn balance_of_at(&self, owner: AccountId, block: BlockNumber) -> Balance {
+            self.checkpoint_at(Some(owner), block)
+        }
+
+        /// Returns the total supply of LP tokens at the start of `block`.
+        #[ink(message)]
+        pub fn total_supply_at(&self, block: BlockNumber) -> Balance {
+            self.checkpoint_at(None, block)
+        }
+
+        /// Returns the fee proposal with the id `proposal_id`.
+        #[ink(message)]
+        pub fn fee_proposal(&self, proposal_id: u32) -> Option<FeeProposal> {
+            self.fee_proposals.get(&proposal_id).copied()
+        }
+
+        /// Proposes to change the swap fee to `fee_bps` and returns the id of the
+        /// proposal.
+        ///
+        /// LP holders vote with their balance at the start of the current block
+        /// for `VOTING_PERIOD_BLOCKS` blocks. Tokens received afterwards,
+        /// including in this block, do not count.
+        ///
+        /// On success a `FeeProposed` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `InvalidFeeBps` if `fee_bps` exceeds `math::MAX_FEE_BPS`.
+        /// - Returns `NoVotingPower` if the caller held no LP tokens at the start
+        ///   of the block.
+        #[ink(message)]
+        pub fn propose_fee(&mut self, fee_bps: u16) -> Result<u32> {
+            Self::ensure_valid_fee_bps(fee_bps)?;
+            let proposer = self.env().caller();
+            let snapshot_block = self.env().block_number();
+            if self.balance_of_at(proposer, snapshot_block) == 0 {
+                return Err(Error::NoVotingPower)
+            }
+            let proposal_id = self.fee_proposal_count;
+            self.fee_proposals.insert(
+                proposal_id,
+                FeeProposal {
+                    fee_bps,
+                    snapshot_block,
+                    votes_for: 0,
+                    votes_against: 0,
+                    executed: false,
+                },
+            );
+            self.fee_proposal_count += 1;
+            self.env().emit_event(FeeProposed {
+                proposal_id,
+                proposer,
+                fee_bps,
+            });
+            Ok(proposal_id)
+        }
+
+        /// Votes for the fee proposal `proposal_id` if `support` or against it,
+        /// weighted by the LP tokens of the caller at its snapshot.
+        ///
+        /// On success a `FeeVoted` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `ProposalNotFound` if there is no such proposal.
+        /// - Returns `VotingClosed` if the voting period has ended.
+        /// - Returns `AlreadyVoted` if the caller has already voted.
+        /// - Returns `NoVotingPower` if the caller held no LP tokens at the
+        ///   snapshot.
+        #[ink(message)]
+        pub fn vote(&mut self, proposal_id: u32, support: bool) -> Result<()> {
+            let voter = self.env().caller();
+            let mut proposal = self
+                .fee_proposal(proposal_id)
+                .ok_or(Error::ProposalNotFound)?;
+            let voting_end = proposal.snapshot_block + VOTING_PERIOD_BLOCKS;
+            if self.env().block_number() >= voting_end {
+                return Err(Error::VotingClosed)
+            }
+            if self.fee_votes.get(&(proposal_id, voter)).is_some() {
+                return Err(Error::AlreadyVoted)
+            }
+            let weight = self.balance_of_at(voter, proposal.snapshot_block);
+            if weight == 0 {
+                return Err(Error::NoVotingPower)
+            }
+            if support {
+                proposal.votes_for += weight;
+            } else {
+                proposal.votes_against += weight;
+            }
+            self.fee_proposals.insert(proposal_id, proposal);
+            self.fee_votes.insert((proposal_id, voter), support);
+            self.env().emit_event(FeeVoted {
+                proposal_id,
+                voter,
+                support,
+                weight,
+            });
+            Ok(())
+        }
+
+        /// Sets the swap fee to the one of the fee proposal `proposal_id`.
+        ///
+        /// Anyone may execute a proposal within `EXECUTION_PERIOD_BLOCKS`
+        /// blocks after the vote has ended, if at least `QUORUM_BPS` of the LP
+        /// supply at the snapshot voted and more voted for than against it.
+        ///
+        /// On success a `FeeProposalExecuted` and a `FeeBpsChanged` event are
+        /// emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `ProposalNotFound` if there is no such proposal.
+        /// - Returns `AlreadyExecuted` if the proposal has been executed.
+        /// - Returns `VotingOpen` if the voting period has not ended.
+        /// - Returns `ProposalExpired` if the execution period has ended.
+        /// - Returns `QuorumNotReached` if too few LP tokens voted.
+        /// - Returns `ProposalRejected` if no more voted for than against.
+        #[ink(message)]
+        pub fn execute(&mut self, proposal_id: u32) -> Result<()> {
+            let mut proposal = self
+                .fee_proposal(proposal_id)
+                .ok_or(Error::ProposalNotFound)?;
+            if proposal.executed {
+                return Err(Error::AlreadyExecuted)
+            }
+            let block = self.env().block_number();
+            let voting_end = proposal.snapshot_block + VOTING_PERIOD_BLOCKS;
+            if block < voting_end {
+                return Err(Error::VotingOpen)
+            }
+            if block >= voting_end + EXECUTION_PERIOD_BLOCKS {
+                return Err(Error::ProposalExpired)
+            }
+            let votes = proposal.votes_for + proposal.votes_against;
+            let supply = self.total_supply_at(proposal.snapshot_block);
+            if votes.saturating_mul(10_000) < supply.saturating_mul(QUORUM_BPS) {
+                return Err(Error::QuorumNotReached)
+            }
+            if proposal.votes_for <= proposal.votes_against {
+                return Err(Error::ProposalRejected)
+            }
+            proposal.executed = true;
+            self.fee_proposals.insert(proposal_id, proposal);
+            self.env().emit_event(FeeProposalExecuted { proposal_id });
+            self.apply_fee_bps(proposal.fee_bps);
+            Ok(())
+        }
+
+        /// Returns the largest price impact in basis points a swap may have,
+        /// zero if unlimited.
+        #[ink(message)]
+        pub fn max_price_impact_bps(&self) -> u16 {
+            *self.max_price_impact_bps
+        }
+
+        /// Sets the largest price impact in basis points a swap may have. Zero
+        /// disables the limit.
+        ///
+        /// The limit applies on top of the constant product check.
+        ///
+        /// On success a `MaxPriceImpactChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `InvalidPriceImpactBps` if `bps` exceeds 10 000.
+        #[ink(message)]
+        pub fn set_max_price_impact_bps(&mut self, bps: u16) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            if bps > 10_000 {
+                return Err(Error::InvalidPriceImpactBps { bps })
+            }
+            *self.max_price_impact_bps = bps;
+            self.env().emit_event(MaxPriceImpactChanged {
+                max_price_impact_bps: bps,
+            });
+            Ok(())
+        }
+
+        /// Returns the all-time fees collected in token0 and token1.
+        ///
+        /// Together with the reserves this allows to compute the APR off-chain.
+        #[ink(message)]
+        pub fn total_fees(&self) -> (Balance, Balance) {
+            (self.fees_collected0, self.fees_collected1)
+        }
+
+        /// Returns the all-time fees of token0 and token1 per LP token.
+        ///
+        /// The values are Q128.128 fixed-point numbers given as their `(high,
+        /// low)` `u128` halves, see `math::FeeGrowth`. Every swap adds its fee
+        /// times `2^128` divided by the LP supply at the time, so mints and burns
+        /// do not move the values. They wrap around at `2^256` and only
+        /// differences are meaningful: a staking contract snapshots them and pays
+        /// `wrapping_sub(now, snapshot) * lp_staked / 2^128`, see
+        /// `math::fees_earned`.
+        #[ink(message)]
+        pub fn fee_growth_global(&self) -> (FeeGrowth, FeeGrowth) {
+            (*self.fee_growth_global0, *self.fee_growth_global1)
+        }
+
+        /// Returns the fees of token0 and token1 earned by the LP tokens of
+        /// `account` while it held them.
+        ///
+        /// The fees stay in the reserves and are paid out when the LP tokens are
+        /// burned. This only attributes them, e.g. to stream them to holders.
+        #[ink(message)]
+        pub fn unclaimed_fees(&self, account: AccountId) -> (Balance, Balance) {
+            let (owed0, owed1) = self.fees_owed.get(&account).copied().unwrap_or((0, 0));
+            let (earned0, earned1) = self.fees_earned_since_last(account);
+            (owed0.saturating_add(earned0), owed1.saturating_add(earned1))
+        }
+
+        /// Returns the amounts of token0 and token1 to deposit for minting
+        /// `liquidity` LP tokens at the current reserves.
+        ///
+        /// The amounts are rounded up, so the mint yields at least `liquidity`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `EmptyPool` if no LP tokens exist, since the first mint sets
+        /// the ratio of the tokens.
+        #[ink(message)]
+        pub fn deposit_for_liquidity(
+            &self,
+            liquidity: Balance,
+        ) -> Result<(Balance, Balance)> {
+            let total_supply = *self.total_supply;
+            if total_supply == 0 {
+                return Err(Error::EmptyPool)
+            }
+            Ok(math::deposit_for_liquidity(
+                liquidity,
+                self.reserve0,
+                self.reserve1,
+                total_supply,
+            ))
+        }
+
+        /// Compares the reserves of the pair to its token balances.
+        ///
+        /// A surplus comes from donations and can be skimmed, a deficit means
+        /// the pair lost tokens outside of its own accounting.
+        #[ink(message)]
+        pub fn reconcile(&self) -> ReconcileReport {
+            let self_account_id = self.env().account_id();
+            self.reconcile_with(
+                self.token0.balance_of(self_account_id),
+                self.token1.balance_of(self_account_id),
+            )
+        }
+
+        /// Returns the LP tokens of `account` and their share of the reserves.
+        ///
+        /// Locked liquidity is held by the pair and not included.
+        #[ink(message)]
+        pub fn position(&self, account: AccountId) -> Position {
+            let lp_balance = self.balance_of(account);
+            let total_supply = *self.total_supply;
+            if total_supply == 0 {
+                return Position {
+                    lp_balance,
+                    share_bps: 0,
+                    underlying0: 0,
+                    underlying1: 0,
+                }
+            }
+            let (underlying0, underlying1) =
+                math::burn_amounts(lp_balance, self.reserve0, self.reserve1, total_supply);
+            Position {
+                lp_balance,
+                share_bps: (lp_balance * 10_000 / total_supply) as u16,
+                underlying0,
+                underlying1,
+            }
+        }
+
+        /// Returns the all-time volume, fees and number of swaps of the pair.
+        #[ink(message)]
+        pub fn get_stats(&self) -> PairStats {
+            PairStats {
+                volume0: self.volume0_cumulative,
+                volume1: self.volume1_cumulative,
+                fees0: self.fees_collected0,
+                fees1: self.fees_collected1,
+                swap_count: self.swap_count,
+            }
+        }
+
+        /// Returns the reserves and statistics of the pair at the current block,
+        /// for `restore_snapshot` to set up a pair on another fork.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn take_snapshot(&self) -> Result<PairSnapshot> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            Ok(PairSnapshot {
+                block: self.env().block_number(),
+                reserve0: self.reserve0,
+                reserve1: self.reserve1,
+                fee_bps: *self.fee_bps,
+                stats: self.get_stats(),
+            })
+        }
+
+        /// Sets up a freshly deployed pair from `snapshot`.
+        ///
+        /// LP balances are not part of the snapshot, so the pair is paused
+        /// until a guardian resumes it after migrating them. Otherwise the
+        /// first mint would receive the restored reserves.
+        ///
+        /// On success a `Sync` and a `Paused` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `AlreadyRestored` if a snapshot has been restored before.
+        /// - Returns `PairInUse` if the pair already has liquidity or swaps.
+        /// - Returns `InvalidFeeBps` if the fee exceeds `math::MAX_FEE_BPS`.
+        /// - Returns `ReserveOverflow` if a reserve exceeds `math::RESERVE_MAX`.
+        #[ink(message)]
+        pub fn restore_snapshot(&mut self, snapshot: PairSnapshot) -> Result<()> {
+            let account = self.env().caller();
+            self.roles.only_role(OWNER_ROLE, account)?;
+            if self.snapshot_restored {
+                return Err(Error::AlreadyRestored)
+            }
+            if *self.total_supply > 0
+                || self.reserve0 > 0
+                || self.reserve1 > 0
+                || self.swap_count > 0
+            {
+                return Err(Error::PairInUse)
+            }
+            Self::ensure_valid_fee_bps(snapshot.fee_bps)?;
+            self.emit_sync(snapshot.reserve0, snapshot.reserve1)?;
+            self.snapshot_restored = true;
+            *self.fee_bps = snapshot.fee_bps;
+            self.volume0_cumulative = snapshot.stats.volume0;
+            self.volume1_cumulative = snapshot.stats.volume1;
+            self.fees_collected0 = snapshot.stats.fees0;
+            self.fees_collected1 = snapshot.stats.fees1;
+            self.swap_count = snapshot.stats.swap_count;
+            self.paused = true;
+            self.env().emit_event(Paused { account });
+            Ok(())
+        }
+
+        /// Returns the block the pair was created in.
+        #[ink(message)]
+        pub fn created_at(&self) -> BlockNumber {
+            self.created_at
+        }
+
+        /// Returns the time the pair was created at.
+        ///
+        /// Together with `get_stats` this allows to annualize the fee APR.
+        #[ink(message)]
+        pub fn created_timestamp(&self) -> Timestamp {
+            self.created_timestamp
+        }
+
+        /// Returns the gas limit of calls into other contracts.
+        #[ink(message)]
+        pub fn callback_gas_limit(&self) -> u64 {
+            *self.callback_gas_limit
+        }
+
+        /// Returns the contract notified of every LP token movement, if any.
+        #[ink(message)]
+        pub fn transfer_hook(&self) -> Option<AccountId> {
+            *self.transfer_hook
+        }
+
+        /// Sets the contract notified of every LP token movement, or removes
+        /// it for `None`.
+        ///
+        /// After the balances have been updated the pair calls
+        /// `LpTransferHook::on_lp_transfer(from, to, amount)` on the hook, with
+        /// `None` as `from` for mints and as `to` for burns. The call may use up
+        /// to `callback_gas_limit`. A failing call only emits `HookFailed`, so
+        /// the hook cannot block LP tokens.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not an owner.
+        #[ink(message)]
+        pub fn set_transfer_hook(
+            &mut self,
+            transfer_hook: Option<AccountId>,
+        ) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            *self.transfer_hook = transfer_hook;
+            Ok(())
+        }
+
+        /// Returns the contracts notified of every update of the reserves.
+        #[ink(message)]
+        pub fn observers(&self) -> Vec<AccountId> {
+            self.observers.iter().copied().collect()
+        }
+
+        /// Registers `observer` to be notified of every update of the reserves.
+        ///
+        /// After each `Sync` event the pair calls
+        /// `PairObserver::on_sync(reserve0, reserve1)` on every observer with up
+        /// to `callback_gas_limit` each. A failing call only emits `HookFailed`.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `ObserverExists` if `observer` is already registered.
+        /// - Returns `TooManyObservers` if `MAX_OBSERVERS` are registered.
+        #[ink(message)]
+        pub fn register_observer(&mut self, observer: AccountId) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            if self.observers.iter().any(|&registered| registered == observer) {
+                return Err(Error::ObserverExists)
+            }
+            if self.observers.len() >= MAX_OBSERVERS {
+                return Err(Error::TooManyObservers)
+            }
+            self.observers.push(observer);
+            Ok(())
+        }
+
+        /// Stops notifying `observer` of updates of the reserves.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `MissingRole` if the caller is not an owner.
+        /// - Returns `ObserverNotFound` if `observer` is not registered.
+        #[ink(message)]
+        pub fn unregister_observer(&mut self, observer: AccountId) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            let index = self
+                .observers
+                .iter()
+                .position(|&registered| registered == observer)
+                .ok_or(Error::ObserverNotFound)?;
+            self.observers.swap_remove_drop(index as u32);
+            Ok(())
+        }
+
+        /// Returns the number of accounts holding LP tokens.
+        ///
+        /// The pair itself is not counted, it only holds the locked
+        /// `minimum_liquidity`, liquidity in custody and LP tokens about to be
+        /// burned.
+        #[ink(message)]
+        pub fn holder_count(&self) -> u32 {
+            self.holder_count
+        }
+
+        /// Returns whether `token` is token0 or token1 of the pair.
+        ///
+        /// Routers can use this to reject sending swap outputs to a pool token.
+        #[ink(message)]
+        pub fn is_pool_token(&self, token: AccountId) -> bool {
+            self.is_token0(token).is_ok()
+        }
+
+        /// Returns whether the pair is initialized, paused, locked and has liquidity.
+        ///
+        /// Routers can use this to skip unusable pairs with a single call.
+        #[ink(message)]
+        pub fn status(&self) -> PoolStatus {
+            PoolStatus {
+                initialized: self.initialized,
+                paused: self.paused,
+                locked: self.locked,
+                has_liquidity: *self.total_supply > 0,
+            }
+        }
+
+        /// Pauses minting and swapping. Burning stays possible.
+        ///
+        /// On success a `Paused` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not a guardian.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            let account = self.env().caller();
+            self.roles.only_role(GUARDIAN_ROLE, account)?;
+            self.paused = true;
+            self.env().emit_event(Paused { account });
+            Ok(())
+        }
+
+        /// Resumes minting and swapping.
+        ///
+        /// On success an `Unpaused` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller is not a guardian.
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            let account = self.env().caller();
+            self.roles.only_role(GUARDIAN_ROLE, account)?;
+            self.paused = false;
+            self.env().emit_event(Unpaused { account });
+            Ok(())
+        }
+
+        /// Burns all LP tokens of the caller for their share of the reserves
+        /// while the pair is paused.
+        ///
+        /// This lets LPs recover their funds from a pair that stays paused. No
+        /// fee is charged.
+        ///
+        /// Returns the amounts of token0 and token1 sent to the caller. On
+        /// success an `EmergencyWithdraw` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `Blacklisted` if the caller is blacklisted.
+        /// - Returns `NotPaused` if the pair is not paused.
+        /// - Returns `NoLiquidity` if the caller holds no LP tokens.
+        ///
+        /// # Panics
+        ///
+        /// If a token transfer fails.
+        #[ink(message)]
+        pub fn emergency_withdraw(&mut self) -> Result<(Balance, Balance)> {
+            let account = self.env().caller();
+            self.lock();
+            let withdrawn = self.emergency_burn(account);
+            if let Ok((amount0, amount1)) = withdrawn {
+                assert!(
+                    self.token0.transfer(account, amount0).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+                assert!(
+                    self.token1.transfer(account, amount1).is_ok(),
+                    "Uniswap: TRANSFER_FAILED"
+                );
+            }
+            self.unlock();
+            withdrawn
+        }
+
+        /// Moves `amount` LP tokens of the caller into the custody of the pair
+        /// until `unlock_timestamp`.
+        ///
+        /// Every account has at most one lock. Locking again adds `amount` to it
+        /// and moves its end to `unlock_timestamp`.
+        ///
+        /// Locked LP tokens belong to the pair while they are locked and can
+        /// therefore neither be transferred nor burned by the owner.
+        ///
+        /// On success a `LiquidityLocked` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `LockShortened` if the existing lock ends after `unlock_timestamp`.
+        /// - Returns `InsufficientBalance` error if there are not enough LP tokens on
+        ///   the caller's account balance.
+        #[ink(message)]
+        pub fn lock_liquidity(&mut self, amount: Balance, unlock_timestamp: Timestamp) -> Result<()> {
+            let owner = self.env().caller();
+            let (locked, current_unlock) = self.get_lock(owner).unwrap_or((0, 0));
+            if unlock_timestamp < current_unlock {
+                return Err(Error::LockShortened {
+                    current: current_unlock,
+                    requested: unlock_timestamp,
+                })
+            }
+            self.transfer_from_to(owner, self.env().account_id(), amount)?;
+            self.liquidity_locks.insert(owner, (locked + amount, unlock_timestamp));
+            self.env().emit_event(LiquidityLocked {
+                owner,
+                amount,
+                unlock_timestamp,
+            });
+            Ok(())
+        }
+
+        /// Moves the end of the caller's lock to `unlock_timestamp`.
+        ///
+        /// On success a `LiquidityLocked` event with an `amount` of zero is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NoLockedLiquidity` if the caller has no lock.
+        /// - Returns `LockShortened` if the lock ends after `unlock_timestamp`.
+        #[ink(message)]
+        pub fn extend_lock(&mut self, unlock_timestamp: Timestamp) -> Result<()> {
+            let owner = self.env().caller();
+            let (locked, current_unlock) =
+                self.get_lock(owner).ok_or(Error::NoLockedLiquidity)?;
+            if unlock_timestamp < current_unlock {
+                return Err(Error::LockShortened {
+                    current: current_unlock,
+                    requested: unlock_timestamp,
+                })
+            }
+            self.liquidity_locks.insert(owner, (locked, unlock_timestamp));
+            self.env().emit_event(LiquidityLocked {
+                owner,
+                amount: 0,
+                unlock_timestamp,
+            });
+            Ok(())
+        }
+
+        /// Returns the LP tokens of `owner` held in custody by the pair and the
+        /// time they are released.
+        #[ink(message)]
+        pub fn get_lock(&self, owner: AccountId) -> Option<(Balance, Timestamp)> {
+            self.liquidity_locks.get(&owner).copied()
+        }
+
+        /// Returns the locked LP tokens of the caller once the block timestamp
+        /// has reached the end of the lock.
+        ///
+        /// On success a `LiquidityUnlocked` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NoLockedLiquidity` if the caller has no lock.
+        /// - Returns `LiquidityStillLocked` if the lock ends in the future.
+        #[ink(message)]
+        pub fn withdraw_locked(&mut self) -> Result<Balance> {
+            let owner = self.env().caller();
+            let (amount, unlock_timestamp) =
+                self.get_lock(owner).ok_or(Error::NoLockedLiquidity)?;
+            let now = self.env().block_timestamp();
+            if now < unlock_timestamp {
+                return Err(Error::LiquidityStillLocked {
+                    unlock_timestamp,
+                    now,
+                })
+            }
+            self.liquidity_locks.take(&owner);
+            self.transfer_from_to(self.env().account_id(), owner, amount)?;
+            self.env().emit_event(LiquidityUnlocked {
+                owner,
+                amount,
+                unlock_timestamp,
+            });
+            Ok(amount)
+        }
+
+        /// Returns `true` if `account` has been granted `role`.
+        #[ink(message)]
+        pub fn has_role(&self, role: RoleId, account: AccountId) -> bool {
+            self.roles.has_role(role, account)
+        }
+
+        /// Returns the admin role that can grant and revoke `role`.
+        #[ink(message)]
+        pub fn role_admin(&self, role: RoleId) -> RoleId {
+            self.roles.role_admin(role)
+        }
+
+        /// Grants `role` to `account`.
+        ///
+        /// A `RoleGranted` event is emitted if `account` did not have `role` before.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller lacks the admin role of `role`.
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: RoleId, account: AccountId) -> Result<()> {
+            let sender = self.env().caller();
+            if self.roles.grant_role(role, account, sender)? {
+                self.env().emit_event(RoleGranted {
+                    role,
+                    account,
+                    sender,
+                });
+            }
+            Ok(())
+        }
+
+        /// Revokes `role` from `account`.
+        ///
+        /// A `RoleRevoked` event is emitted if `account` had `role` before.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MissingRole` if the caller lacks the admin role of `role`.
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: RoleId, account: AccountId) -> Result<()> {
+            let sender = self.env().caller();
+            if self.roles.revoke_role(role, account, sender)? {
+                self.env().emit_event(RoleRevoked {
+                    role,
+                    account,
+                    sender,
+                });
+            }
+            Ok(())
+        }
+
+        /// Returns the total token supply.
+        #[ink(message)]
+        pub fn total_supply(&self) -> Balance {
+            *self.total_supply
+        }
+
+         /// Returns the account balance for the specified `owner`.
+        ///
+        /// Returns `0` if the account is non-existent.
+        #[ink(message)]
+        pub fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(&owner).copied().unwrap_or(0)
+        }
+           
+        /// Returns the amount which `spender` is still allowed to withdraw from `owner`.
+        ///
+        /// Returns `0` if no allowance has been set `0`.
+        #[ink(message)]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.allowances.get(&(owner, spender)).copied().unwrap_or(0)
+        }
+
+        /// Transfers `value` amount of tokens from the caller's account to account `to`.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the caller's account balance.
+        ///
+        /// Returns `BalanceOverflow` error if the balance of `to` would overflow.
+        ///
+        /// Returns `Blacklisted` error if the caller or `to` is blacklisted.
+        #[ink(message)]
+        pub fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            let from = self.env().caller();
+            self.transfer_from_to(from, to, value)?;
+            Ok(())
+        }
+
+        /// Allows `spender` to withdraw from the caller's account multiple times, up to
+        /// the `value` amount.
+        ///
+        /// If this function is called again it overwrites the current allowance with `value`.
+        ///
+        /// An `Approval` event is emitted.
+        #[ink(message)]
+        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            self.allowances.insert((owner, spender), value);
+            self.env().emit_event(Approval {
+                owner,
+                spender,
+                value,
+            });
+            Ok(())
+        }
+
+        /// Sets the allowance of every spender of `approvals` like `approve`.
+        ///
+        /// An `Approval` event is emitted for every entry.
+        ///
+        /// # Errors
+        ///
+        /// Returns `TooManyApprovals` without setting any allowance if
+        /// `approvals` has more than `MAX_BATCH_APPROVALS` entries.
+        #[ink(message)]
+        pub fn batch_approve(
+            &mut self,
+            approvals: Vec<(AccountId, Balance)>,
+        ) -> Result<()> {
+            if approvals.len() > MAX_BATCH_APPROVALS as usize {
+                return Err(Error::TooManyApprovals)
+            }
+            for (spender, value) in approvals {
+                self.approve(spender, value)?;
+            }
+            Ok(())
+        }
+
+        /// Approves `spender` like `approve` and calls its
+        /// `on_token_approval(owner, value, data)` message, so the spender can
+        /// act on the allowance in the same transaction.
+        ///
+        /// The pair is not locked during the call, the spender is expected to
+        /// call `transfer_from`.
+        ///
+        /// # Panics
+        ///
+        /// If the spender rejects the approval.
+        #[ink(message)]
+        pub fn approve_and_call(
+            &mut self,
+            spender: AccountId,
+            value: Balance,
+            data: Vec<u8>,
+        ) -> Result<()> {
+            let owner = self.env().caller();
+            self.approve(spender, value)?;
+            let accepted = build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(spender)
+                .gas_limit(*self.callback_gas_limit)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ON_TOKEN_APPROVAL_SELECTOR))
+                        .push_arg(&owner)
+                        .push_arg(&value)
+                        .push_arg(&data),
+                )
+                .returns::<ReturnType<bool>>()
+                .fire();
+            assert!(accepted == Ok(true), "Uniswap: APPROVAL_REJECTED");
+            Ok(())
+        }
+
+        /// Transfers `value` tokens on the behalf of `from` to the account `to`.
+        ///
+        /// This can be used to allow a contract to transfer tokens on ones behalf and/or
+        /// to charge fees in sub-currencies, for example.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientAllowance` error if there are not enough tokens allowed
+        /// for the caller to withdraw from `from`.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the the account balance of `from`.
+        ///
+        /// Returns `Blacklisted` error if `from` or `to` is blacklisted.
+        #[ink(message)]
+        pub fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let allowance = self.allowance(from, caller);
+            if allowance < value {
+                return Err(Error::InsufficientAllowance {
+                    required: value,
+                    available: allowance,
+                })
+            }
+            self.transfer_from_to(from, to, value)?;
+            self.allowances.insert((from, caller), allowance - value);
+            Ok(())
+        }
+
+        /// Transfers `value` amount of tokens from the account `from` to account `to`.
+        ///
+        /// Returns the new balances of `from` and `to`.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the account balance of `from`.
+        ///
+        /// Returns `BalanceOverflow` error if the balance of `to` would overflow.
+        ///
+        /// Returns `TransferLocked` error if `from` received minted LP tokens less
+        /// than `transfer_lock_blocks` blocks ago.
+        /// No balance is changed in these cases.
+        fn transfer_from_to(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<(Balance, Balance)> {
+            self.ensure_not_blacklisted(from)?;
+            self.ensure_not_blacklisted(to)?;
+            if let Some(&mint_block) = self.mint_block.get(&from) {
+                let unlock_block = mint_block.saturating_add(*self.transfer_lock_blocks);
+                if self.env().block_number() < unlock_block {
+                    return Err(Error::TransferLocked { unlock_block })
+                }
+            }
+            let available = self.balance_of(from);
+            if available < value {
+                return Err(Error::InsufficientBalance {
+                    required: value,
+                    available,
+                })
+            }
+            let mut from_balance = available - value;
+            let to_balance = if from == to {
+                from_balance
+            } else {
+                self.balance_of(to)
+            };
+            let to_balance = to_balance
+                .checked_add(value)
+                .ok_or(Error::BalanceOverflow)?;
+            if from == to {
+                // A self-transfer leaves the balance unchanged.
+                from_balance = to_balance;
+            }
+            self.accrue_fees(from);
+            self.accrue_fees(to);
+            self.balances.insert(from, from_balance);
+            self.balances.insert(to, to_balance);
+            if from != to {
+                self.track_holder(from, available, from_balance);
+                self.track_holder(to, to_balance - value, to_balance);
+                self.write_checkpoint(Some(from), from_balance);
+                self.write_checkpoint(Some(to), to_balance);
+            }
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value,
+            });
+            self.notify_transfer_hook(Some(from), Some(to), value);
+            Ok((from_balance, to_balance))
+        }
+
+        /// Returns `(amount_out, reserve0, reserve1)` after swapping exactly
+        /// `amount_in` of token0 (`zero_for_one`) or token1.
+        ///
+        /// # Panics
+        ///
+        /// If the output is less than `amount_out_min` or if the new reserves
+        /// would violate the constant product.
+        fn quote_exact_in(
+            &self,
+            amount_in: Balance,
+            zero_for_one: bool,
+            amount_out_min: Balance,
+        ) -> (Balance, Balance, Balance) {
+            let (reserve_in, reserve_out) = if zero_for_one {
+                (self.reserve0, self.reserve1)
+            } else {
+                (self.reserve1, self.reserve0)
+            };
+            let fee_bps = self.swap_fee_bps();
+            let amount_out =
+                math::get_amount_out(amount_in, reserve_in, reserve_out, fee_bps);
+            assert!(
+                amount_out >= amount_out_min,
+                "Uniswap: INSUFFICIENT_OUTPUT_AMOUNT"
+            );
+            let balance_in = reserve_in + amount_in;
+            let balance_out = reserve_out - amount_out;
+            assert!(
+                math::keeps_k(
+                    reserve_in,
+                    reserve_out,
+                    balance_in,
+                    balance_out,
+                    amount_in,
+                    fee_bps,
+                ),
+                "Uniswap: K"
+            );
+            if zero_for_one {
+                (amount_out, balance_in, balance_out)
+            } else {
+                (amount_out, balance_out, balance_in)
+            }
+        }
+
+        /// Returns the LP tokens minted for depositing `amount0` and `amount1`
+        /// while `total_supply` LP tokens exist.
+        ///
+        /// # Panics
+        ///
+        /// - If the first mint deposits less than `initial_mint_minimum` of a token.
+        /// - If donations inflated the LP token price so far that the mint loses
+        ///   a significant part of the deposit to rounding.
+        /// - If no LP tokens would be minted.
+        fn liquidity_to_mint(
+            &self,
+            amount0: Balance,
+            amount1: Balance,
+            total_supply: Balance,
+        ) -> Balance {
+            if total_supply == 0 {
+                let minimum = *self.initial_mint_minimum;
+                assert!(
+                    amount0 >= minimum && amount1 >= minimum,
+                    "Uniswap: INITIAL_MINT_TOO_SMALL"
+                );
+                let liquidity =
+                    math::sqrt(amount0 * amount1).saturating_sub(*self.minimum_liquidity);
+                assert!(liquidity > 0, "Uniswap: INSUFFICIENT_LIQUIDITY_MINTED");
+                return liquidity
+            }
+            let liquidity = math::mint_liquidity(
+                amount0,
+                amount1,
+                self.reserve0,
+                self.reserve1,
+                total_supply,
+            );
+            assert!(
+                amount0 == 0 || amount1 == 0 || liquidity >= DONATION_LIQUIDITY_THRESHOLD,
+                "Uniswap: DONATION_ATTACK"
+            );
+            assert!(liquidity > 0, "Uniswap: INSUFFICIENT_LIQUIDITY_MINTED");
+            liquidity
+        }
+
+        /// Returns the part of the fee on `amount_in` refunded to a swap that moves
+        /// the reserves to `(reserve0, reserve1)`.
+        ///
+        /// Only swaps that reduce the reserve imbalance are refunded.
+        fn imbalance_refund(
+            &self,
+            amount_in: Balance,
+            reserve0: Balance,
+            reserve1: Balance,
+        ) -> Balance {
+            if !math::reduces_imbalance(self.reserve0, self.reserve1, reserve0, reserve1) {
+                return 0
+            }
+            let fee_taken = self.fee_of(amount_in);
+            fee_taken * *self.imbalance_refund_bps as Balance / 100
+        }
+
+        /// Returns the rebate of a swap by `account` that paid `fee_taken` of its
+        /// input token, and the rebate converted to the output token at the price
+        /// of the reserves `reserve_in` and `reserve_out` after the swap.
+        ///
+        /// The rebate is capped to the fee left after the imbalance `refund`, so
+        /// the pair never pays back more than the fee and the swap keeps the
+        /// constant product.
+        fn holder_rebate(
+            &self,
+            account: AccountId,
+            fee_taken: Balance,
+            refund: Balance,
+            reserve_in: Balance,
+            reserve_out: Balance,
+        ) -> (Balance, Balance) {
+            if *self.rebate_threshold == 0 || fee_taken == 0 {
+                return (0, 0)
+            }
+            let governance_token: Erc20 =
+                FromAccountId::from_account_id(*self.governance_token);
+            let rebate = self
+                .rebate_for(fee_taken, governance_token.balance_of(account))
+                .min(fee_taken - refund);
+            let rebate_out = math::mul_div(rebate, reserve_out, reserve_in).unwrap_or(0);
+            (rebate, rebate_out)
+        }
+
+        /// Returns the part of `fee_taken` refunded to a holder of
+        /// `governance_balance` governance tokens.
+        fn rebate_for(&self, fee_taken: Balance, governance_balance: Balance) -> Balance {
+            let threshold = *self.rebate_threshold;
+            if threshold == 0 || governance_balance < threshold {
+                return 0
+            }
+            fee_taken * HOLDER_REBATE_PERCENT / 100
+        }
+
+        /// Returns the latest answer of the Chainlink aggregator `price_feed`
+        /// with 18 decimals.
+        fn latest_price(&self, price_feed: AccountId) -> Option<Balance> {
+            let answer = build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(price_feed)
+                .gas_limit(*self.callback_gas_limit)
+                .transferred_value(0)
+                .exec_input(ExecutionInput::new(Selector::new(LATEST_ANSWER_SELECTOR)))
+                .returns::<ReturnType<i128>>()
+                .fire()
+                .ok()?;
+            let decimals = build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(price_feed)
+                .gas_limit(*self.callback_gas_limit)
+                .transferred_value(0)
+                .exec_input(ExecutionInput::new(Selector::new(DECIMALS_SELECTOR)))
+                .returns::<ReturnType<u8>>()
+                .fire()
+                .ok()?;
+            math::normalize_price(answer, decimals)
+        }
+
+        /// Returns the value of one LP token with `math::PRICE_DECIMALS` decimals
+        /// for the normalized prices `price0` and `price1` of tokens with
+        /// `decimals0` and `decimals1` decimals.
+        fn lp_value(
+            &self,
+            price0: Balance,
+            price1: Balance,
+            decimals0: u8,
+            decimals1: u8,
+        ) -> Option<Balance> {
+            math::lp_value(
+                math::normalize_amount(self.reserve0, decimals0)?,
+                price0,
+                math::normalize_amount(self.reserve1, decimals1)?,
+                price1,
+                *self.total_supply,
+            )
+        }
+
+        /// Returns the cached name and symbol of the LP token, composing and
+        /// caching them first if necessary.
+        fn lp_metadata(&mut self) -> (String, String) {
+            if let Some(metadata) = &*self.lp_metadata {
+                return metadata.clone()
+            }
+            let metadata = lp_metadata_of(
+                self.token_symbol(self.token0.to_account_id()),
+                self.token_symbol(self.token1.to_account_id()),
+            );
+            *self.lp_metadata = Some(metadata.clone());
+            metadata
+        }
+
+        /// Returns the symbol of `token` or `None` if the call fails, e.g.
+        /// because the token has no `symbol` message.
+        fn token_symbol(&self, token: AccountId) -> Option<String> {
+            build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(token)
+                .gas_limit(*self.callback_gas_limit)
+                .transferred_value(0)
+                .exec_input(ExecutionInput::new(Selector::new(SYMBOL_SELECTOR)))
+                .returns::<ReturnType<String>>()
+                .fire()
+                .ok()
+        }
+
+        /// Burns all LP tokens of `account` and takes their share of the
+        /// reserves out of the reserves, leaving the token transfers to the
+        /// caller.
+        ///
+        /// Returns the amounts of token0 and token1 owed to `account`.
+        fn emergency_burn(&mut self, account: AccountId) -> Result<(Balance, Balance)> {
+            self.ensure_not_blacklisted(account)?;
+            if !self.paused {
+                return Err(Error::NotPaused)
+            }
+            let liquidity = self.balance_of(account);
+            if liquidity == 0 {
+                return Err(Error::NoLiquidity)
+            }
+            let (amount0, amount1) = math::burn_amounts(
+                liquidity,
+                self.reserve0,
+                self.reserve1,
+                *self.total_supply,
+            );
+            self.emit_sync(self.reserve0 - amount0, self.reserve1 - amount1)?;
+            self._burn(account, liquidity);
+            self.env().emit_event(EmergencyWithdraw {
+                account,
+                liquidity,
+                amount0,
+                amount1,
+            });
+            Ok((amount0, amount1))
+        }
+
+        /// Returns the report of `reconcile` for the token balances `balance0`
+        /// and `balance1`.
+        fn reconcile_with(
+            &self,
+            balance0: Balance,
+            balance1: Balance,
+        ) -> ReconcileReport {
+            ReconcileReport {
+                reserve0: self.reserve0,
+                reserve1: self.reserve1,
+                balance0,
+                balance1,
+                surplus0: balance0.saturating_sub(self.reserve0),
+                surplus1: balance1.saturating_sub(self.reserve1),
+                deficit0: self.reserve0.saturating_sub(balance0),
+                deficit1: self.reserve1.saturating_sub(balance1),
+            }
+        }
+
+        /// Returns the result of `skimmable` for the token balances `balance0`
+        /// and `balance1`.
+        fn skimmable_with(
+            &self,
+            balance0: Balance,
+            balance1: Balance,
+        ) -> (Balance, Balance) {
+            (
+                balance0.saturating_sub(self.reserve0),
+                balance1.saturating_sub(self.reserve1),
+            )
+        }
+
+        /// Burns the fewest LP tokens of `owner` that pay out at least
+        /// `amount0_out` and `amount1_out` to `to`, and takes the payout out of
+        /// the reserves, leaving the token transfers to the caller.
+        ///
+        /// Returns the LP tokens burned and the amounts of token0 and token1
+        /// owed to `to`.
+        fn burn_for_amounts(
+            &mut self,
+            owner: AccountId,
+            amount0_out: Balance,
+            amount1_out: Balance,
+            to: AccountId,
+            deadline: Timestamp,
+        ) -> Result<(Balance, Balance, Balance)> {
+            let now = self.env().block_timestamp();
+            if now > deadline {
+                return Err(Error::Expired { deadline, now })
+            }
+            let total_supply = *self.total_supply;
+            if total_supply == 0 {
+                return Err(Error::EmptyPool)
+            }
+            let liquidity = math::liquidity_for_amounts(
+                amount0_out,
+                amount1_out,
+                self.reserve0,
+                self.reserve1,
+                total_supply,
+            );
+            let available = self.balance_of(owner);
+            if available < liquidity {
+                return Err(Error::InsufficientBalance {
+                    required: liquidity,
+                    available,
+                })
+            }
+            let (amount0, amount1) =
+                math::burn_amounts(liquidity, self.reserve0, self.reserve1, total_supply);
+            #[cfg(feature = "debug")]
+            ink_env::debug_println(&format!(
+                "burn: liquidity={}, amount0={}, amount1={}, total_supply={}",
+                liquidity, amount0, amount1, total_supply
+            ));
+            let mut batch = EventBatch::default();
+            self.push_sync(&mut batch, self.reserve0 - amount0, self.reserve1 - amount1)?;
+            self._burn(owner, liquidity);
+            batch.emit_event(Burn {
+                sender: owner,
+                amount0,
+                amount1,
+                to,
+            });
+            self.emit_batch_and_notify(batch);
+            Ok((liquidity, amount0, amount1))
+        }
+
+        /// Mints LP tokens to `to` for the deposit of `amount0` and `amount1`
+        /// received by the pair.
+        ///
+        /// Returns the LP tokens minted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ReserveOverflow` if a reserve would exceed
+        /// `math::RESERVE_MAX`.
+        fn mint_deposit(
+            &mut self,
+            sender: AccountId,
+            to: AccountId,
+            amount0: Balance,
+            amount1: Balance,
+        ) -> Result<Balance> {
+            let total_supply = *self.total_supply;
+            let liquidity = self.liquidity_to_mint(amount0, amount1, total_supply);
+            #[cfg(feature = "debug")]
+            ink_env::debug_println(&format!(
+                "mint: amount0={}, amount1={}, liquidity={}, total_supply={}",
+                amount0, amount1, liquidity, total_supply
+            ));
+            let mut batch = EventBatch::default();
+            self.push_sync(&mut batch, self.reserve0 + amount0, self.reserve1 + amount1)?;
+            if total_supply == 0 {
+                let self_account_id = self.env().account_id();
+                let minimum_liquidity = *self.minimum_liquidity;
+                self._mint(self_account_id, minimum_liquidity);
+            }
+            self._mint(to, liquidity);
+            batch.emit_event(Mint {
+                sender,
+                amount0,
+                amount1,
+            });
+            self.emit_batch_and_notify(batch);
+            Ok(liquidity)
+        }
+
+        /// Mints LP tokens to `to` for the deposit of `amount0` and `amount1`
+        /// received by the pair and records the deposit as a position.
+        ///
+        /// Returns the id of the position.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ReserveOverflow` if a reserve would exceed
+        /// `math::RESERVE_MAX`.
+        fn mint_for_position(
+            &mut self,
+            sender: AccountId,
+            to: AccountId,
+            amount0: Balance,
+            amount1: Balance,
+        ) -> Result<u128> {
+            let liquidity = self.mint_deposit(sender, to, amount0, amount1)?;
+            let id = self.next_position_id;
+            self.positions.insert(
+                id,
+                LiquidityPosition {
+                    owner: to,
+                    liquidity,
+                    token0_deposited: amount0,
+                    token1_deposited: amount1,
+                },
+            );
+            self.next_position_id += 1;
+            Ok(id)
+        }
+
+        /// Returns the amounts of token0 and token1 `add_liquidity` uses from
+        /// `amount0_desired` and `amount1_desired`.
+        fn add_liquidity_amounts(
+            &self,
+            amount0_desired: Balance,
+            amount1_desired: Balance,
+            amount0_min: Balance,
+            amount1_min: Balance,
+        ) -> Result<(Balance, Balance)> {
+            let (amount0, amount1) = math::optimal_amounts(
+                amount0_desired,
+                amount1_desired,
+                self.reserve0,
+                self.reserve1,
+            );
+            if amount0 < amount0_min {
+                return Err(Error::InsufficientAmount {
+                    amount: amount0,
+                    min: amount0_min,
+                })
+            }
+            if amount1 < amount1_min {
+                return Err(Error::InsufficientAmount {
+                    amount: amount1,
+                    min: amount1_min,
+                })
+            }
+            Ok((amount0, amount1))
+        }
+
+        /// Returns the swap fee in basis points.
+        fn swap_fee_bps(&self) -> Balance {
+            Balance::from(*self.fee_bps)
+        }
+
+        /// Returns the swap fee on `amount_in`.
+        fn fee_of(&self, amount_in: Balance) -> Balance {
+            math::fee_of(amount_in, self.swap_fee_bps())
+        }
+
+        /// Returns whether `token` is token0, as opposed to token1.
+        ///
+        /// Returns `UnsupportedToken` if it is neither.
+        fn is_token0(&self, token: AccountId) -> Result<bool> {
+            if token == self.token0.to_account_id() {
+                Ok(true)
+            } else if token == self.token1.to_account_id() {
+                Ok(false)
+            } else {
+                Err(Error::UnsupportedToken)
+            }
+        }
+
+        /// Adds the repayment of a flash loan of token0 (`zero`) or token1 to
+        /// the reserves, given the balances of the pair before the loan and after
+        /// its repayment.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ReserveOverflow` if the reserve would exceed
+        /// `math::RESERVE_MAX`.
+        ///
+        /// # Panics
+        ///
+        /// If the pair gained less than `fee`.
+        fn settle_flash_loan(
+            &mut self,
+            zero: bool,
+            balance_before: Balance,
+            balance_after: Balance,
+            fee: Balance,
+        ) -> Result<()> {
+            assert!(
+                balance_after >= balance_before + fee,
+                "Uniswap: FLASH_LOAN_NOT_REPAID"
+            );
+            // Only the repayment is added, tokens donated before stay skimmable.
+            let gained = balance_after - balance_before;
+            if zero {
+                self.emit_sync(self.reserve0 + gained, self.reserve1)
+            } else {
+                self.emit_sync(self.reserve0, self.reserve1 + gained)
+            }
+        }
+
+        /// Adds a swap of `amount0_in` and `amount1_in` that paid `fee0` and
+        /// `fee1` to the all-time statistics.
+        fn record_swap(
+            &mut self,
+            amount0_in: Balance,
+            amount1_in: Balance,
+            fee0: Balance,
+            fee1: Balance,
+        ) {
+            self.volume0_cumulative = self.volume0_cumulative.saturating_add(amount0_in);
+            self.volume1_cumulative = self.volume1_cumulative.saturating_add(amount1_in);
+            self.fees_collected0 = self.fees_collected0.saturating_add(fee0);
+            self.fees_collected1 = self.fees_collected1.saturating_add(fee1);
+            let total_supply = *self.total_supply;
+            *self.fee_growth_global0 = math::wrapping_add_growth(
+                *self.fee_growth_global0,
+                math::fee_growth(fee0, total_supply),
+            );
+            *self.fee_growth_global1 = math::wrapping_add_growth(
+                *self.fee_growth_global1,
+                math::fee_growth(fee1, total_supply),
+            );
+            self.swap_count = self.swap_count.saturating_add(1);
+        }
+
+        /// Returns the fees earned by the LP tokens of `account` since its last
+        /// LP balance change.
+        fn fees_earned_since_last(&self, account: AccountId) -> (Balance, Balance) {
+            let (last0, last1) = self
+                .fee_growth_inside_last
+                .get(&account)
+                .copied()
+                .unwrap_or(((0, 0), (0, 0)));
+            let liquidity = self.balance_of(account);
+            let growth0 = math::wrapping_sub_growth(*self.fee_growth_global0, last0);
+            let growth1 = math::wrapping_sub_growth(*self.fee_growth_global1, last1);
+            (
+                math::fees_earned(growth0, liquidity),
+                math::fees_earned(growth1, liquidity),
+            )
+        }
+
+        /// Credits the fees earned by `account` at its current LP balance, must
+        /// be called before the balance changes.
+        fn accrue_fees(&mut self, account: AccountId) {
+            let (earned0, earned1) = self.fees_earned_since_last(account);
+            if earned0 > 0 || earned1 > 0 {
+                let owed = self.fees_owed.entry(account).or_insert((0, 0));
+                owed.0 = owed.0.saturating_add(earned0);
+                owed.1 = owed.1.saturating_add(earned1);
+            }
+            self.fee_growth_inside_last.insert(
+                account,
+                (*self.fee_growth_global0, *self.fee_growth_global1),
+            );
+        }
+
+        /// Adds `account` to the mint whitelist if `allowed` or removes it.
+        fn update_whitelist(&mut self, account: AccountId, allowed: bool) -> Result<()> {
+            self.roles.only_role(OWNER_ROLE, self.env().caller())?;
+            if allowed {
+                self.mint_whitelist.insert(account, true);
+            } else {
+                self.mint_whitelist.take(&account);
+            }
+            self.env().emit_event(WhitelistUpdated { account, allowed });
+            Ok(())
+        }
+
+        /// Returns `NotWhitelisted` if the mint whitelist is enabled and `to` is
+        /// not on it.
+        fn ensure_whitelisted(&self, to: AccountId) -> Result<()> {
+            if *self.mint_whitelist_enabled && !self.is_whitelisted(to) {
+                return Err(Error::NotWhitelisted)
+            }
+            Ok(())
+        }
+
+        /// Returns `Blacklisted` if the LP tokens of `account` are frozen.
+        fn ensure_not_blacklisted(&self, account: AccountId) -> Result<()> {
+            if self.is_blacklisted(account) {
+                return Err(Error::Blacklisted { account })
+            }
+            Ok(())
+        }
+
+        /// Returns `PriceImpactTooHigh` if swapping `amount_in` of token0
+        /// (`zero_for_one`) or token1 moves the price of the in token by more
+        /// than `max_price_impact_bps`.
+        fn ensure_price_impact(
+            &self,
+            amount_in: Balance,
+            zero_for_one: bool,
+        ) -> Result<()> {
+            if *self.max_price_impact_bps == 0 {
+                return Ok(())
+            }
+            let (reserve_in, reserve_out) = if zero_for_one {
+                (self.reserve0, self.reserve1)
+            } else {
+                (self.reserve1, self.reserve0)
+            };
+            let fee_bps = self.swap_fee_bps();
+            let amount_out =
+                math::get_amount_out(amount_in, reserve_in, reserve_out, fee_bps);
+            if zero_for_one {
+                self.ensure_reserves_impact(
+                    self.reserve0 + amount_in,
+                    self.reserve1 - amount_out,
+                )
+            } else {
+                self.ensure_reserves_impact(
+                    self.reserve0 - amount_out,
+                    self.reserve1 + amount_in,
+                )
+            }
+        }
+
+        /// Returns `PriceImpactTooHigh` if moving the reserves to `reserve0` and
+        /// `reserve1` lowers the price of either token by more than
+        /// `max_price_impact_bps`.
+        fn ensure_reserves_impact(
+            &self,
+            reserve0: Balance,
+            reserve1: Balance,
+        ) -> Result<()> {
+            let max_bps = *self.max_price_impact_bps;
+            if max_bps == 0 {
+                return Ok(())
+            }
+            // Only the price of the in token falls, the other impact is zero.
+            let impact_bps = math::price_impact_bps(
+                self.reserve0,
+                self.reserve1,
+                reserve0,
+                reserve1,
+            )
+            .max(math::price_impact_bps(
+                self.reserve1,
+                self.reserve0,
+                reserve1,
+                reserve0,
+            )) as u16;
+            if impact_bps > max_bps {
+                return Err(Error::PriceImpactTooHigh { impact_bps, max_bps })
+            }
+            Ok(())
+        }
+
+        /// Panic if a swap pays less than `min_swap_amount`.
+        ///
+        /// `amount_in` has to be derived from the balances of the pair, since the
+        /// stated input of fee on transfer tokens overstates it.
+        fn ensure_not_dust(&self, amount_in: Balance) {
+            assert!(amount_in >= *self.min_swap_amount, "Uniswap: DUST_AMOUNT");
+        }
+
+        /// Checks that no token rebased down since the last update of the
+        /// reserves, see `handle_rebase`.
+        fn ensure_no_rebase(&mut self, batch: &mut EventBatch) -> Result<()> {
+            let self_account_id = self.env().account_id();
+            let balance0 = self.token0.balance_of(self_account_id);
+            let balance1 = self.token1.balance_of(self_account_id);
+            self.handle_rebase(batch, balance0, balance1)
+        }
+
+        /// Syncs the reserves to `balance0` and `balance1` like `push_update` if
+        /// `auto_sync` is enabled and a balance fell below its reserve.
+        ///
+        /// Swaps would otherwise price the input against reserves the pair no
+        /// longer holds. The `Sync` event is added to the `batch` of the swap,
+        /// which notifies the observers once at its end.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ReserveOverflow` if the other balance exceeds
+        /// `math::RESERVE_MAX`.
+        ///
+        /// # Panics
+        ///
+        /// - If a balance fell below its reserve and `auto_sync` is disabled.
+        /// - If both balances are zero while LP tokens exist.
+        fn handle_rebase(
+            &mut self,
+            batch: &mut EventBatch,
+            balance0: Balance,
+            balance1: Balance,
+        ) -> Result<()> {
+            if balance0 >= self.reserve0 && balance1 >= self.reserve1 {
+                return Ok(())
+            }
+            assert!(*self.auto_sync, "Uniswap: REBASE_DETECTED");
+            self.push_update(batch, balance0, balance1)
+        }
+
+        /// Panic if the reserves would be set to zero while LP tokens exist.
+        ///
+        /// An LP token backed by nothing means the pair lost track of its
+        /// balances, and the next price computation would divide by zero. An
+        /// empty pool may have zero reserves.
+        fn ensure_valid_reserves(&self, reserve0: Balance, reserve1: Balance) {
+            assert!(
+                *self.total_supply == 0 || reserve0 > 0 || reserve1 > 0,
+                "Uniswap: INVALID_RESERVES"
+            );
+        }
+
+        /// Sets the reserves like `push_sync`, emits the `Sync` event and
+        /// notifies the observers.
+        fn emit_sync(&mut self, reserve0: Balance, reserve1: Balance) -> Result<()> {
+            let mut batch = EventBatch::default();
+            self.push_sync(&mut batch, reserve0, reserve1)?;
+            self.emit_batch_and_notify(batch);
+            Ok(())
+        }
+
+        /// Sets the reserves to `reserve0` and `reserve1`, adds a `Sync` event
+        /// with them to `batch` and starts a new round of `latest_round_data`.
+        ///
+        /// The observers are left to be notified after the batch was emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ReserveOverflow` and leaves the reserves unchanged if a
+        /// reserve exceeds `math::RESERVE_MAX`, since indexers of the event may
+        /// expect the 112-bit reserves of UniswapV2.
+        fn push_sync(
+            &mut self,
+            batch: &mut EventBatch,
+            reserve0: Balance,
+            reserve1: Balance,
+        ) -> Result<()> {
+            self.ensure_reserves_fit(reserve0, reserve1)?;
+            self.reserve0 = reserve0;
+            self.reserve1 = reserve1;
+            self.reserves_updated_at = self.env().block_timestamp();
+            self.price_round += 1;
+            batch.emit_event(Sync { reserve0, reserve1 });
+            batch.synced = true;
+            Ok(())
+        }
+
+        /// Returns `ReserveOverflow` if `reserve0` or `reserve1` exceeds
+        /// `math::RESERVE_MAX`.
+        ///
+        /// Messages that transfer tokens check the reserves they will sync to
+        /// with this beforehand, since an error returned after a transfer does
+        /// not revert it.
+        fn ensure_reserves_fit(
+            &self,
+            reserve0: Balance,
+            reserve1: Balance,
+        ) -> Result<()> {
+            if reserve0 > math::RESERVE_MAX || reserve1 > math::RESERVE_MAX {
+                return Err(Error::ReserveOverflow)
+            }
+            Ok(())
+        }
+
+        /// Returns `ReserveOverflow` if depositing `amount0` and `amount1` would
+        /// sync a reserve above `math::RESERVE_MAX`.
+        ///
+        /// Tokens donated to the pair are synced along with the deposit.
+        fn ensure_deposit_fits(&self, amount0: Balance, amount1: Balance) -> Result<()> {
+            let self_account_id = self.env().account_id();
+            let balance0 = self.token0.balance_of(self_account_id);
+            let balance1 = self.token1.balance_of(self_account_id);
+            self.ensure_reserves_fit(
+                balance0.saturating_add(amount0),
+                balance1.saturating_add(amount1),
+            )
+        }
+
+        /// Adds `swap` to `batch`, followed by a `SwapLegacy` event with the same
+        /// fields if the pair emits legacy events.
+        fn push_swap(&self, batch: &mut EventBatch, swap: Swap) {
+            let legacy = SwapLegacy {
+                sender: swap.sender,
+                to: swap.to,
+                amount0In: swap.amount0In,
+                amount1In: swap.amount1In,
+                amount0Out: swap.amount0Out,
+                amount1Out: swap.amount1Out,
+            };
+            batch.emit_event(swap);
+            if self.legacy_events {
+                batch.emit_event(legacy);
+            }
+        }
+
+        /// Emits the events of `batch` in the order they were added.
+        fn emit_batch(&self, batch: EventBatch) {
+            for event in batch.events {
+                self.env().emit_event(event);
+            }
+        }
+
+        /// Emits the events of `batch` like `emit_batch` and notifies the
+        /// observers once if the reserves were synced for it.
+        fn emit_batch_and_notify(&self, batch: EventBatch) {
+            let synced = batch.synced;
+            self.emit_batch(batch);
+            if synced {
+                self.notify_observers();
+            }
+        }
+
+        /// Calls `on_sync(reserve0, reserve1)` on every observer and emits
+        /// `HookFailed` for every failed call.
+        fn notify_observers(&self) {
+            for &observer in self.observers.iter() {
+                let result = self.call_on_sync(observer);
+                self.report_hook_result(observer, result);
+            }
+        }
+
+        /// Calls `on_sync(reserve0, reserve1)` on `observer`.
+        #[cfg(not(test))]
+        fn call_on_sync(&self, observer: AccountId) -> ink_env::Result<()> {
+            build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                .callee(observer)
+                .gas_limit(*self.callback_gas_limit)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ON_SYNC_SELECTOR))
+                        .push_arg(&self.reserve0)
+                        .push_arg(&self.reserve1),
+                )
+                .returns::<()>()
+                .fire()
+        }
+
+        /// Contracts cannot be called off-chain, so in the unit tests every
+        /// observer call fails and is counted by its `HookFailed` event.
+        #[cfg(test)]
+        fn call_on_sync(&self, _observer: AccountId) -> ink_env::Result<()> {
+            Err(ink_env::Error::CalleeTrapped)
+        }
+
+        /// Records a skim in the current block.
+        ///
+        /// Returns `RateLimited` without recording it if the last skim was less
+        /// than `skim_interval_blocks` blocks ago.
+        fn ensure_skim_allowed(&mut self) -> Result<()> {
+            let now = self.env().block_number();
+            let interval = *self.skim_interval_blocks;
+            if let Some(last_skim_block) = self.last_skim_block {
+                let next_block = last_skim_block.saturating_add(interval);
+                if now < next_block {
+                    return Err(Error::RateLimited { next_block })
+                }
+            }
+            self.last_skim_block = Some(now);
+            Ok(())
+        }
+
+        /// Panic if the pair has been paused.
+        fn ensure_not_paused(&self) {
+            assert!(!self.paused, "Uniswap: PAUSED");
+        }
+
+        /// Returns `TradingNotStarted` if swaps are not allowed yet.
+        fn ensure_trading_started(&self) -> Result<()> {
+            if let Some(trading_start) = *self.trading_start {
+                let now = self.env().block_timestamp();
+                if now < trading_start {
+                    return Err(Error::TradingNotStarted { trading_start, now })
+                }
+            }
+            Ok(())
+        }
+
+        /// Sets the tokens and the configuration of the pair, see `new`.
+        ///
+        /// The pair can only be initialized once, so a second call cannot reset
+        /// the tokens or the owner's configuration.
+        #[allow(clippy::too_many_arguments)]
+        fn init(
+            &mut self,
+            token0: AccountId,
+            token1: AccountId,
+            chain_id: u64,
+            max_callback_gas: u64,
+            governance_token: AccountId,
+            rebate_threshold: Balance,
+            minimum_liquidity: Balance,
+            trading_start: Option<Timestamp>,
+            legacy_events: bool,
+        ) {
+            assert!(!self.initialized, "Uniswap: ALREADY_INITIALIZED");
+            let bounds = MIN_MINIMUM_LIQUIDITY..=MAX_MINIMUM_LIQUIDITY;
+            assert!(
+                bounds.contains(&minimum_liquidity),
+                "Uniswap: INVALID_MINIMUM_LIQUIDITY"
+            );
+            *self.token0 = FromAccountId::from_account_id(token0);
+            *self.token1 = FromAccountId::from_account_id(token1);
+            *self.chain_id = chain_id;
+            *self.domain_separator = domain_separator_of(
+                DOMAIN_NAME,
+                DOMAIN_VERSION,
+                chain_id,
+                self.env().account_id(),
+            );
+            *self.callback_gas_limit = max_callback_gas;
+            *self.governance_token = governance_token;
+            *self.rebate_threshold = rebate_threshold;
+            *self.minimum_liquidity = minimum_liquidity;
+            *self.trading_start = trading_start;
+            self.legacy_events = legacy_events;
+            self.initialized = true;
+        }
+
+        /// Panic if a call into the pair is already in progress or the pair is
+        /// not initialized.
+        fn lock(&mut self) {
+            assert!(self.initialized, "Uniswap: NOT_INITIALIZED");
+            assert!(!self.locked, "Uniswap: LOCKED");
+            self.locked = true;
+        }
+
+        fn unlock(&mut self) {
+            self.locked = false;
+        }
+
+        fn _burn(&mut self, to: AccountId, value: Balance) {
+            self.accrue_fees(to);
+            let to_balance = self.balance_of(to);
+            self.balances.insert(to, to_balance - value);
+            self.track_holder(to, to_balance, to_balance - value);
+            self.write_checkpoint(Some(to), to_balance - value);
+
+            *self.total_supply -= value;
+            self.write_checkpoint(None, *self.total_supply);
+            self.notify_transfer_hook(Some(to), None, value);
+        }
+
+        fn _mint(&mut self, to: AccountId, value: Balance) {
+            self.accrue_fees(to);
+            let to_balance = self.balance_of(to);
+            self.balances.insert(to, to_balance + value);
+            self.track_holder(to, to_balance, to_balance + value);
+            self.write_checkpoint(Some(to), to_balance + value);
+            self.mint_block.insert(to, self.env().block_number());
+
+            *self.total_supply += value;
+            self.write_checkpoint(None, *self.total_supply);
+            self.notify_transfer_hook(None, Some(to), value);
+        }
+
+        /// Returns `InvalidFeeBps` if `bps` exceeds `math::MAX_FEE_BPS`.
+        fn ensure_valid_fee_bps(bps: u16) -> Result<()> {
+            if Balance::from(bps) > math::MAX_FEE_BPS {
+                return Err(Error::InvalidFeeBps { bps })
+            }
+            Ok(())
+        }
+
+        fn apply_fee_bps(&mut self, bps: u16) {
+            *self.fee_bps = bps;
+            self.env().emit_event(FeeBpsChanged { fee_bps: bps });
+        }
+
+        /// Returns the balance of `owner`, or the total supply for `None`, at
+        /// the start of `block`.
+        fn checkpoint_at(&self, owner: Option<AccountId>, block: BlockNumber) -> Balance {
+            let checkpoint = |index: u32| self.checkpoints[&(owner, index)];
+            // Find the number of checkpoints written before `block`.
+            let mut low = 0;
+            let mut high = self.checkpoint_counts.get(&owner).copied().unwrap_or(0);
+            while low < high {
+                let mid = (low + high) / 2;
+                if checkpoint(mid).0 < block {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            if low == 0 {
+                0
+            } else {
+                checkpoint(low - 1).1
+            }
+        }
+
+        /// Records `balance` as the balance of `owner`, or the total supply for
+        /// `None`, at the end of the current block.
+        fn write_checkpoint(&mut self, owner: Option<AccountId>, balance: Balance) {
+            let block = self.env().block_number();
+            let count = self.checkpoint_counts.get(&owner).copied().unwrap_or(0);
+            if count > 0 {
+                let last = &mut self.checkpoints[&(owner, count - 1)];
+                if last.0 == block {
+                    last.1 = balance;
+                    return
+                }
+            }
+            self.checkpoints.insert((owner, count), (block, balance));
+            self.checkpoint_counts.insert(owner, count + 1);
+        }
+
+        /// Mints the `amount` LP tokens of a flash mint to `receiver`.
+        ///
+        /// Unlike `_mint` this keeps the transfer lock of the receiver, since
+        /// the tokens are burned again before the flash mint returns.
+        fn begin_flash_mint(&mut self, receiver: AccountId, amount: Balance) {
+            let mint_block = self.mint_block.get(&receiver).copied();
+            self._mint(receiver, amount);
+            match mint_block {
+                Some(mint_block) => self.mint_block.insert(receiver, mint_block),
+                None => self.mint_block.take(&receiver),
+            };
+        }
+
+        /// Burns the repayment of a flash mint of `amount` LP tokens and `fee`
+        /// from `receiver`.
+        ///
+        /// # Panics
+        ///
+        /// If `receiver` holds fewer than `amount + fee` LP tokens.
+        fn settle_flash_mint(
+            &mut self,
+            receiver: AccountId,
+            amount: Balance,
+            fee: Balance,
+        ) {
+            assert!(
+                self.balance_of(receiver) >= amount + fee,
+                "Uniswap: FLASH_MINT_NOT_REPAID"
+            );
+            self._burn(receiver, amount + fee);
+        }
+
+        /// Calls `on_lp_transfer(from, to, amount)` on the transfer hook, if
+        /// any, and emits `HookFailed` if the call fails.
+        fn notify_transfer_hook(
+            &self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            amount: Balance,
+        ) {
+            if let Some(hook) = *self.transfer_hook {
+                let result = build_call::<<Self as ::ink_lang::ContractEnv>::Env>()
+                    .callee(hook)
+                    .gas_limit(*self.callback_gas_limit)
+                    .transferred_value(0)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ON_LP_TRANSFER_SELECTOR))
+                            .push_arg(&from)
+                            .push_arg(&to)
+                            .push_arg(&amount),
+                    )
+                    .returns::<()>()
+                    .fire();
+                self.report_hook_result(hook, result);
+            }
+        }
+
+        /// Emits `HookFailed` if the call of the transfer hook or observer `hook`
+        /// returned `result` as an error.
+        ///
+        /// The failure is only reported, the operation that notified `hook` goes
+        /// through regardless.
+        fn report_hook_result(&self, hook: AccountId, result: ink_env::Result<()>) {
+            if result.is_err() {
+                self.env().emit_event(HookFailed { hook });
+            }
+        }
+
+        /// Updates `holder_count` for the balance of `owner` changing from
+        /// `before` to `after`.
+        fn track_holder(&mut self, owner: AccountId, before: Balance, after: Balance) {
+            if owner == self.env().account_id() {
+                return
+            }
+            if before == 0 && after > 0 {
+                self.holder_count += 1;
+            } else if before > 0 && after == 0 {
+                self.holder_count -= 1;
+            }
+        }
+
+        /// Sets the reserves to `balance0` and `balance1`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ReserveOverflow` if a balance exceeds `math::RESERVE_MAX`.
+        ///
+        /// # Panics
+        ///
+        /// If both balances are zero while LP tokens exist.
+        fn update(&mut self, balance0: Balance, balance1: Balance) -> Result<()> {
+            self.ensure_valid_reserves(balance0, balance1);
+            self.emit_sync(balance0, balance1)
+        }
+
+        /// Sets the reserves like `update`, but adds the `Sync` event to `batch`
+        /// and leaves the observers to be notified with it.
+        fn push_update(
+            &mut self,
+            batch: &mut EventBatch,
+            balance0: Balance,
+            balance1: Balance,
+        ) -> Result<()> {
+            self.ensure_valid_reserves(balance0, balance1);
+            self.push_sync(batch, balance0, balance1)
+        }
+
+
+    }
+
+    /// Unit tests.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use crate::mock_erc20;
+        use ink_lang as ink;
+
+        type Event = <Uniswap_pair as ::ink_lang::BaseEvent>::Type;
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .unwrap_or([0x0; 32].into());
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                caller,
+                callee,
+                1000000,
+                1000000,
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])),
+            );
+        }
+
+        fn advance_blocks(blocks: BlockNumber) {
+            for _ in 0..blocks {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+        }
+
+        /// The pair together with the accounts used across the tests.
+        ///
+        /// The token contracts cannot be deployed off-chain, so `token0` and
+        /// `token1` are `MockErc20` tokens at plain accounts. Alice deploys the pair
+        /// and owns it.
+        struct TestEnv {
+            token0: AccountId,
+            token1: AccountId,
+            pair: Uniswap_pair,
+            alice: AccountId,
+            bob: AccountId,
+            charlie: AccountId,
+        }
+
+        /// The constructor arguments the tests vary, see `TestEnv::with_config`.
+        struct PairConfig {
+            minimum_liquidity: Balance,
+            trading_start: Option<Timestamp>,
+            legacy_events: bool,
+        }
+
+        impl Default for PairConfig {
+            fn default() -> Self {
+                Self {
+                    minimum_liquidity: MINIMUM_LIQUIDITY,
+                    trading_start: None,
+                    legacy_events: false,
+                }
+            }
+        }
+
+        impl TestEnv {
+            /// Creates a pair without reserves.
+            fn new() -> Self {
+                Self::with_reserves(0, 0)
+            }
+
+            /// Creates a pair holding `reserve0` and `reserve1`.
+            fn with_reserves(reserve0: Balance, reserve1: Balance) -> Self {
+                let mut env = Self::with_config(PairConfig::default());
+                env.pair.reserve0 = reserve0;
+                env.pair.reserve1 = reserve1;
+                let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get contract id");
+                mock_erc20::set_balance(env.token0, pair_account, reserve0);
+                mock_erc20::set_balance(env.token1, pair_account, reserve1);
+                env
+            }
+
+            /// Creates a pair without reserves from `config`.
+            fn with_config(config: PairConfig) -> Self {
+                let accounts =
+                    ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                        .expect("Cannot get accounts");
+                let pair = Uniswap_pair::new(
+                    accounts.django,
+                    accounts.eve,
+                    1,
+                    CALLBACK_GAS_LIMIT,
+                    accounts.frank,
+                    0,
+                    config.minimum_liquidity,
+                    config.trading_start,
+                    config.legacy_events,
+                );
+                mock_erc20::reset();
+                Self {
+                    token0: accounts.django,
+                    token1: accounts.eve,
+                    pair,
+                    alice: accounts.alice,
+                    bob: accounts.bob,
+                    charlie: accounts.charlie,
+                }
+            }
+        }
+
+        #[ink::test]
+        fn new_stores_tokens() {
+            let TestEnv { pair, token0, token1, .. } = TestEnv::new();
+            assert_eq!(pair.token0.to_account_id(), token0);
+            assert_eq!(pair.token1.to_account_id(), token1);
+        }
+
+        #[ink::test]
+        fn is_pool_token_works() {
+            let TestEnv { pair, token0, token1, alice, .. } = TestEnv::new();
+            assert!(pair.is_pool_token(token0));
+            assert!(pair.is_pool_token(token1));
+            assert!(!pair.is_pool_token(alice));
+        }
+
+        #[ink::test]
+        fn sort_tokens_works() {
+            let TestEnv { alice, bob, .. } = TestEnv::new();
+            assert_eq!(Uniswap_pair::sort_tokens(alice, bob), (alice, bob));
+            assert_eq!(Uniswap_pair::sort_tokens(bob, alice), (alice, bob));
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: IDENTICAL_ADDRESSES")]
+        fn sort_tokens_fails_for_identical_addresses() {
+            let TestEnv { alice, .. } = TestEnv::new();
+            Uniswap_pair::sort_tokens(alice, alice);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: ZERO_ADDRESS")]
+        fn sort_tokens_fails_for_zero_address() {
+            let TestEnv { alice, .. } = TestEnv::new();
+            Uniswap_pair::sort_tokens(alice, AccountId::from([0x0; 32]));
+        }
+
+        #[ink::test]
+        fn domain_separator_matches_reference() {
+            // The domain of the EIP-712 reference implementation.
+            let mut verifying_contract = [0x0; 32];
+            verifying_contract[12..].copy_from_slice(&[0xcc; 20]);
+            assert_eq!(
+                domain_separator_of(b"Ether Mail", b"1", 1, verifying_contract.into()),
+                [
+                    0xf2, 0xce, 0xe3, 0x75, 0xfa, 0x42, 0xb4, 0x21, 0x43, 0x80, 0x40, 0x25,
+                    0xfc, 0x44, 0x9d, 0xea, 0xfd, 0x50, 0xcc, 0x03, 0x1c, 0xa2, 0x57, 0xe0,
+                    0xb1, 0x94, 0xa6, 0x50, 0xa9, 0x12, 0x09, 0x0f,
+                ]
+            );
+        }
+
+        #[test]
+        fn lp_metadata_is_composed_of_symbols() {
+            assert_eq!(
+                lp_metadata_of(Some(String::from("DOT")), Some(String::from("USDT"))),
+                (
+                    String::from("Uniswap V2 DOT/USDT"),
+                    String::from("DOT-USDT-LP")
+                )
+            );
+        }
+
+        #[test]
+        fn lp_metadata_falls_back_for_tokens_without_metadata() {
+            assert_eq!(
+                lp_metadata_of(Some(String::from("DOT")), None),
+                (
+                    String::from("Uniswap V2 DOT/UNKNOWN"),
+                    String::from("DOT-UNKNOWN-LP")
+                )
+            );
+            assert_eq!(lp_metadata_of(None, None).1, "UNKNOWN-UNKNOWN-LP");
+        }
+
+        #[ink::test]
+        fn lp_metadata_is_cached() {
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            assert_eq!(pair.lp_decimals(), 18);
+            // The token symbols are only queried while nothing is cached, so the
+            // tokens are never called here.
+            *pair.lp_metadata = Some(lp_metadata_of(Some(String::from("DOT")), None));
+            assert_eq!(pair.lp_name(), "Uniswap V2 DOT/UNKNOWN");
+            assert_eq!(pair.lp_symbol(), "DOT-UNKNOWN-LP");
+        }
+
+        #[ink::test]
+        fn rebuild_domain_separator_works() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            let mainnet = pair.domain_separator();
+            assert_eq!(pair.chain_id(), 1);
+
+            set_caller(bob);
+            assert_eq!(
+                pair.rebuild_domain_separator(2),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+
+            assert_eq!(pair.rebuild_domain_separator(2), Ok(()));
+            assert_eq!(pair.chain_id(), 2);
+            assert_ne!(pair.domain_separator(), mainnet);
+            assert_eq!(pair.rebuild_domain_separator(1), Ok(()));
+            assert_eq!(pair.domain_separator(), mainnet);
+        }
+
+        #[ink::test]
+        fn new_records_creation() {
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            let TestEnv { pair, .. } = TestEnv::new();
+            assert_eq!(pair.created_at(), 1);
+            assert_eq!(pair.created_timestamp(), 5);
+
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(pair.created_at(), 1);
+            assert_eq!(pair.created_timestamp(), 5);
+        }
+
+        #[ink::test]
+        fn new_initializes_lazy_fields() {
+            let TestEnv { pair, .. } = TestEnv::new();
+            assert_eq!(pair.total_supply(), 0);
+            assert_eq!(pair.imbalance_refund_bps(), 0);
+            assert_eq!(pair.initial_mint_minimum(), 0);
+            assert_eq!(pair.min_swap_amount(), 0);
+            assert_eq!(pair.transfer_lock_blocks(), 0);
+            assert_eq!(pair.chain_id(), 1);
+            assert_ne!(pair.domain_separator(), [0x0; 32]);
+            assert_eq!(pair.callback_gas_limit(), CALLBACK_GAS_LIMIT);
+        }
+
+        #[ink::test]
+        fn new_grants_admin_and_owner_roles() {
+            let TestEnv { pair, alice, bob, .. } = TestEnv::new();
+            assert!(pair.has_role(DEFAULT_ADMIN_ROLE, alice));
+            assert!(pair.has_role(OWNER_ROLE, alice));
+            assert!(!pair.has_role(OWNER_ROLE, bob));
+            assert_eq!(pair.role_admin(OWNER_ROLE), DEFAULT_ADMIN_ROLE);
+        }
+
+        #[ink::test]
+        fn only_role_admin_can_grant_and_revoke() {
+            let TestEnv { mut pair, alice, bob, charlie, .. } = TestEnv::new();
+            assert_eq!(pair.grant_role(GUARDIAN_ROLE, bob), Ok(()));
+            assert!(pair.has_role(GUARDIAN_ROLE, bob));
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+
+            // Holding a role does not allow to grant it further.
+            set_caller(bob);
+            let missing_admin = Err(Error::MissingRole(MissingRole {
+                role: DEFAULT_ADMIN_ROLE,
+                account: bob,
+            }));
+            assert_eq!(pair.grant_role(GUARDIAN_ROLE, charlie), missing_admin);
+            assert_eq!(pair.revoke_role(OWNER_ROLE, alice), missing_admin);
+            assert!(pair.has_role(OWNER_ROLE, alice));
+            ink_env::test::pop_execution_context();
+
+            assert_eq!(pair.revoke_role(GUARDIAN_ROLE, bob), Ok(()));
+            assert!(!pair.has_role(GUARDIAN_ROLE, bob));
+            // Revoking a role that is not held emits no event.
+            assert_eq!(pair.revoke_role(GUARDIAN_ROLE, bob), Ok(()));
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+        }
+
+        #[ink::test]
+        fn lock_liquidity_works() {
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract account");
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+
+            assert_eq!(pair.lock_liquidity(600, 10), Ok(()));
+            assert_eq!(pair.lock_liquidity(100, 15), Ok(()));
+            assert_eq!(pair.balance_of(alice), 300);
+            assert_eq!(pair.balance_of(pair_account), 700);
+            assert_eq!(pair.get_lock(alice), Some((700, 15)));
+            assert_eq!(pair.get_lock(bob), None);
+            assert_eq!(pair.total_supply(), 1_000);
+            assert_eq!(
+                pair.lock_liquidity(100, 10),
+                Err(Error::LockShortened {
+                    current: 15,
+                    requested: 10,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn locked_liquidity_cannot_be_transferred() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.lock_liquidity(1_000, 10), Ok(()));
+            assert_eq!(
+                pair.transfer(bob, 1),
+                Err(Error::InsufficientBalance {
+                    required: 1,
+                    available: 0,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn allowances_are_independent_of_other_entries() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.approve(bob, 300), Ok(()));
+            assert_eq!(pair.allowance(alice, bob), 300);
+            // Each map hashes its keys under its own root key, so neither the
+            // reversed pair nor the balances see the allowance.
+            assert_eq!(pair.allowance(bob, alice), 0);
+            assert_eq!(pair.allowance(alice, alice), 0);
+            assert_eq!(pair.balance_of(alice), 1_000);
+            assert_eq!(pair.balance_of(bob), 0);
+        }
+
+        #[ink::test]
+        fn transfer_from_to_returns_new_balances() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.transfer_from_to(alice, bob, 400), Ok((600, 400)));
+            assert_eq!(pair.transfer_from_to(alice, alice, 100), Ok((600, 600)));
+            assert_eq!(pair.balance_of(alice), 600);
+            assert_eq!(
+                pair.transfer_from_to(alice, bob, 601),
+                Err(Error::InsufficientBalance {
+                    required: 601,
+                    available: 600,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn fee_growth_is_proportional_to_fees() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 2_048);
+            pair.record_swap(10_000, 0, 32, 0);
+            pair.record_swap(20_000, 0, 64, 0);
+            pair.record_swap(0, 30_000, 0, 96);
+            // 96 token0 and 96 token1 shared by 2_048 LP tokens, 3 / 64 each.
+            let growth = (0, 3 << 122);
+            assert_eq!(pair.fee_growth_global(), (growth, growth));
+
+            // Mints and burns change the share of later fees only.
+            pair._mint(bob, 2_048);
+            pair._burn(alice, 1_024);
+            assert_eq!(pair.fee_growth_global(), (growth, growth));
+            // 96 token0 shared by 3_072 LP tokens add 1 / 32.
+            pair.record_swap(30_000, 0, 96, 0);
+            assert_eq!(pair.fee_growth_global(), ((0, 5 << 122), growth));
+        }
+
+        #[ink::test]
+        fn fee_growth_wraps_around_on_overflow() {
+            let TestEnv { mut pair, alice, .. } = TestEnv::new();
+            *pair.fee_growth_global0 = (u128::MAX, u128::MAX);
+            pair._mint(alice, 1);
+            pair.record_swap(1_000, 0, 1, 0);
+            assert_eq!(pair.fee_growth_global(), ((0, u128::MAX), (0, 0)));
+            // The difference to the snapshot taken at the mint is still right.
+            assert_eq!(pair.unclaimed_fees(alice), (1, 0));
+        }
+
+        #[ink::test]
+        fn unclaimed_fees_follow_lp_balances() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 1_024);
+            pair._mint(bob, 3_072);
+            // A fee of 400 token0 is shared by 4_096 LP tokens, 25 / 256 each.
+            pair.record_swap(40_000, 0, 400, 0);
+            assert_eq!(pair.fee_growth_global(), ((0, 25 << 120), (0, 0)));
+            assert_eq!(pair.unclaimed_fees(alice), (100, 0));
+            assert_eq!(pair.unclaimed_fees(bob), (300, 0));
+
+            // Alice keeps her share of the earlier fee after moving her tokens.
+            assert_eq!(pair.transfer_from_to(alice, bob, 1_024), Ok((0, 4_096)));
+            pair.record_swap(0, 80_000, 0, 800);
+            assert_eq!(pair.unclaimed_fees(alice), (100, 0));
+            assert_eq!(pair.unclaimed_fees(bob), (300, 800));
+
+            pair._burn(bob, 4_096);
+            pair.record_swap(0, 80_000, 0, 800);
+            assert_eq!(pair.unclaimed_fees(bob), (300, 800));
+        }
+
+        #[ink::test]
+        fn unclaimed_fees_are_exact_for_large_supplies() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            // Each LP token earns far less than one unit of the fee.
+            pair._mint(alice, 10u128.pow(30));
+            pair._mint(bob, 10u128.pow(30));
+            pair.record_swap(40_000, 0, 2_000_000, 0);
+            assert_ne!(pair.fee_growth_global().0, (0, 0));
+            assert_eq!(pair.unclaimed_fees(alice), (999_999, 0));
+            assert_eq!(pair.unclaimed_fees(bob), (999_999, 0));
+        }
+
+        #[ink::test]
+        fn holder_count_works() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract account");
+            pair._mint(pair_account, MINIMUM_LIQUIDITY);
+            assert_eq!(pair.holder_count(), 0);
+            // Minting to a new holder.
+            pair._mint(alice, 1_000);
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.holder_count(), 1);
+            assert_eq!(pair.transfer_from_to(alice, bob, 500), Ok((1_500, 500)));
+            assert_eq!(pair.holder_count(), 2);
+            assert!(pair.transfer_from_to(bob, bob, 500).is_ok());
+            assert_eq!(pair.holder_count(), 2);
+            // Transferring everything out.
+            assert_eq!(pair.transfer_from_to(bob, alice, 500), Ok((0, 2_000)));
+            assert_eq!(pair.holder_count(), 1);
+            // Sending to the pair for a burn and burning to zero.
+            assert_eq!(
+                pair.transfer_from_to(alice, pair_account, 2_000),
+                Ok((0, 3_000))
+            );
+            assert_eq!(pair.holder_count(), 0);
+            pair._mint(bob, 100);
+            pair._burn(bob, 100);
+            pair._burn(pair_account, 2_000);
+            assert_eq!(pair.holder_count(), 0);
+            assert_eq!(pair.balance_of(bob), 0);
+        }
+
+        #[ink::test]
+        fn transfer_cannot_overflow_recipient() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 1);
+            // Only reachable if the total supply is not bounded by the balances.
+            pair.balances.insert(bob, Balance::MAX);
+            assert_eq!(pair.transfer(bob, 1), Err(Error::BalanceOverflow));
+            assert_eq!(pair.balance_of(alice), 1);
+            assert_eq!(pair.balance_of(bob), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn fresh_liquidity_cannot_be_transferred() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            assert_eq!(pair.set_transfer_lock_blocks(2), Ok(()));
+            pair._mint(alice, 1_000);
+            assert_eq!(
+                pair.transfer(bob, 100),
+                Err(Error::TransferLocked { unlock_block: 2 })
+            );
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(
+                pair.transfer(bob, 100),
+                Err(Error::TransferLocked { unlock_block: 2 })
+            );
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(pair.transfer(bob, 100), Ok(()));
+            assert_eq!(pair.balance_of(bob), 100);
+        }
+
+        #[ink::test]
+        fn minting_again_renews_the_transfer_lock() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            assert_eq!(pair.set_transfer_lock_blocks(2), Ok(()));
+            pair._mint(alice, 1_000);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            pair._mint(alice, 1_000);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(
+                pair.transfer(bob, 100),
+                Err(Error::TransferLocked { unlock_block: 3 })
+            );
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(pair.transfer(bob, 100), Ok(()));
+        }
+
+        #[ink::test]
+        fn only_owner_can_set_transfer_lock_blocks() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            set_caller(bob);
+            assert_eq!(
+                pair.set_transfer_lock_blocks(2),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+            assert_eq!(pair.transfer_lock_blocks(), 0);
+        }
+
+        #[ink::test]
+        fn extend_lock_works() {
+            let TestEnv { mut pair, alice, .. } = TestEnv::new();
+            assert_eq!(pair.extend_lock(20), Err(Error::NoLockedLiquidity));
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.lock_liquidity(1_000, 10), Ok(()));
+            assert_eq!(
+                pair.extend_lock(5),
+                Err(Error::LockShortened {
+                    current: 10,
+                    requested: 5,
+                })
+            );
+            assert_eq!(pair.extend_lock(20), Ok(()));
+            assert_eq!(pair.get_lock(alice), Some((1_000, 20)));
+
+            // The original end of the lock has passed.
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(
+                pair.withdraw_locked(),
+                Err(Error::LiquidityStillLocked {
+                    unlock_timestamp: 20,
+                    now: 10,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn withdraw_locked_fails_before_unlock() {
+            let TestEnv { mut pair, alice, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.lock_liquidity(1_000, 10), Ok(()));
+
+            // One block of 5 time units is not enough.
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(
+                pair.withdraw_locked(),
+                Err(Error::LiquidityStillLocked {
+                    unlock_timestamp: 10,
+                    now: 5,
+                })
+            );
+            assert_eq!(pair.balance_of(alice), 0);
+            assert_eq!(pair.get_lock(alice), Some((1_000, 10)));
+        }
+
+        #[ink::test]
+        fn withdraw_locked_works_after_unlock() {
+            let TestEnv { mut pair, alice, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.lock_liquidity(1_000, 10), Ok(()));
+
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(pair.withdraw_locked(), Ok(1_000));
+            assert_eq!(pair.balance_of(alice), 1_000);
+            assert_eq!(pair.get_lock(alice), None);
+            // Nothing is left to withdraw.
+            assert_eq!(pair.withdraw_locked(), Err(Error::NoLockedLiquidity));
+        }
+
+        #[ink::test]
+        fn status_works() {
+            let TestEnv { mut pair, alice, .. } = TestEnv::new();
+            let mut expected = PoolStatus {
+                initialized: true,
+                paused: false,
+                locked: false,
+                has_liquidity: false,
+            };
+            assert_eq!(pair.status(), expected);
+
+            pair._mint(alice, 1_000);
+            expected.has_liquidity = true;
+            assert_eq!(pair.status(), expected);
+
+            assert_eq!(pair.grant_role(GUARDIAN_ROLE, alice), Ok(()));
+            assert_eq!(pair.pause(), Ok(()));
+            expected.paused = true;
+            assert_eq!(pair.status(), expected);
+
+            pair.lock();
+            expected.locked = true;
+            assert_eq!(pair.status(), expected);
+
+            pair.unlock();
+            assert_eq!(pair.unpause(), Ok(()));
+            expected.locked = false;
+            expected.paused = false;
+            assert_eq!(pair.status(), expected);
+        }
+
+        #[ink::test]
+        fn default_pair_is_initialized_once() {
+            let TestEnv { token0, token1, .. } = TestEnv::new();
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let mut pair = Uniswap_pair::default();
+            assert!(!pair.status().initialized);
+            assert_eq!(pair.total_supply(), 0);
+
+            assert_eq!(
+                pair.initialize(
+                    token0,
+                    token1,
+                    1,
+                    CALLBACK_GAS_LIMIT,
+                    accounts.frank,
+                    0,
+                    MIN_MINIMUM_LIQUIDITY,
+                    None,
+                    false,
+                ),
+                Ok(())
+            );
+            assert!(pair.status().initialized);
+            assert_eq!(pair.token0.to_account_id(), token0);
+            assert_eq!(pair.token1.to_account_id(), token1);
+            assert_eq!(pair.callback_gas_limit(), CALLBACK_GAS_LIMIT);
+            assert_eq!(pair.minimum_liquidity(), MIN_MINIMUM_LIQUIDITY);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: ALREADY_INITIALIZED")]
+        fn initialize_fails_for_initialized_pair() {
+            let TestEnv { mut pair, token0, token1, bob, .. } = TestEnv::new();
+            // Reserves and tokens cannot be reset by initializing again.
+            pair.initialize(bob, token1, 1, 0, token0, 0, MINIMUM_LIQUIDITY, None, false)
+                .expect("Alice owns the pair");
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: NOT_INITIALIZED")]
+        fn lock_fails_before_initialize() {
+            let mut pair = Uniswap_pair::default();
+            pair.lock();
+        }
+
+        /// Returns a pair that allows swaps from `trading_start`.
+        fn pair_trading_from(trading_start: Timestamp) -> Uniswap_pair {
+            TestEnv::with_config(PairConfig {
+                trading_start: Some(trading_start),
+                ..PairConfig::default()
+            })
+            .pair
+        }
+
+        #[ink::test]
+        fn mint_works_before_trading_start() {
+            let TestEnv { alice, .. } = TestEnv::new();
+            let mut pair = pair_trading_from(100);
+            assert_eq!(pair.trading_start(), Some(100));
+            assert_eq!(
+                pair.mint_deposit(alice, alice, 4_000, 1_000_000),
+                Ok(63_245 - MINIMUM_LIQUIDITY)
+            );
+            assert_eq!((pair.reserve0, pair.reserve1), (4_000, 1_000_000));
+        }
+
+        #[ink::test]
+        fn swap_fails_before_trading_start() {
+            let TestEnv { alice, bob, .. } = TestEnv::new();
+            let mut pair = pair_trading_from(100);
+            pair.mint_deposit(alice, alice, 4_000, 1_000_000).unwrap();
+            assert_eq!(
+                pair.swap_exact_in(1_000, true, 0, bob),
+                Err(Error::TradingNotStarted {
+                    trading_start: 100,
+                    now: 0,
+                })
+            );
+            // 20 blocks of 5 time units reach the start.
+            advance_blocks(19);
+            assert_eq!(
+                pair.ensure_trading_started(),
+                Err(Error::TradingNotStarted {
+                    trading_start: 100,
+                    now: 95,
+                })
+            );
+            advance_blocks(1);
+            assert_eq!(pair.ensure_trading_started(), Ok(()));
+        }
+
+        #[ink::test]
+        fn trading_start_cannot_be_postponed() {
+            let mut pair = pair_trading_from(100);
+            assert_eq!(
+                pair.set_trading_start(101),
+                Err(Error::TradingStartPostponed {
+                    current: 100,
+                    requested: 101,
+                })
+            );
+            assert_eq!(pair.set_trading_start(50), Ok(()));
+            assert_eq!(pair.trading_start(), Some(50));
+
+            // A pair that always allowed swaps cannot start restricting them.
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            assert_eq!(pair.trading_start(), None);
+            assert_eq!(
+                pair.set_trading_start(100),
+                Err(Error::TradingStartPostponed {
+                    current: 0,
+                    requested: 100,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn emergency_withdraw_works_while_paused() {
+            let TestEnv { mut pair, alice, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair._mint(alice, 1_000);
+            pair._mint(bob, 1_000);
+            assert_eq!(pair.grant_role(GUARDIAN_ROLE, alice), Ok(()));
+            assert_eq!(pair.pause(), Ok(()));
+
+            assert_eq!(pair.emergency_burn(bob), Ok((500_000, 1_000_000)));
+            assert_eq!(pair.balance_of(bob), 0);
+            assert_eq!(pair.total_supply(), 1_000);
+            assert_eq!((pair.reserve0, pair.reserve1), (500_000, 1_000_000));
+            // Sync and EmergencyWithdraw follow the role grant and the pause.
+            assert_eq!(ink_env::test::recorded_events().count(), 4);
+            // Bob has nothing left to withdraw.
+            assert_eq!(pair.emergency_burn(bob), Err(Error::NoLiquidity));
+
+            // The last LP takes the remaining reserves.
+            assert_eq!(pair.emergency_burn(alice), Ok((500_000, 1_000_000)));
+            assert_eq!((pair.reserve0, pair.reserve1), (0, 0));
+            assert_eq!(pair.total_supply(), 0);
+        }
+
+        #[ink::test]
+        fn mint_for_position_records_positions() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            assert_eq!(pair.next_position_id(), 0);
+            assert_eq!(pair.mint_for_position(alice, alice, 4_000, 1_000_000), Ok(0));
+            assert_eq!(pair.mint_for_position(alice, bob, 2_000, 500_000), Ok(1));
+            assert_eq!(pair.next_position_id(), 2);
+
+            assert_eq!(
+                pair.position_of(0),
+                Some(LiquidityPosition {
+                    owner: alice,
+                    liquidity: 63_245 - MINIMUM_LIQUIDITY,
+                    token0_deposited: 4_000,
+                    token1_deposited: 1_000_000,
+                })
+            );
+            // Half of the first deposit mints half of the total supply of 63_245.
+            assert_eq!(
+                pair.position_of(1),
+                Some(LiquidityPosition {
+                    owner: bob,
+                    liquidity: 31_622,
+                    token0_deposited: 2_000,
+                    token1_deposited: 500_000,
+                })
+            );
+            assert_eq!(pair.position_of(2), None);
+            // The LP tokens stay fungible.
+            assert_eq!(pair.balance_of(bob), 31_622);
+            assert_eq!((pair.reserve0, pair.reserve1), (6_000, 1_500_000));
+        }
+
+        /// Returns the names of the recorded `Sync`, `Mint` and `Burn` events in
+        /// the order they were emitted.
+        fn recorded_event_names() -> Vec<&'static str> {
+            ink_env::test::recorded_events()
+                .map(|event| {
+                    let decoded = <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer");
+                    match decoded {
+                        Event::Sync(_) => "Sync",
+                        Event::Mint(_) => "Mint",
+                        Event::Burn(_) => "Burn",
+                        _ => "Other",
+                    }
+                })
+                .collect()
+        }
+
+        #[ink::test]
+        fn events_of_an_operation_are_emitted_in_order() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair.mint_deposit(alice, alice, 1_000_000, 2_000_000).unwrap();
+            pair.burn_for_amounts(alice, 1_000, 2_000, bob, 0).unwrap();
+            assert_eq!(recorded_event_names(), ["Sync", "Mint", "Sync", "Burn"]);
+        }
+
+        #[ink::test]
+        fn legacy_pairs_emit_both_swap_events() {
+            let TestEnv { pair, alice, bob, .. } = TestEnv::with_config(PairConfig {
+                legacy_events: true,
+                ..PairConfig::default()
+            });
+            assert!(pair.legacy_events());
+            let mut batch = EventBatch::default();
+            pair.push_swap(
+                &mut batch,
+                Swap {
+                    sender: alice,
+                    amount0In: 1_000,
+                    amount1In: 0,
+                    amount0Out: 0,
+                    amount1Out: 1_992,
+                    to: bob,
+                },
+            );
+            pair.emit_batch(batch);
+
+            let events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            let decode = |event: &ink_env::test::EmittedEvent| {
+                <Event as scale::Decode>::decode(&mut &event.data[..])
+                    .expect("encountered invalid contract event data buffer")
+            };
+            match decode(&events[0]) {
+                Event::Swap(swap) => {
+                    assert_eq!((swap.sender, swap.to), (alice, bob));
+                    assert_eq!((swap.amount0In, swap.amount1Out), (1_000, 1_992));
+                }
+                _ => panic!("encountered unexpected event kind"),
+            }
+            match decode(&events[1]) {
+                Event::SwapLegacy(legacy) => {
+                    let parties = (legacy.sender, legacy.to);
+                    assert_eq!(parties, (alice, bob));
+                    assert_eq!((legacy.amount0In, legacy.amount1Out), (1_000, 1_992));
+                }
+                _ => panic!("encountered unexpected event kind"),
+            }
+            // The legacy layout puts the recipient after the sender.
+            let encoded = scale::Encode::encode(&(alice, bob));
+            assert_eq!(events[1].data[1..65], encoded[..]);
+        }
+
+        #[ink::test]
+        fn add_liquidity_transfers_only_the_used_amounts() {
+            let TestEnv { mut pair, token0, token1, alice, bob, .. } = TestEnv::new();
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+            mock_erc20::set_balance(token0, pair_account, 1_000_000);
+            mock_erc20::set_balance(token1, pair_account, 2_000_000);
+            pair.mint_deposit(alice, alice, 1_000_000, 2_000_000).unwrap();
+            mock_erc20::set_balance(token0, alice, 10_000);
+            mock_erc20::set_balance(token1, alice, 30_000);
+
+            // 10_000 token0 only take 20_000 of the 30_000 token1 offered, the
+            // remaining 10_000 token1 are never transferred.
+            assert_eq!(pair.add_liquidity(bob, 10_000, 30_000, 0, 0), Ok(14_142));
+            assert_eq!(mock_erc20::balance(token0, alice), 0);
+            assert_eq!(mock_erc20::balance(token1, alice), 10_000);
+            assert_eq!(mock_erc20::balance(token0, pair_account), 1_010_000);
+            assert_eq!(mock_erc20::balance(token1, pair_account), 2_020_000);
+            assert_eq!((pair.reserve0, pair.reserve1), (1_010_000, 2_020_000));
+            assert_eq!(pair.balance_of(bob), 14_142);
+
+            assert_eq!(
+                pair.add_liquidity(bob, 10_000, 30_000, 0, 25_000),
+                Err(Error::InsufficientAmount {
+                    amount: 20_000,
+                    min: 25_000,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn burn_for_amounts_burns_the_fewest_lp_tokens() {
+            let TestEnv { mut pair, alice, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair._mint(alice, 1_000);
+            // 1_500 token1 need 0.75 LP tokens, rounded up to 1, which pays
+            // out 1_000 token0 and 2_000 token1.
+            assert_eq!(
+                pair.burn_for_amounts(alice, 0, 1_500, bob, 0),
+                Ok((1, 1_000, 2_000))
+            );
+            assert_eq!(pair.balance_of(alice), 999);
+            assert_eq!((pair.reserve0, pair.reserve1), (999_000, 1_998_000));
+
+            assert_eq!(
+                pair.burn_for_amounts(alice, 10_000, 19_980, bob, 0),
+                Ok((10, 10_000, 20_000))
+            );
+            assert_eq!(pair.balance_of(alice), 989);
+        }
+
+        #[ink::test]
+        fn burn_exact_output_validates_request() {
+            let TestEnv { mut pair, alice, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(
+                pair.burn_exact_output(1_000, 0, bob, 0),
+                Err(Error::EmptyPool)
+            );
+            pair._mint(alice, 1_000);
+            assert_eq!(
+                pair.burn_exact_output(1_000_001, 0, bob, 0),
+                Err(Error::InsufficientBalance {
+                    required: 1_001,
+                    available: 1_000,
+                })
+            );
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(
+                pair.burn_exact_output(1_000, 0, bob, 0),
+                Err(Error::Expired {
+                    deadline: 0,
+                    now: 5,
+                })
+            );
+            assert_eq!(pair.balance_of(alice), 1_000);
+            assert!(!pair.status().locked);
+        }
+
+        #[ink::test]
+        fn emergency_withdraw_fails_while_not_paused() {
+            let TestEnv { mut pair, alice, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.emergency_withdraw(), Err(Error::NotPaused));
+            assert_eq!(pair.balance_of(alice), 1_000);
+            assert!(!pair.status().locked);
+        }
+
+        #[ink::test]
+        fn only_guardian_can_pause() {
+            let TestEnv { mut pair, alice, .. } = TestEnv::new();
+            let missing_guardian = Err(Error::MissingRole(MissingRole {
+                role: GUARDIAN_ROLE,
+                account: alice,
+            }));
+            assert_eq!(pair.pause(), missing_guardian);
+            assert_eq!(pair.unpause(), missing_guardian);
+            assert!(!pair.status().paused);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: LOCKED")]
+        fn reentrant_calls_fail() {
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            pair.lock();
+            pair.lock();
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: auth mismatch")]
+        fn skim_fails_for_non_owner() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            set_caller(bob);
+            let _ = pair.skim(bob);
+        }
+
+        #[ink::test]
+        fn skim_is_rate_limited() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            // Without a limit every skim is allowed.
+            assert_eq!(pair.ensure_skim_allowed(), Ok(()));
+            assert_eq!(pair.ensure_skim_allowed(), Ok(()));
+
+            assert_eq!(pair.set_skim_interval_blocks(3), Ok(()));
+            assert_eq!(pair.skim_interval_blocks(), 3);
+            advance_blocks(2);
+            assert_eq!(
+                pair.ensure_skim_allowed(),
+                Err(Error::RateLimited { next_block: 3 })
+            );
+            advance_blocks(1);
+            assert_eq!(pair.ensure_skim_allowed(), Ok(()));
+            assert_eq!(
+                pair.ensure_skim_allowed(),
+                Err(Error::RateLimited { next_block: 6 })
+            );
+
+            set_caller(bob);
+            assert_eq!(
+                pair.set_skim_interval_blocks(0),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: auth mismatch")]
+        fn sync_fails_for_non_owner() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            set_caller(bob);
+            let _ = pair.sync();
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: auth mismatch")]
+        fn burn_fails_for_other_account() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            // Bob cannot burn the liquidity of Alice.
+            set_caller(bob);
+            let _ = pair.burn(alice);
+        }
+
+        #[ink::test]
+        fn handle_rebase_ignores_balances_above_reserves() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert!(!pair.auto_sync());
+            let mut batch = EventBatch::default();
+            assert_eq!(pair.handle_rebase(&mut batch, 1_000_000, 2_000_001), Ok(()));
+            pair.emit_batch_and_notify(batch);
+            assert_eq!((pair.reserve0, pair.reserve1), (1_000_000, 2_000_000));
+            assert_eq!(ink_env::test::recorded_events().count(), 0);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: REBASE_DETECTED")]
+        fn handle_rebase_fails_after_rebase_down() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            // token1 rebased down by 1% since the reserves were updated.
+            let _ = pair.handle_rebase(&mut EventBatch::default(), 1_000_000, 1_980_000);
+        }
+
+        #[ink::test]
+        fn handle_rebase_syncs_with_auto_sync() {
+            let TestEnv { mut pair, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.set_auto_sync(true), Ok(()));
+            let mut batch = EventBatch::default();
+            assert_eq!(pair.handle_rebase(&mut batch, 1_000_000, 1_980_000), Ok(()));
+            assert_eq!((pair.reserve0, pair.reserve1), (1_000_000, 1_980_000));
+            pair.emit_batch_and_notify(batch);
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+            // The swap is now priced against the reserves the pair holds.
+            assert_eq!(
+                pair.quote_exact_in(1_000, true, 0),
+                (1_972, 1_001_000, 1_978_028)
+            );
+
+            set_caller(bob);
+            assert_eq!(
+                pair.set_auto_sync(false),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn latest_round_data_works() {
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            assert_eq!(pair.latest_round_data(), Err(Error::EmptyPool));
+
+            advance_blocks(2);
+            assert_eq!(pair.emit_sync(1_000_000, 2_500_000), Ok(()));
+            assert_eq!(pair.latest_round_data(), Ok((1, 250_000_000, 10, 10, 1)));
+
+            advance_blocks(1);
+            assert_eq!(pair.emit_sync(1_000_000, 2_000_000), Ok(()));
+            assert_eq!(pair.latest_round_data(), Ok((2, 200_000_000, 15, 15, 2)));
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: INVALID_RESERVES")]
+        fn sync_fails_for_drained_pool() {
+            let TestEnv { mut pair, alice, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.set_auto_sync(true), Ok(()));
+            // Both tokens were drained outside of the pair's accounting.
+            let _ = pair.handle_rebase(&mut EventBatch::default(), 0, 0);
+        }
+
+        #[ink::test]
+        fn sync_to_zero_works_for_empty_pool() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.set_auto_sync(true), Ok(()));
+            assert_eq!(pair.handle_rebase(&mut EventBatch::default(), 0, 0), Ok(()));
+            assert_eq!((pair.reserve0, pair.reserve1), (0, 0));
+        }
+
+        #[ink::test]
+        fn update_fails_above_reserve_max() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(
+                pair.update(math::RESERVE_MAX + 1, 2_000_000),
+                Err(Error::ReserveOverflow)
+            );
+            assert_eq!((pair.reserve0, pair.reserve1), (1_000_000, 2_000_000));
+            assert_eq!(ink_env::test::recorded_events().count(), 0);
+            assert_eq!(pair.update(1_000_000, math::RESERVE_MAX), Ok(()));
+            assert_eq!((pair.reserve0, pair.reserve1), (1_000_000, math::RESERVE_MAX));
+        }
+
+        #[ink::test]
+        fn mint_fails_above_reserve_max() {
+            let TestEnv { mut pair, alice, .. } =
+                TestEnv::with_reserves(math::RESERVE_MAX - 10, math::RESERVE_MAX - 10);
+            pair._mint(alice, 1 << 27);
+            assert_eq!(
+                pair.mint_deposit(alice, alice, 1 << 100, 1 << 100),
+                Err(Error::ReserveOverflow)
+            );
+            // Neither the LP tokens nor the reserves changed.
+            assert_eq!(pair.total_supply(), 1 << 27);
+            assert_eq!(
+                (pair.reserve0, pair.reserve1),
+                (math::RESERVE_MAX - 10, math::RESERVE_MAX - 10)
+            );
+        }
+
+        #[ink::test]
+        fn burn_fails_above_reserve_max_before_paying_out() {
+            let TestEnv { mut pair, token0, token1, alice, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair._mint(alice, 1_000_000);
+            pair._mint(bob, 10);
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+            // A donation leaves more token0 than a reserve can hold after the burn.
+            mock_erc20::set_balance(token0, pair_account, 2 * math::RESERVE_MAX);
+            set_caller(bob);
+            assert_eq!(pair.burn(bob), Err(Error::ReserveOverflow));
+            ink_env::test::pop_execution_context();
+            // The LP tokens were not burned and nothing was paid out.
+            assert_eq!(pair.balance_of(bob), 10);
+            assert_eq!(pair.total_supply(), 1_000_010);
+            assert_eq!(mock_erc20::balance(token0, bob), 0);
+            assert_eq!(mock_erc20::balance(token1, bob), 0);
+            assert_eq!(
+                mock_erc20::balance(token0, pair_account),
+                2 * math::RESERVE_MAX
+            );
+            assert_eq!((pair.reserve0, pair.reserve1), (1_000_000, 2_000_000));
+        }
+
+        #[ink::test]
+        fn swap_exact_in_fails_above_reserve_max_before_transfer() {
+            let TestEnv { mut pair, token0, token1, bob, .. } =
+                TestEnv::with_reserves(math::RESERVE_MAX - 1_000, 2_000_000);
+            mock_erc20::set_balance(token0, bob, 2_000);
+            set_caller(bob);
+            assert_eq!(
+                pair.swap_exact_in(2_000, true, 0, bob),
+                Err(Error::ReserveOverflow)
+            );
+            ink_env::test::pop_execution_context();
+            // The input was not taken from Bob.
+            assert_eq!(mock_erc20::balance(token0, bob), 2_000);
+            assert_eq!(mock_erc20::balance(token1, bob), 0);
+            assert_eq!(
+                (pair.reserve0, pair.reserve1),
+                (math::RESERVE_MAX - 1_000, 2_000_000)
+            );
+        }
+
+        #[ink::test]
+        fn quote_exact_in_zero_for_one_works() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(
+                pair.quote_exact_in(1_000, true, 1_992),
+                (1_992, 1_001_000, 1_998_008)
+            );
+        }
+
+        #[ink::test]
+        fn quote_exact_in_one_for_zero_works() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(
+                pair.quote_exact_in(1_000, false, 0),
+                (498, 999_502, 2_001_000)
+            );
+        }
+
+        #[cfg(feature = "debug")]
+        #[ink::test]
+        fn debug_swap_state_works() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(
+                pair.debug_swap_state(1_000, true),
+                SwapDebugState {
+                    reserve_in: 1_000_000,
+                    reserve_out: 2_000_000,
+                    fee_bps: 30,
+                    fee: 3,
+                    amount_out: 1_992,
+                    reserve0: 1_001_000,
+                    reserve1: 1_998_008,
+                    price_impact_bps: 19,
+                }
+            );
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: INSUFFICIENT_OUTPUT_AMOUNT")]
+        fn quote_exact_in_fails_below_min_output() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair.quote_exact_in(1_000, true, 1_993);
+        }
+
+        #[ink::test]
+        fn quote_exact_in_keeps_k_for_fee_on_transfer_tokens() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            // The token takes 1% of the 100_000 sent, so the pair receives 99_000.
+            let (amount_out, reserve0, reserve1) = pair.quote_exact_in(99_000, true, 0);
+            assert_eq!(reserve0, 1_099_000);
+            assert_eq!(reserve1, 2_000_000 - amount_out);
+            assert!(
+                (reserve0 * 1000 - 99_000 * 3) * reserve1 * 1000
+                    >= 1_000_000 * 2_000_000 * 1000 * 1000
+            );
+            // Quoting the stated amount would pay out more than the pair can afford.
+            let (stated_out, _, _) = pair.quote_exact_in(100_000, true, 0);
+            assert!(stated_out > amount_out);
+            assert!(
+                (reserve0 * 1000 - 99_000 * 3) * (2_000_000 - stated_out) * 1000
+                    < 1_000_000 * 2_000_000 * 1000 * 1000
+            );
+        }
+
+        #[ink::test]
+        fn swaps_at_min_swap_amount_work() {
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            pair.ensure_not_dust(0);
+            assert_eq!(pair.set_min_swap_amount(1_000), Ok(()));
+            assert_eq!(pair.min_swap_amount(), 1_000);
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+            pair.ensure_not_dust(1_000);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: DUST_AMOUNT")]
+        fn swaps_below_min_swap_amount_fail() {
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            assert_eq!(pair.set_min_swap_amount(1_000), Ok(()));
+            pair.ensure_not_dust(999);
+        }
+
+        #[ink::test]
+        fn only_owner_can_set_min_swap_amount() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            set_caller(bob);
+            assert_eq!(
+                pair.set_min_swap_amount(1_000),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+            assert_eq!(pair.min_swap_amount(), 0);
+        }
+
+        #[ink::test]
+        fn imbalance_refund_rewards_rebalancing() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.set_imbalance_refund_bps(50), Ok(()));
+            // Selling token0 moves the reserves towards 1:1.
+            let (_, reserve0, reserve1) = pair.quote_exact_in(100_000, true, 0);
+            assert_eq!(pair.imbalance_refund(100_000, reserve0, reserve1), 150);
+            // Selling token1 skews them further.
+            let (_, reserve0, reserve1) = pair.quote_exact_in(100_000, false, 0);
+            assert_eq!(pair.imbalance_refund(100_000, reserve0, reserve1), 0);
+        }
+
+        #[ink::test]
+        fn imbalance_refund_is_disabled_by_default() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.imbalance_refund_bps(), 0);
+            let (_, reserve0, reserve1) = pair.quote_exact_in(100_000, true, 0);
+            assert_eq!(pair.imbalance_refund(100_000, reserve0, reserve1), 0);
+        }
+
+        /// Returns the recipients and amounts of the recorded `ImbalanceRefund`
+        /// events.
+        fn recorded_refunds() -> Vec<(AccountId, Balance)> {
+            ink_env::test::recorded_events()
+                .filter_map(|event| {
+                    let decoded = <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer");
+                    match decoded {
+                        Event::ImbalanceRefund(ImbalanceRefund { to, amount }) => {
+                            Some((to, amount))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect()
+        }
+
+        #[ink::test]
+        fn swap_exact_in_pays_imbalance_refund() {
+            let TestEnv { mut pair, token0, token1, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.set_imbalance_refund_bps(50), Ok(()));
+            let (amount_out, _, _) = pair.quote_exact_in(100_000, true, 0);
+            mock_erc20::set_balance(token0, bob, 100_000);
+            set_caller(bob);
+            assert_eq!(pair.swap_exact_in(100_000, true, 0, bob), Ok(amount_out));
+            ink_env::test::pop_execution_context();
+            // Half of the fee of 300 went back to Bob.
+            assert_eq!(mock_erc20::balance(token0, bob), 150);
+            assert_eq!(mock_erc20::balance(token1, bob), amount_out);
+            assert_eq!(recorded_refunds(), vec![(bob, 150)]);
+            assert_eq!(pair.reserve0, 1_100_000 - 150);
+            assert_eq!(pair.fees_collected0, 150);
+        }
+
+        #[ink::test]
+        fn swap_pays_imbalance_refund() {
+            let TestEnv { mut pair, token0, token1, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.set_imbalance_refund_bps(50), Ok(()));
+            let (amount_out, _, _) = pair.quote_exact_in(100_000, true, 0);
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+            // Bob sent the input to the pair beforehand.
+            mock_erc20::set_balance(token0, pair_account, 1_100_000);
+            set_caller(bob);
+            assert_eq!(pair.swap(0, amount_out, bob), Ok(()));
+            ink_env::test::pop_execution_context();
+            assert_eq!(mock_erc20::balance(token0, bob), 150);
+            assert_eq!(mock_erc20::balance(token1, bob), amount_out);
+            assert_eq!(recorded_refunds(), vec![(bob, 150)]);
+            assert_eq!(
+                (pair.reserve0, pair.reserve1),
+                (1_100_000 - 150, 2_000_000 - amount_out)
+            );
+            assert_eq!(pair.fees_collected0, 150);
+        }
+
+        #[ink::test]
+        fn holder_rebate_refunds_part_of_the_fee() {
+            let TestEnv { mut pair, alice, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            assert_eq!(pair.governance_token(), accounts.frank);
+            // Disabled rebates do not call the governance token.
+            assert_eq!(pair.rebate_threshold(), 0);
+            assert_eq!(pair.holder_rebate(alice, 300, 0, 1_000, 1_000), (0, 0));
+
+            *pair.rebate_threshold = 100;
+            // A holder of 100 governance tokens pays 20% less fee than a
+            // swapper holding 99.
+            let fee = pair.fee_of(100_000);
+            assert_eq!(fee, 300);
+            assert_eq!(pair.rebate_for(fee, 100), 60);
+            assert_eq!(pair.rebate_for(fee, 99), 0);
+            // The rebate is taken on the full fee, but never exceeds the fee
+            // left after the imbalance refund.
+            mock_erc20::set_balance(accounts.frank, alice, 100);
+            assert_eq!(pair.holder_rebate(alice, fee, 150, 1_000, 2_000), (60, 120));
+            assert_eq!(pair.holder_rebate(alice, fee, 270, 1_000, 2_000), (30, 60));
+        }
+
+        /// Returns the recipients and amounts of the recorded `HolderRebate`
+        /// events.
+        fn recorded_rebates() -> Vec<(AccountId, Balance)> {
+            ink_env::test::recorded_events()
+                .filter_map(|event| {
+                    let decoded = <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer");
+                    match decoded {
+                        Event::HolderRebate(HolderRebate { to, amount }) => {
+                            Some((to, amount))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect()
+        }
+
+        /// Resets the reserves and the pair balances of `env` to 1 000 000 token0
+        /// and 2 000 000 token1.
+        fn reset_reserves(env: &mut TestEnv) {
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+            env.pair.reserve0 = 1_000_000;
+            env.pair.reserve1 = 2_000_000;
+            mock_erc20::set_balance(env.token0, pair_account, 1_000_000);
+            mock_erc20::set_balance(env.token1, pair_account, 2_000_000);
+        }
+
+        #[ink::test]
+        fn swap_exact_in_adds_holder_rebate_to_the_output() {
+            let mut env = TestEnv::with_reserves(1_000_000, 2_000_000);
+            let (token0, token1, bob, charlie) =
+                (env.token0, env.token1, env.bob, env.charlie);
+            *env.pair.rebate_threshold = 100;
+            let governance_token = env.pair.governance_token();
+            mock_erc20::set_balance(governance_token, bob, 100);
+            mock_erc20::set_balance(governance_token, charlie, 99);
+            let (amount_out, reserve0, reserve1) =
+                env.pair.quote_exact_in(100_000, true, 0);
+            // 20% of the fee of 300 token0, paid in token1 at the price after
+            // the swap.
+            let rebate = 60 * reserve1 / reserve0;
+            assert!(rebate > 0);
+            // Bob holds enough governance tokens, Charlie does not.
+            for &swapper in [bob, charlie].iter() {
+                reset_reserves(&mut env);
+                mock_erc20::set_balance(token0, swapper, 100_000);
+                set_caller(swapper);
+                assert!(env.pair.swap_exact_in(100_000, true, 0, swapper).is_ok());
+                ink_env::test::pop_execution_context();
+            }
+            let bob_out = mock_erc20::balance(token1, bob);
+            let charlie_out = mock_erc20::balance(token1, charlie);
+            assert_eq!(charlie_out, amount_out);
+            assert_eq!(bob_out - charlie_out, rebate);
+            assert_eq!(recorded_rebates(), vec![(bob, rebate)]);
+        }
+
+        #[ink::test]
+        fn swap_adds_holder_rebate_to_the_output() {
+            let mut env = TestEnv::with_reserves(1_000_000, 2_000_000);
+            let (token0, token1, bob, charlie) =
+                (env.token0, env.token1, env.bob, env.charlie);
+            *env.pair.rebate_threshold = 100;
+            let governance_token = env.pair.governance_token();
+            mock_erc20::set_balance(governance_token, bob, 100);
+            let (amount_out, reserve0, reserve1) =
+                env.pair.quote_exact_in(100_000, true, 0);
+            let rebate = 60 * reserve1 / reserve0;
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+            for &swapper in [bob, charlie].iter() {
+                reset_reserves(&mut env);
+                // The swapper sent the input to the pair beforehand.
+                mock_erc20::set_balance(token0, pair_account, 1_100_000);
+                set_caller(swapper);
+                assert_eq!(env.pair.swap(0, amount_out, swapper), Ok(()));
+                ink_env::test::pop_execution_context();
+            }
+            let bob_out = mock_erc20::balance(token1, bob);
+            let charlie_out = mock_erc20::balance(token1, charlie);
+            assert_eq!(charlie_out, amount_out);
+            assert_eq!(bob_out - charlie_out, rebate);
+            assert_eq!(recorded_rebates(), vec![(bob, rebate)]);
+            // The rebate of Bob is not counted as a collected fee.
+            assert_eq!(env.pair.fees_collected0, (300 - 60) + 300);
+        }
+
+        #[ink::test]
+        fn set_imbalance_refund_bps_validates_input() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            assert_eq!(
+                pair.set_imbalance_refund_bps(101),
+                Err(Error::InvalidRefundBps { bps: 101 })
+            );
+            set_caller(bob);
+            assert_eq!(
+                pair.set_imbalance_refund_bps(50),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+            assert_eq!(pair.imbalance_refund_bps(), 0);
+        }
+
+        #[ink::test]
+        fn mint_whitelist_transitions_work() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            assert_eq!(pair.ensure_whitelisted(bob), Ok(()));
+
+            assert_eq!(pair.enable_mint_whitelist(), Ok(()));
+            assert!(pair.mint_whitelist_enabled());
+            assert_eq!(pair.ensure_whitelisted(bob), Err(Error::NotWhitelisted));
+
+            assert_eq!(pair.add_to_whitelist(bob), Ok(()));
+            assert!(pair.is_whitelisted(bob));
+            assert_eq!(pair.ensure_whitelisted(bob), Ok(()));
+
+            assert_eq!(pair.remove_from_whitelist(bob), Ok(()));
+            assert!(!pair.is_whitelisted(bob));
+            assert_eq!(pair.ensure_whitelisted(bob), Err(Error::NotWhitelisted));
+
+            assert_eq!(pair.disable_mint_whitelist(), Ok(()));
+            assert!(!pair.mint_whitelist_enabled());
+            assert_eq!(pair.ensure_whitelisted(bob), Ok(()));
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+        }
+
+        #[ink::test]
+        fn batch_approve_sets_every_allowance() {
+            let TestEnv { mut pair, alice, bob, charlie, .. } = TestEnv::new();
+            assert_eq!(
+                pair.batch_approve(vec![(bob, 100), (charlie, 200), (bob, 300)]),
+                Ok(())
+            );
+            // Later entries overwrite earlier ones like repeated approvals.
+            assert_eq!(pair.allowance(alice, bob), 300);
+            assert_eq!(pair.allowance(alice, charlie), 200);
+
+            let approvals = ink_env::test::recorded_events()
+                .map(|event| {
+                    let decoded = <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer");
+                    match decoded {
+                        Event::Approval(Approval { spender, value, .. }) => {
+                            (spender, value)
+                        }
+                        _ => panic!("encountered unexpected event kind"),
+                    }
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(approvals, [(bob, 100), (charlie, 200), (bob, 300)]);
+        }
+
+        #[ink::test]
+        fn batch_approve_fails_above_cap() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            let approvals = vec![(bob, 100); MAX_BATCH_APPROVALS as usize + 1];
+            assert_eq!(pair.batch_approve(approvals), Err(Error::TooManyApprovals));
+            assert_eq!(pair.allowance(alice, bob), 0);
+            assert_eq!(ink_env::test::recorded_events().count(), 0);
+
+            let approvals = vec![(bob, 100); MAX_BATCH_APPROVALS as usize];
+            assert_eq!(pair.batch_approve(approvals), Ok(()));
+            assert_eq!(pair.allowance(alice, bob), 100);
+        }
+
+        #[ink::test]
+        fn blacklisted_accounts_cannot_receive_lp_tokens() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.blacklist(bob), Ok(()));
+            assert!(pair.is_blacklisted(bob));
+            assert_eq!(
+                pair.transfer_from_to(alice, bob, 400),
+                Err(Error::Blacklisted { account: bob })
+            );
+            // The checks run before any token is called.
+            assert_eq!(pair.mint(bob), Err(Error::Blacklisted { account: bob }));
+            set_caller(bob);
+            assert_eq!(
+                pair.burn_exact_output(1, 1, bob, 0),
+                Err(Error::Blacklisted { account: bob })
+            );
+            ink_env::test::pop_execution_context();
+
+            assert_eq!(pair.unblacklist(bob), Ok(()));
+            assert!(!pair.is_blacklisted(bob));
+            assert_eq!(pair.transfer_from_to(alice, bob, 400), Ok((600, 400)));
+            assert_eq!(ink_env::test::recorded_events().count(), 3);
+        }
+
+        #[ink::test]
+        fn blacklisted_accounts_cannot_send_lp_tokens() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.blacklist(alice), Ok(()));
+            assert_eq!(
+                pair.transfer_from_to(alice, bob, 400),
+                Err(Error::Blacklisted { account: alice })
+            );
+            assert_eq!(pair.balance_of(alice), 1_000);
+        }
+
+        #[ink::test]
+        fn blacklisted_accounts_cannot_withdraw_in_an_emergency() {
+            let TestEnv { mut pair, alice, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.grant_role(GUARDIAN_ROLE, alice), Ok(()));
+            assert_eq!(pair.pause(), Ok(()));
+            assert_eq!(pair.blacklist(alice), Ok(()));
+            assert_eq!(
+                pair.emergency_withdraw(),
+                Err(Error::Blacklisted { account: alice })
+            );
+            assert_eq!(pair.balance_of(alice), 1_000);
+            assert_eq!((pair.reserve0, pair.reserve1), (1_000_000, 2_000_000));
+            assert!(!pair.status().locked);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: BLACKLISTED")]
+        fn blacklisted_accounts_cannot_receive_flash_mints() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            assert_eq!(pair.blacklist(bob), Ok(()));
+            pair.flash_mint(1_000, bob, Vec::new());
+        }
+
+        #[ink::test]
+        fn mint_whitelist_does_not_gate_swaps() {
+            let TestEnv { mut pair, token0, token1, alice, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.enable_mint_whitelist(), Ok(()));
+            assert_eq!(pair.add_to_whitelist(alice), Ok(()));
+            // The whitelist is checked before any token is called.
+            assert_eq!(pair.mint(bob), Err(Error::NotWhitelisted));
+            assert_eq!(pair.total_supply(), 0);
+            // Bob is not whitelisted but can still swap.
+            mock_erc20::set_balance(token0, bob, 1_000);
+            set_caller(bob);
+            assert_eq!(pair.swap_exact_in(1_000, true, 1_992, bob), Ok(1_992));
+            ink_env::test::pop_execution_context();
+            assert_eq!(mock_erc20::balance(token0, bob), 0);
+            assert_eq!(mock_erc20::balance(token1, bob), 1_992);
+            assert_eq!((pair.reserve0, pair.reserve1), (1_001_000, 1_998_008));
+        }
+
+        #[ink::test]
+        fn only_owner_can_update_mint_whitelist() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            set_caller(bob);
+            let missing_owner = Err(Error::MissingRole(MissingRole {
+                role: OWNER_ROLE,
+                account: bob,
+            }));
+            assert_eq!(pair.enable_mint_whitelist(), missing_owner);
+            assert_eq!(pair.disable_mint_whitelist(), missing_owner);
+            assert_eq!(pair.add_to_whitelist(bob), missing_owner);
+            assert_eq!(pair.remove_from_whitelist(bob), missing_owner);
+            ink_env::test::pop_execution_context();
+            assert!(!pair.mint_whitelist_enabled());
+            assert!(!pair.is_whitelisted(bob));
+        }
+
+        #[ink::test]
+        fn sweep_native_works() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract account");
+            let bob_balance =
+                ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(bob)
+                    .expect("Cannot get account balance");
+            // Someone sends 1_000 directly to the pair.
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(
+                pair_account,
+                1_000,
+            )
+            .expect("Cannot set account balance");
+
+            // The existential deposit of the off-chain environment is 42.
+            assert_eq!(pair.sweep_native(bob), Ok(958));
+            assert_eq!(
+                ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(bob),
+                Ok(bob_balance + 958)
+            );
+            assert_eq!(
+                ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(
+                    pair_account
+                ),
+                Ok(42)
+            );
+            assert_eq!(pair.sweep_native(bob), Ok(0));
+        }
+
+        #[ink::test]
+        fn only_owner_can_sweep_native() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            set_caller(bob);
+            assert_eq!(
+                pair.sweep_native(bob),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn first_mint_works() {
+            let TestEnv { pair, .. } = TestEnv::new();
+            assert_eq!(
+                pair.liquidity_to_mint(4_000, 1_000_000, 0),
+                63_245 - MINIMUM_LIQUIDITY
+            );
+        }
+
+        #[ink::test]
+        fn first_mint_locks_custom_minimum_liquidity() {
+            let TestEnv { pair, .. } = TestEnv::with_config(PairConfig {
+                minimum_liquidity: MIN_MINIMUM_LIQUIDITY,
+                ..PairConfig::default()
+            });
+            assert_eq!(pair.minimum_liquidity(), 100);
+            assert_eq!(pair.liquidity_to_mint(4_000, 1_000_000, 0), 63_245 - 100);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: INVALID_MINIMUM_LIQUIDITY")]
+        fn new_fails_below_minimum_liquidity_bounds() {
+            TestEnv::with_config(PairConfig {
+                minimum_liquidity: MIN_MINIMUM_LIQUIDITY - 1,
+                ..PairConfig::default()
+            });
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: INVALID_MINIMUM_LIQUIDITY")]
+        fn new_fails_above_minimum_liquidity_bounds() {
+            TestEnv::with_config(PairConfig {
+                minimum_liquidity: MAX_MINIMUM_LIQUIDITY + 1,
+                ..PairConfig::default()
+            });
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: INITIAL_MINT_TOO_SMALL")]
+        fn first_mint_below_minimum_fails() {
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            assert_eq!(pair.set_initial_mint_minimum(1_000_000), Ok(()));
+            assert_eq!(pair.initial_mint_minimum(), 1_000_000);
+            // The attacker opens the pair with the smallest possible mint.
+            pair.liquidity_to_mint(1_001, 1_001, 0);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: DONATION_ATTACK")]
+        fn mint_after_donation_fails() {
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            // The attacker minted 1 LP token on top of `MINIMUM_LIQUIDITY` and
+            // donated 10^12 of each token, so 1 LP token is worth ~10^9.
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract account");
+            pair._mint(pair_account, MINIMUM_LIQUIDITY + 1);
+            pair.reserve0 = 1_000_000_001_001;
+            pair.reserve1 = 1_000_000_001_001;
+            // Without the check the victim receives 500 LP tokens worth 499.5 * 10^9
+            // and the attacker takes the rest.
+            pair.liquidity_to_mint(
+                500_000_000_000,
+                500_000_000_000,
+                MINIMUM_LIQUIDITY + 1,
+            );
+        }
+
+        #[ink::test]
+        fn mint_at_fair_price_works() {
+            let TestEnv { mut pair, alice, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair._mint(alice, 1_414_213);
+            assert_eq!(pair.liquidity_to_mint(10_000, 20_000, 1_414_213), 14_142);
+        }
+
+        #[ink::test]
+        fn deposit_for_liquidity_mints_target() {
+            let TestEnv { mut pair, alice, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair._mint(alice, 1_414_213);
+            for &liquidity in &[1_000, 14_142, 123_457] {
+                let (amount0, amount1) = pair.deposit_for_liquidity(liquidity).unwrap();
+                assert_eq!(
+                    pair.liquidity_to_mint(amount0, amount1, pair.total_supply()),
+                    liquidity
+                );
+            }
+            assert_eq!(pair.deposit_for_liquidity(14_142), Ok((10_000, 20_000)));
+        }
+
+        #[ink::test]
+        fn deposit_for_liquidity_fails_without_liquidity() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.deposit_for_liquidity(1_000), Err(Error::EmptyPool));
+        }
+
+        #[ink::test]
+        fn position_works() {
+            let TestEnv { mut pair, alice, bob, charlie, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair._mint(alice, 250);
+            pair._mint(bob, 751);
+            assert_eq!(
+                pair.position(alice),
+                Position {
+                    lp_balance: 250,
+                    share_bps: 2_497,
+                    underlying0: 249_750,
+                    underlying1: 499_500,
+                }
+            );
+            assert_eq!(pair.position(charlie).underlying0, 0);
+        }
+
+        #[ink::test]
+        fn reconcile_reports_donations() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            // 500 token0 have been donated to the pair, 7 token1 went missing.
+            assert_eq!(
+                pair.reconcile_with(1_000_500, 1_999_993),
+                ReconcileReport {
+                    reserve0: 1_000_000,
+                    reserve1: 2_000_000,
+                    balance0: 1_000_500,
+                    balance1: 1_999_993,
+                    surplus0: 500,
+                    surplus1: 0,
+                    deficit0: 0,
+                    deficit1: 7,
+                }
+            );
+            let report = pair.reconcile_with(1_000_000, 2_000_000);
+            assert_eq!((report.surplus0, report.deficit1), (0, 0));
+        }
+
+        #[ink::test]
+        fn skimmable_is_zero_when_synced() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.skimmable_with(1_000_000, 2_000_000), (0, 0));
+            // A deficit is not skimmable.
+            assert_eq!(pair.skimmable_with(999_000, 2_000_000), (0, 0));
+        }
+
+        #[ink::test]
+        fn skimmable_reports_donations() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            // 500 token0 and 20 token1 have been sent to the pair directly.
+            assert_eq!(pair.skimmable_with(1_000_500, 2_000_020), (500, 20));
+        }
+
+        #[ink::test]
+        fn position_without_liquidity_is_empty() {
+            let TestEnv { pair, alice, .. } = TestEnv::new();
+            assert_eq!(
+                pair.position(alice),
+                Position {
+                    lp_balance: 0,
+                    share_bps: 0,
+                    underlying0: 0,
+                    underlying1: 0,
+                }
+            );
+        }
+
+        #[ink::test]
+        fn set_max_price_impact_bps_works() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            assert_eq!(pair.max_price_impact_bps(), 0);
+            assert_eq!(pair.set_max_price_impact_bps(500), Ok(()));
+            assert_eq!(pair.max_price_impact_bps(), 500);
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+            assert_eq!(
+                pair.set_max_price_impact_bps(10_001),
+                Err(Error::InvalidPriceImpactBps { bps: 10_001 })
+            );
+
+            set_caller(bob);
+            assert_eq!(
+                pair.set_max_price_impact_bps(0),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+            assert_eq!(pair.max_price_impact_bps(), 500);
+        }
+
+        #[ink::test]
+        fn price_impact_guard_works() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::with_reserves(1_000, 1_000);
+            // Without a limit any impact is accepted.
+            assert_eq!(pair.ensure_price_impact(500, true), Ok(()));
+
+            // Swapping 10 for 9 moves the price by 1.88%.
+            assert_eq!(pair.set_max_price_impact_bps(188), Ok(()));
+            assert_eq!(pair.ensure_price_impact(10, true), Ok(()));
+            assert_eq!(pair.ensure_price_impact(10, false), Ok(()));
+            assert_eq!(pair.set_max_price_impact_bps(187), Ok(()));
+            let too_high = || {
+                Error::PriceImpactTooHigh {
+                    impact_bps: 188,
+                    max_bps: 187,
+                }
+            };
+            assert_eq!(pair.ensure_price_impact(10, true), Err(too_high()));
+            // The swap is rejected before any token is moved.
+            assert_eq!(pair.swap_exact_in(10, true, 0, bob), Err(too_high()));
+            assert!(!pair.status().locked);
+        }
+
+        #[ink::test]
+        fn price_impact_guard_applies_to_swap() {
+            let TestEnv { mut pair, token0, token1, bob, .. } =
+                TestEnv::with_reserves(1_000, 1_000);
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+            // Bob sends 10 token0 to the pair beforehand and asks for 9 token1,
+            // which moves the price by 1.88%.
+            mock_erc20::set_balance(token0, pair_account, 1_010);
+            assert_eq!(pair.set_max_price_impact_bps(187), Ok(()));
+            set_caller(bob);
+            assert_eq!(
+                pair.swap(0, 9, bob),
+                Err(Error::PriceImpactTooHigh {
+                    impact_bps: 188,
+                    max_bps: 187,
+                })
+            );
+            ink_env::test::pop_execution_context();
+            // The output was not sent.
+            assert_eq!(mock_erc20::balance(token1, bob), 0);
+            assert_eq!(mock_erc20::balance(token1, pair_account), 1_000);
+            assert!(!pair.status().locked);
+
+            assert_eq!(pair.set_max_price_impact_bps(188), Ok(()));
+            set_caller(bob);
+            assert_eq!(pair.swap(0, 9, bob), Ok(()));
+            ink_env::test::pop_execution_context();
+            assert_eq!(mock_erc20::balance(token1, bob), 9);
+            assert_eq!((pair.reserve0, pair.reserve1), (1_010, 991));
+        }
+
+        #[ink::test]
+        fn set_max_referral_bps_works() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            assert_eq!(pair.max_referral_bps(), 0);
+            assert_eq!(pair.set_max_referral_bps(50), Ok(()));
+            assert_eq!(pair.max_referral_bps(), 50);
+            assert_eq!(
+                pair.set_max_referral_bps(10_001),
+                Err(Error::ReferralBpsTooHigh {
+                    bps: 10_001,
+                    max_bps: 10_000,
+                })
+            );
+
+            set_caller(bob);
+            assert_eq!(
+                pair.set_max_referral_bps(0),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn register_observer_works() {
+            let TestEnv { mut pair, alice, bob, charlie, .. } = TestEnv::new();
+            assert_eq!(pair.observers(), Vec::new());
+            assert_eq!(pair.register_observer(bob), Ok(()));
+            assert_eq!(pair.register_observer(charlie), Ok(()));
+            assert_eq!(pair.register_observer(bob), Err(Error::ObserverExists));
+            assert_eq!(pair.observers(), vec![bob, charlie]);
+
+            assert_eq!(pair.unregister_observer(bob), Ok(()));
+            assert_eq!(pair.unregister_observer(bob), Err(Error::ObserverNotFound));
+            assert_eq!(pair.observers(), vec![charlie]);
+
+            set_caller(bob);
+            assert_eq!(
+                pair.register_observer(alice),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn register_observer_fails_above_limit() {
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            for byte in 1..=MAX_OBSERVERS as u8 {
+                assert_eq!(pair.register_observer(AccountId::from([byte; 32])), Ok(()));
+            }
+            assert_eq!(
+                pair.register_observer(AccountId::from([0xff; 32])),
+                Err(Error::TooManyObservers)
+            );
+        }
+
+        /// Returns the number of recorded `HookFailed` events, which the tests
+        /// emit once for every call of an observer.
+        fn recorded_observer_calls() -> usize {
+            ink_env::test::recorded_events()
+                .filter(|event| {
+                    let decoded = <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer");
+                    matches!(decoded, Event::HookFailed(_))
+                })
+                .count()
+        }
+
+        #[ink::test]
+        fn swaps_notify_observers_once() {
+            let TestEnv { mut pair, token0, token1, bob, charlie, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+            assert_eq!(pair.set_auto_sync(true), Ok(()));
+            assert_eq!(pair.register_observer(charlie), Ok(()));
+
+            // token1 rebased down, so the swap syncs the reserves twice but
+            // notifies the observer once.
+            mock_erc20::set_balance(token1, pair_account, 1_980_000);
+            mock_erc20::set_balance(token0, bob, 1_000);
+            set_caller(bob);
+            assert!(pair.swap_exact_in(1_000, true, 0, bob).is_ok());
+            ink_env::test::pop_execution_context();
+            assert_eq!(recorded_observer_calls(), 1);
+
+            // Bob sent the input to the pair beforehand. `swap` cannot follow a
+            // rebase, the sync would take the input into the reserves.
+            mock_erc20::set_balance(token0, pair_account, pair.reserve0 + 1_000);
+            set_caller(bob);
+            assert_eq!(pair.swap(0, 100, bob), Ok(()));
+            ink_env::test::pop_execution_context();
+            assert_eq!(recorded_observer_calls(), 2);
+        }
+
+        #[ink::test]
+        fn set_transfer_hook_works() {
+            let TestEnv { mut pair, alice, bob, charlie, .. } = TestEnv::new();
+            assert_eq!(pair.transfer_hook(), None);
+            // Without a hook no contract is called.
+            pair._mint(alice, 1_000);
+            assert_eq!(pair.transfer_from_to(alice, bob, 400), Ok((600, 400)));
+            pair._burn(bob, 400);
+
+            assert_eq!(pair.set_transfer_hook(Some(charlie)), Ok(()));
+            assert_eq!(pair.transfer_hook(), Some(charlie));
+            set_caller(bob);
+            assert_eq!(
+                pair.set_transfer_hook(None),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+            assert_eq!(pair.set_transfer_hook(None), Ok(()));
+            assert_eq!(pair.transfer_hook(), None);
+        }
+
+        #[ink::test]
+        fn failed_hook_calls_emit_hook_failed() {
+            let TestEnv { pair, charlie, .. } = TestEnv::new();
+            // Contracts cannot be called off-chain, so the result of the hook
+            // call is passed to the handling shared by all hook calls directly.
+            let events_before = ink_env::test::recorded_events().count();
+            pair.report_hook_result(charlie, Ok(()));
+            assert_eq!(ink_env::test::recorded_events().count(), events_before);
+
+            pair.report_hook_result(charlie, Err(ink_env::Error::CalleeTrapped));
+            let events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), events_before + 1);
+            let data = &events[events_before].data;
+            let event = <Event as scale::Decode>::decode(&mut &data[..])
+                .expect("encountered invalid contract event data buffer");
+            match event {
+                Event::HookFailed(HookFailed { hook }) => assert_eq!(hook, charlie),
+                _ => panic!("encountered unexpected event kind"),
+            }
+        }
+
+        #[ink::test]
+        fn swap_with_referral_fails_above_max_referral_bps() {
+            let TestEnv { mut pair, bob, charlie, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            // Referrals are disabled by default.
+            assert_eq!(
+                pair.swap_with_referral(10_000, true, 0, bob, charlie, 1),
+                Err(Error::ReferralBpsTooHigh { bps: 1, max_bps: 0 })
+            );
+            assert_eq!(pair.set_max_referral_bps(50), Ok(()));
+            assert_eq!(
+                pair.swap_with_referral(10_000, true, 0, bob, charlie, 51),
+                Err(Error::ReferralBpsTooHigh {
+                    bps: 51,
+                    max_bps: 50,
+                })
+            );
+            assert_eq!((pair.reserve0, pair.reserve1), (1_000_000, 2_000_000));
+            assert!(!pair.status().locked);
+        }
+
+        #[ink::test]
+        fn flash_loan_views_work() {
+            let TestEnv { pair, token0, token1, alice, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.max_flash_loan(token0), 1_000_000);
+            assert_eq!(pair.max_flash_loan(token1), 2_000_000);
+            assert_eq!(pair.max_flash_loan(alice), 0);
+            assert_eq!(pair.flash_fee(token1, 10_000), Ok(30));
+            assert_eq!(pair.flash_fee(alice, 10_000), Err(Error::UnsupportedToken));
+        }
+
+        #[ink::test]
+        fn flash_loan_validates_request() {
+            let TestEnv { mut pair, token0, alice, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(
+                pair.flash_loan(bob, alice, 1_000, Vec::new()),
+                Err(Error::UnsupportedToken)
+            );
+            assert_eq!(
+                pair.flash_loan(bob, token0, 1_000_001, Vec::new()),
+                Err(Error::InsufficientBalance {
+                    required: 1_000_001,
+                    available: 1_000_000,
+                })
+            );
+            assert!(!pair.status().locked);
+        }
+
+        #[ink::test]
+        fn settle_flash_loan_adds_fee_to_reserves() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            // A donation of 500 token1 preceded the loan and stays out of the
+            // reserves.
+            assert_eq!(pair.settle_flash_loan(false, 2_000_500, 2_000_530, 30), Ok(()));
+            assert_eq!((pair.reserve0, pair.reserve1), (1_000_000, 2_000_030));
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: FLASH_LOAN_NOT_REPAID")]
+        fn flash_loan_fails_when_underpaid() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            // The loan was repaid without the last unit of the fee.
+            let _ = pair.settle_flash_loan(true, 1_000_000, 1_000_029, 30);
+        }
+
+        #[ink::test]
+        fn set_flash_mint_fee_bps_works() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            assert_eq!(pair.flash_mint_fee_bps(), 0);
+            assert_eq!(pair.flash_mint_fee(10_000), 0);
+            assert_eq!(pair.set_flash_mint_fee_bps(9), Ok(()));
+            assert_eq!(pair.flash_mint_fee(10_000), 9);
+            assert_eq!(pair.flash_mint_fee(1_000), 0);
+            assert_eq!(
+                pair.set_flash_mint_fee_bps(1_001),
+                Err(Error::InvalidFeeBps { bps: 1_001 })
+            );
+
+            set_caller(bob);
+            assert_eq!(
+                pair.set_flash_mint_fee_bps(0),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn flash_mint_burns_amount_and_fee() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            assert_eq!(pair.set_transfer_lock_blocks(10), Ok(()));
+            pair._mint(alice, 100_000);
+            pair._mint(bob, 500);
+            advance_blocks(10);
+            assert_eq!(pair.set_flash_mint_fee_bps(9), Ok(()));
+            let fee = pair.flash_mint_fee(1_000_000);
+            assert_eq!(fee, 900);
+
+            pair.begin_flash_mint(bob, 1_000_000);
+            // The flash-minted tokens can be put to use right away.
+            assert_eq!(pair.transfer_from_to(bob, alice, 1_000), Ok((999_500, 101_000)));
+            assert_eq!(pair.transfer_from_to(alice, bob, 1_400), Ok((99_600, 1_000_900)));
+            pair.settle_flash_mint(bob, 1_000_000, fee);
+            // The supply ends up lower by the fee.
+            assert_eq!(pair.total_supply(), 100_500 - fee);
+            assert_eq!(pair.balance_of(bob), 0);
+            assert_eq!(pair.balance_of(alice), 99_600);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: FLASH_MINT_NOT_REPAID")]
+        fn flash_mint_fails_when_underpaid() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            assert_eq!(pair.set_flash_mint_fee_bps(9), Ok(()));
+            pair.begin_flash_mint(bob, 1_000_000);
+            // Bob holds the minted tokens but nothing to pay the fee with.
+            pair.settle_flash_mint(bob, 1_000_000, pair.flash_mint_fee(1_000_000));
+        }
+
+        #[ink::test]
+        fn set_fee_bps_works() {
+            let TestEnv { mut pair, bob, .. } = TestEnv::new();
+            assert_eq!(pair.fee_bps(), 30);
+            assert_eq!(pair.set_fee_bps(0), Ok(()));
+            assert_eq!(pair.fee_bps(), 0);
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+            assert_eq!(
+                pair.set_fee_bps(1_001),
+                Err(Error::InvalidFeeBps { bps: 1_001 })
+            );
+
+            set_caller(bob);
+            assert_eq!(
+                pair.set_fee_bps(30),
+                Err(Error::MissingRole(MissingRole {
+                    role: OWNER_ROLE,
+                    account: bob,
+                }))
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn balance_history_works() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            advance_blocks(1);
+            assert_eq!(pair.transfer_from_to(alice, bob, 400), Ok((600, 400)));
+            // Changes within a block only keep the final balance.
+            pair._mint(alice, 100);
+            pair._burn(alice, 100);
+            advance_blocks(1);
+
+            assert_eq!(pair.balance_of_at(alice, 0), 0);
+            assert_eq!(pair.balance_of_at(alice, 1), 1_000);
+            assert_eq!(pair.balance_of_at(alice, 2), 600);
+            assert_eq!(pair.balance_of_at(alice, 100), 600);
+            assert_eq!(pair.balance_of_at(bob, 1), 0);
+            assert_eq!(pair.balance_of_at(bob, 2), 400);
+            assert_eq!(pair.checkpoint_counts.get(&Some(alice)), Some(&2));
+            assert_eq!(pair.total_supply_at(0), 0);
+            assert_eq!(pair.total_supply_at(1), 1_000);
+            assert_eq!(pair.total_supply_at(2), 1_000);
+        }
+
+        #[ink::test]
+        fn propose_fee_validates_request() {
+            let TestEnv { mut pair, alice, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            // Tokens received in the current block do not count yet.
+            assert_eq!(pair.propose_fee(10), Err(Error::NoVotingPower));
+            advance_blocks(1);
+            assert_eq!(
+                pair.propose_fee(1_001),
+                Err(Error::InvalidFeeBps { bps: 1_001 })
+            );
+            assert_eq!(pair.vote(0, true), Err(Error::ProposalNotFound));
+            assert_eq!(pair.execute(0), Err(Error::ProposalNotFound));
+            assert_eq!(pair.propose_fee(10), Ok(0));
+            assert_eq!(pair.propose_fee(20), Ok(1));
+            assert_eq!(pair.fee_proposal(1).map(|proposal| proposal.fee_bps), Some(20));
+        }
+
+        #[ink::test]
+        fn fee_vote_with_two_voters_works() {
+            let TestEnv { mut pair, alice, bob, charlie, .. } = TestEnv::new();
+            pair._mint(alice, 600);
+            pair._mint(bob, 400);
+            advance_blocks(1);
+            assert_eq!(pair.propose_fee(10), Ok(0));
+            // Tokens moved after the snapshot keep their weight.
+            assert_eq!(pair.transfer_from_to(bob, charlie, 100), Ok((300, 100)));
+
+            assert_eq!(pair.vote(0, true), Ok(()));
+            assert_eq!(pair.vote(0, false), Err(Error::AlreadyVoted));
+            set_caller(bob);
+            assert_eq!(pair.vote(0, false), Ok(()));
+            ink_env::test::pop_execution_context();
+            set_caller(charlie);
+            assert_eq!(pair.vote(0, true), Err(Error::NoVotingPower));
+            ink_env::test::pop_execution_context();
+            assert_eq!(
+                pair.fee_proposal(0),
+                Some(FeeProposal {
+                    fee_bps: 10,
+                    snapshot_block: 1,
+                    votes_for: 600,
+                    votes_against: 400,
+                    executed: false,
+                })
+            );
+            assert_eq!(pair.execute(0), Err(Error::VotingOpen));
+
+            advance_blocks(VOTING_PERIOD_BLOCKS);
+            set_caller(charlie);
+            assert_eq!(pair.vote(0, true), Err(Error::VotingClosed));
+            // Anyone may execute a passed proposal.
+            assert_eq!(pair.execute(0), Ok(()));
+            ink_env::test::pop_execution_context();
+            assert_eq!(pair.fee_bps(), 10);
+            assert_eq!(pair.execute(0), Err(Error::AlreadyExecuted));
+        }
+
+        #[ink::test]
+        fn fee_vote_fails_without_quorum() {
+            let TestEnv { mut pair, alice, charlie, .. } = TestEnv::new();
+            pair._mint(alice, 1_000);
+            pair._mint(charlie, 100_000);
+            advance_blocks(1);
+            assert_eq!(pair.propose_fee(10), Ok(0));
+            assert_eq!(pair.vote(0, true), Ok(()));
+            advance_blocks(VOTING_PERIOD_BLOCKS);
+            // 1_000 of 101_000 LP tokens is below the quorum of 10%.
+            assert_eq!(pair.execute(0), Err(Error::QuorumNotReached));
+            assert_eq!(pair.fee_bps(), 30);
+        }
+
+        #[ink::test]
+        fn fee_vote_can_be_rejected_or_expire() {
+            let TestEnv { mut pair, alice, bob, .. } = TestEnv::new();
+            pair._mint(alice, 600);
+            pair._mint(bob, 400);
+            advance_blocks(1);
+            assert_eq!(pair.propose_fee(10), Ok(0));
+            assert_eq!(pair.propose_fee(20), Ok(1));
+            assert_eq!(pair.vote(0, false), Ok(()));
+            assert_eq!(pair.vote(1, true), Ok(()));
+            set_caller(bob);
+            assert_eq!(pair.vote(0, true), Ok(()));
+            ink_env::test::pop_execution_context();
+
+            advance_blocks(VOTING_PERIOD_BLOCKS);
+            assert_eq!(pair.execute(0), Err(Error::ProposalRejected));
+            advance_blocks(EXECUTION_PERIOD_BLOCKS);
+            assert_eq!(pair.execute(1), Err(Error::ProposalExpired));
+            assert_eq!(pair.fee_bps(), 30);
+        }
+
+        #[ink::test]
+        fn zero_fee_round_trip_returns_input() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 1_000_000);
+            assert_eq!(pair.set_fee_bps(0), Ok(()));
+            let (out, reserve0, reserve1) = pair.quote_exact_in(10_000, true, 0);
+            assert_eq!((out, reserve0, reserve1), (9_900, 1_010_000, 990_100));
+            pair.reserve0 = reserve0;
+            pair.reserve1 = reserve1;
+            // Only the integer rounding of both swaps is lost.
+            let (back, reserve0, reserve1) = pair.quote_exact_in(out, false, 0);
+            assert_eq!((back, reserve0, reserve1), (9_999, 1_000_001, 1_000_000));
+            assert_eq!(pair.fee_of(10_000), 0);
+        }
+
+        #[ink::test]
+        fn max_input_to_price_works() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            // token1 is worth 0.5 token0 and is pushed down to 0.45.
+            let amount_in = pair.max_input_to_price(9, 20, false);
+            let (_, reserve0, reserve1) = pair.quote_exact_in(amount_in, false, 0);
+            assert!(reserve0 * 20 >= reserve1 * 9);
+            assert!(reserve0 * 20 * 1_000 < reserve1 * 9 * 1_001);
+            // token0 is worth 2 token1, which is below 2.5 already.
+            assert_eq!(pair.max_input_to_price(5, 2, true), 0);
+        }
+
+        #[ink::test]
+        fn total_fees_accumulate() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.total_fees(), (0, 0));
+            for &(amount0_in, amount1_in) in &[(10_000, 0), (0, 4_000), (333, 0), (50_000, 0)] {
+                pair.record_swap(
+                    amount0_in,
+                    amount1_in,
+                    math::fee_of(amount0_in, math::FEE_BPS),
+                    math::fee_of(amount1_in, math::FEE_BPS),
+                );
+            }
+            // 30 + 0 + 0 + 150 and 12.
+            assert_eq!(pair.total_fees(), (180, 12));
+        }
+
+        #[ink::test]
+        fn total_fees_saturate() {
+            let TestEnv { mut pair, .. } = TestEnv::new();
+            pair.record_swap(0, 0, Balance::MAX, 1);
+            pair.record_swap(0, 0, 1, 1);
+            assert_eq!(pair.total_fees(), (Balance::MAX, 2));
+        }
+
+        #[ink::test]
+        fn snapshot_round_trip_works() {
+            let TestEnv { mut pair, bob, .. } =
+                TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair.record_swap(10_000, 0, 30, 0);
+            pair.record_swap(0, 4_000, 0, 12);
+            assert_eq!(pair.set_fee_bps(25), Ok(()));
+            let snapshot = pair.take_snapshot().unwrap();
+            assert_eq!(snapshot.reserve0, 1_000_000);
+            assert_eq!(snapshot.stats, pair.get_stats());
+
+            // The snapshot survives being passed to another chain.
+            let encoded = scale::Encode::encode(&snapshot);
+            let decoded: PairSnapshot = scale::Decode::decode(&mut &encoded[..]).unwrap();
+            let TestEnv { pair: mut fork, .. } = TestEnv::new();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(fork.restore_snapshot(decoded), Ok(()));
+            assert_eq!((fork.reserve0, fork.reserve1), (pair.reserve0, pair.reserve1));
+            assert_eq!(fork.fee_bps(), 25);
+            assert_eq!(fork.get_stats(), pair.get_stats());
+            assert!(fork.status().paused);
+            assert_eq!(
+                fork.take_snapshot(),
+                Ok(PairSnapshot {
+                    block: 1,
+                    ..snapshot
+                })
+            );
+            assert_eq!(fork.restore_snapshot(snapshot), Err(Error::AlreadyRestored));
+
+            // Only owners may take or restore snapshots, and only into fresh pairs.
+            assert_eq!(pair.restore_snapshot(snapshot), Err(Error::PairInUse));
+            set_caller(bob);
+            let missing_owner = MissingRole {
+                role: OWNER_ROLE,
+                account: bob,
+            };
+            assert_eq!(pair.take_snapshot(), Err(Error::MissingRole(missing_owner)));
+            assert_eq!(
+                pair.restore_snapshot(snapshot),
+                Err(Error::MissingRole(missing_owner))
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn get_stats_sums_swaps() {
+            let TestEnv { mut pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            let swaps = [(10_000, 0, 30, 0), (0, 4_000, 0, 12), (50_000, 0, 150, 0)];
+            for &(amount0_in, amount1_in, fee0, fee1) in &swaps {
+                pair.record_swap(amount0_in, amount1_in, fee0, fee1);
+            }
+            assert_eq!(
+                pair.get_stats(),
+                PairStats {
+                    volume0: 60_000,
+                    volume1: 4_000,
+                    fees0: 180,
+                    fees1: 12,
+                    swap_count: 3,
+                }
+            );
+        }
+
+        #[ink::test]
+        fn spot_price0_fixed_works() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(4_000_000, 10_000_000);
+            assert_eq!(pair.spot_price0_fixed(0), Ok(2));
+            assert_eq!(pair.spot_price0_fixed(6), Ok(2_500_000));
+            assert_eq!(pair.spot_price0_fixed(18), Ok(2_500_000_000_000_000_000));
+            assert_eq!(
+                pair.spot_price0_fixed(19),
+                Err(Error::InvalidPrecision { precision: 19 })
+            );
+
+            let TestEnv { pair, .. } = TestEnv::with_reserves(3, 1);
+            assert_eq!(pair.spot_price0_fixed(6), Ok(333_333));
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1, Balance::MAX / 10);
+            assert_eq!(pair.spot_price0_fixed(18), Err(Error::PriceOverflow));
+            let TestEnv { pair, .. } = TestEnv::new();
+            assert_eq!(pair.spot_price0_fixed(6), Err(Error::EmptyPool));
+        }
+
+        #[ink::test]
+        fn lp_value_uses_normalized_prices() {
+            // One token0 with 8 decimals and two token1 with 6 decimals back one
+            // LP token.
+            let TestEnv { mut pair, alice, .. } =
+                TestEnv::with_reserves(100_000_000, 2_000_000);
+            pair._mint(alice, 1_000_000_000_000_000_000);
+            // token0 trades at $2 with 8 decimals, token1 at $1 with 18 decimals.
+            let price0 = math::normalize_price(200_000_000, 8).unwrap();
+            let price1 = math::normalize_price(1_000_000_000_000_000_000, 18).unwrap();
+            assert_eq!(
+                pair.lp_value(price0, price1, 8, 6),
+                Some(4_000_000_000_000_000_000)
+            );
+            // Reading the reserves with 18 decimals would price them at almost
+            // nothing.
+            assert_eq!(pair.lp_value(price0, price1, 18, 18), Some(202_000_000));
+            assert_eq!(math::normalize_amount(1, 20), Some(0));
+            assert_eq!(math::normalize_amount(Balance::MAX, 6), None);
+            assert_eq!(math::normalize_price(-1, 8), None);
+            assert_eq!(math::lp_value(1, price0, 1, price1, 0), None);
+        }
+
+        #[ink::test]
+        fn execution_price_works() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.execution_price(1_000, true), (1_992, 1_000));
+            assert_eq!(pair.execution_price(1_000, false), (498, 1_000));
+        }
+
+        #[ink::test]
+        fn min_received_applies_slippage() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            assert_eq!(pair.min_received(1_000, true, 0), 1_992);
+            // 1_992 * 0.995 = 1_982.04
+            assert_eq!(pair.min_received(1_000, true, 50), 1_982);
+            // 1_992 * 0.97 = 1_932.24
+            assert_eq!(pair.min_received(1_000, true, 300), 1_932);
+            assert_eq!(pair.min_received(1_000, true, 10_000), 0);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: INVALID_SLIPPAGE")]
+        fn min_received_fails_above_full_slippage() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 2_000_000);
+            pair.min_received(1_000, true, 10_001);
+        }
+
+        #[ink::test]
+        fn execution_price_degrades_with_size() {
+            let TestEnv { pair, .. } = TestEnv::with_reserves(1_000_000, 1_000_000);
+            let (small_out, small_in) = pair.execution_price(1_000, true);
+            let (large_out, large_in) = pair.execution_price(100_000, true);
+            // small_out / small_in > large_out / large_in
+            assert!(small_out * large_in > large_out * small_in);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Uniswap: INSUFFICIENT_LIQUIDITY")]
+        fn execution_price_fails_without_liquidity() {
+            let TestEnv { pair, .. } = TestEnv::new();
+            pair.execution_price(1_000, true);
+        }
+
+        /// An operation of the randomized pair test.
+        #[derive(Debug, Clone, Copy)]
+        enum Op {
+            Mint { to: usize, amount0: Balance, amount1: Balance },
+            Burn { from: usize },
+            Swap { from: usize, amount_in: Balance, zero_for_one: bool },
+            Transfer { from: usize, to: usize, value: Balance },
+            Donate { amount0: Balance, amount1: Balance },
+            Skim { to: usize },
+            Sync,
+        }
+
+        /// A xorshift generator, good enough to pick random operations.
+        struct Rng(u64);
+
+        impl Rng {
+            fn next(&mut self) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0
+            }
+
+            /// Returns a value in `1..=max`, leaning towards small values.
+            fn amount(&mut self, max: Balance) -> Balance {
+                let bits = self.next() % 41;
+                1 + (self.next() as Balance % max).min((1 << bits) - 1)
+            }
+
+            fn account(&mut self) -> usize {
+                (self.next() % 3) as usize
+            }
+
+            fn op(&mut self) -> Op {
+                match self.next() % 7 {
+                    0 => Op::Mint {
+                        to: self.account(),
+                        amount0: self.amount(1 << 40),
+                        amount1: self.amount(1 << 40),
+                    },
+                    1 => Op::Burn { from: self.account() },
+                    2 => Op::Swap {
+                        from: self.account(),
+                        amount_in: self.amount(1 << 40),
+                        zero_for_one: self.next() % 2 == 0,
+                    },
+                    3 => Op::Transfer {
+                        from: self.account(),
+                        to: self.account(),
+                        value: self.amount(1 << 40),
+                    },
+                    4 => Op::Donate {
+                        amount0: self.amount(1 << 20),
+                        amount1: self.amount(1 << 20),
+                    },
+                    5 => Op::Skim { to: self.account() },
+                    _ => Op::Sync,
+                }
+            }
+        }
+
+        /// Adds `value` to the `token` balance of `owner`.
+        fn credit(token: AccountId, owner: AccountId, value: Balance) {
+            let balance = mock_erc20::balance(token, owner);
+            mock_erc20::set_balance(token, owner, balance + value);
+        }
+
+        /// Runs `op` through the messages of `env.pair`, with the caller funding
+        /// its deposits and swaps. Operations the pair would panic on are skipped.
+        ///
+        /// Returns whether the operation ran.
+        fn apply(env: &mut TestEnv, pair_account: AccountId, op: Op) -> bool {
+            let accounts = [env.alice, env.bob, env.charlie];
+            let (token0, token1) = (env.token0, env.token1);
+            let pair = &mut env.pair;
+            let total_supply = pair.total_supply();
+            let balance0 = mock_erc20::balance(token0, pair_account);
+            let balance1 = mock_erc20::balance(token1, pair_account);
+            match op {
+                Op::Mint { to, amount0, amount1 } => {
+                    let deposit0 = balance0 - pair.reserve0 + amount0;
+                    let deposit1 = balance1 - pair.reserve1 + amount1;
+                    if total_supply == 0 {
+                        if math::sqrt(deposit0 * deposit1) <= pair.minimum_liquidity() {
+                            return false
+                        }
+                    } else if math::mint_liquidity(
+                        deposit0,
+                        deposit1,
+                        pair.reserve0,
+                        pair.reserve1,
+                        total_supply,
+                    ) < DONATION_LIQUIDITY_THRESHOLD
+                    {
+                        return false
+                    }
+                    credit(token0, pair_account, amount0);
+                    credit(token1, pair_account, amount1);
+                    set_caller(accounts[to]);
+                    let minted = pair.mint(accounts[to]);
+                    ink_env::test::pop_execution_context();
+                    minted.is_ok()
+                }
+                Op::Burn { from } => {
+                    let liquidity = pair.balance_of(accounts[from]);
+                    if liquidity == 0 {
+                        return false
+                    }
+                    let (amount0, amount1) =
+                        math::burn_amounts(liquidity, balance0, balance1, total_supply);
+                    if amount0 == 0 || amount1 == 0 {
+                        return false
+                    }
+                    set_caller(accounts[from]);
+                    let burned = pair.burn(accounts[from]);
+                    ink_env::test::pop_execution_context();
+                    burned.is_ok()
+                }
+                Op::Swap { from, amount_in, zero_for_one } => {
+                    if total_supply == 0 {
+                        return false
+                    }
+                    let token_in = if zero_for_one { token0 } else { token1 };
+                    credit(token_in, accounts[from], amount_in);
+                    set_caller(accounts[from]);
+                    let swapped =
+                        pair.swap_exact_in(amount_in, zero_for_one, 0, accounts[from]);
+                    ink_env::test::pop_execution_context();
+                    swapped.is_ok()
+                }
+                Op::Transfer { from, to, value } => {
+                    set_caller(accounts[from]);
+                    let transferred = pair.transfer(accounts[to], value);
+                    ink_env::test::pop_execution_context();
+                    transferred.is_ok()
+                }
+                Op::Donate { amount0, amount1 } => {
+                    credit(token0, pair_account, amount0);
+                    credit(token1, pair_account, amount1);
+                    true
+                }
+                Op::Skim { to } => {
+                    set_caller(env.alice);
+                    let skimmed = pair.skim(accounts[to]);
+                    ink_env::test::pop_execution_context();
+                    skimmed.is_ok()
+                }
+                Op::Sync => {
+                    set_caller(env.alice);
+                    let synced = pair.sync();
+                    ink_env::test::pop_execution_context();
+                    synced.is_ok()
+                }
+            }
+        }
+
+        /// Drives pairs through random operation sequences against mocked token
+        /// balances and checks that the balances only exceed the reserves by
+        /// donations, that the LP balances add up to the total supply and that
+        /// only burns decrease `K`.
+        ///
+        /// Run with `cargo test -- --ignored`. A violation prints the operations
+        /// that led to it.
+        #[ink::test]
+        #[ignore]
+        fn randomized_operations_keep_invariants() {
+            let pair_account = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract account");
+            let surplus = |env: &TestEnv| {
+                let balance0 = mock_erc20::balance(env.token0, pair_account);
+                let balance1 = mock_erc20::balance(env.token1, pair_account);
+                (balance0 - env.pair.reserve0, balance1 - env.pair.reserve1)
+            };
+            let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+            for _ in 0..1_000 {
+                let mut env = TestEnv::new();
+                let accounts = [env.alice, env.bob, env.charlie, pair_account];
+                let mut ops = Vec::new();
+                for _ in 0..100 {
+                    let op = rng.op();
+                    let (surplus0, surplus1) = surplus(&env);
+                    let k = env.pair.reserve0 * env.pair.reserve1;
+                    if !apply(&mut env, pair_account, op) {
+                        continue
+                    }
+                    ops.push(op);
+                    let expected_surplus = match op {
+                        Op::Donate { amount0, amount1 } => {
+                            (surplus0 + amount0, surplus1 + amount1)
+                        }
+                        Op::Swap { .. } | Op::Transfer { .. } => (surplus0, surplus1),
+                        _ => (0, 0),
+                    };
+                    assert_eq!(
+                        surplus(&env),
+                        expected_surplus,
+                        "Balances exceed the reserves by more than donations after {:?}",
+                        ops
+                    );
+                    let lp_balances = accounts
+                        .iter()
+                        .map(|account| env.pair.balance_of(*account))
+                        .sum::<Balance>();
+                    assert_eq!(
+                        lp_balances,
+                        env.pair.total_supply(),
+                        "LP balances differ from the total supply after {:?}",
+                        ops
+                    );
+                    if !matches!(op, Op::Burn { .. }) {
+                        assert!(
+                            env.pair.reserve0 * env.pair.reserve1 >= k,
+                            "K decreased after {:?}",
+                            ops
+                        );
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn metadata_resolves_all_event_fields() {
+            extern "Rust" {
+                fn __ink_generate_metadata() -> ink_metadata::InkProject;
+            }
+            let metadata = unsafe { __ink_generate_metadata() };
+            let json =
+                serde_json::to_value(&metadata).expect("Cannot serialize metadata");
+            let types = json["types"].as_array().expect("Missing types");
+            let events = json["spec"]["events"].as_array().expect("Missing events");
+            let names = events
+                .iter()
+                .map(|event| event["name"].as_str().expect("Missing event name"))
+                .collect::<Vec<_>>();
+            for name in &["Sync", "Mint", "Burn", "Swap", "Transfer", "Approval"] {
+                assert!(names.contains(name), "Missing event {}", name);
+            }
+            for event in events {
+                for arg in event["args"].as_array().expect("Missing event args") {
+                    let display_name = arg["type"]["displayName"]
+                        .as_array()
+                        .expect("Missing display name");
+                    assert!(!display_name.is_empty(), "Unnamed type in {}", event);
+                    let id = arg["type"]["type"].as_u64().expect("Missing type id");
+                    assert!(id >= 1 && id as usize <= types.len(), "Unresolved type in {}", event);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,667 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pure arithmetic used by the pair contract.
+
+pub use full_math::{
+    full_mul,
+    mul_div,
+};
+
+/// The balance type of the default environment.
+pub type Balance = u128;
+
+/// Returns the smaller of `x` and `y`.
+pub fn min(x: Balance, y: Balance) -> Balance {
+    if x < y {
+        x
+    } else {
+        y
+    }
+}
+
+/// Returns the integer square root of `y` using the babylonian method.
+pub fn sqrt(y: Balance) -> Balance {
+    if y > 3 {
+        let mut z = y;
+        let mut x = y / 2 + 1;
+        while x < z {
+            z = x;
+            x = (y / x + x) / 2;
+        }
+        z
+    } else if y != 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// The default swap fee in basis points.
+pub const FEE_BPS: Balance = 30;
+
+/// The largest swap fee in basis points.
+pub const MAX_FEE_BPS: Balance = 1_000;
+
+/// The largest reserve, matching the `uint112` reserves of UniswapV2.
+pub const RESERVE_MAX: Balance = (1 << 112) - 1;
+
+/// Packs `reserve0` and `reserve1` into the 256-bit `(high, low)` value of the
+/// reserve slot of UniswapV2, `reserve0` in the lowest 112 bits followed by
+/// `reserve1`.
+///
+/// The 32 bits of the block timestamp at the top are left zero.
+///
+/// # Panics
+///
+/// If a reserve exceeds `RESERVE_MAX`.
+pub fn encode_reserves_packed(reserve0: Balance, reserve1: Balance) -> (u128, u128) {
+    assert!(
+        reserve0 <= RESERVE_MAX && reserve1 <= RESERVE_MAX,
+        "Uniswap: OVERFLOW"
+    );
+    (reserve1 >> 16, reserve0 | (reserve1 << 112))
+}
+
+/// Returns the portion of `amount_in` taken by a fee of `fee_bps` basis points.
+pub fn fee_of(amount_in: Balance, fee_bps: Balance) -> Balance {
+    mul_div(amount_in, fee_bps, 10_000).expect("Uniswap: FEE_OVERFLOW")
+}
+
+/// Given an input amount of an asset and the pair reserves, returns the maximum
+/// output amount of the other asset after a swap fee of `fee_bps` basis points.
+pub fn get_amount_out(
+    amount_in: Balance,
+    reserve_in: Balance,
+    reserve_out: Balance,
+    fee_bps: Balance,
+) -> Balance {
+    assert!(amount_in > 0, "Uniswap: INSUFFICIENT_INPUT_AMOUNT");
+    assert!(
+        reserve_in > 0 && reserve_out > 0,
+        "Uniswap: INSUFFICIENT_LIQUIDITY"
+    );
+    let amount_in_with_fee = amount_in * (10_000 - fee_bps);
+    let denominator = reserve_in * 10_000 + amount_in_with_fee;
+    // Less than `reserve_out`, so it always fits.
+    mul_div(amount_in_with_fee, reserve_out, denominator)
+        .expect("the output is below reserve_out")
+}
+
+/// Returns `true` if a swap of `amount_in` that moves the reserves from
+/// `(reserve_in, reserve_out)` to `(balance_in, balance_out)` keeps the constant
+/// product after a fee of `fee_bps` basis points on the input.
+///
+/// Without a fee the raw balances are compared.
+pub fn keeps_k(
+    reserve_in: Balance,
+    reserve_out: Balance,
+    balance_in: Balance,
+    balance_out: Balance,
+    amount_in: Balance,
+    fee_bps: Balance,
+) -> bool {
+    if fee_bps == 0 {
+        return full_mul(balance_in, balance_out) >= full_mul(reserve_in, reserve_out)
+    }
+    // The products are compared as `(high, low)` halves, which order correctly.
+    full_mul(balance_in * 10_000 - amount_in * fee_bps, balance_out)
+        >= full_mul(reserve_in * 10_000, reserve_out)
+}
+
+/// Returns the largest input that swapped into a pair with the reserves
+/// `reserve_in` and `reserve_out` keeps the marginal price
+/// `reserve_out / reserve_in` of the input at or above
+/// `price_num / price_den`, after a fee of `fee_bps` basis points.
+///
+/// Returns zero if the price is already at or below the target.
+///
+/// # Panics
+///
+/// If `price_num` or `price_den` is zero.
+pub fn max_input_to_price(
+    reserve_in: Balance,
+    reserve_out: Balance,
+    price_num: Balance,
+    price_den: Balance,
+    fee_bps: Balance,
+) -> Balance {
+    assert!(price_num > 0 && price_den > 0, "Uniswap: INVALID_PRICE");
+    if reserve_out * price_den <= price_num * reserve_in {
+        return 0
+    }
+    // With `g = 1 - fee` and `r = reserve_in` the input `x` moves the price to
+    // `k / ((r + g * x) * (r + x))`. Setting it to the target `t` and solving
+    // for `x` gives
+    //
+    // `x = (sqrt(r^2 * (1 - g)^2 + 4 * g * k / t) - r * (1 + g)) / (2 * g)`
+    //
+    // which is computed with `g` scaled by 10_000 and rounded down.
+    let gamma = 10_000 - fee_bps;
+    let k_over_target = reserve_in * reserve_out * price_den / price_num;
+    let fee_term = reserve_in * (10_000 - gamma);
+    let root = sqrt(fee_term * fee_term + 4 * gamma * 10_000 * k_over_target);
+    root.saturating_sub(reserve_in * (10_000 + gamma)) / (2 * gamma)
+}
+
+/// Returns the LP tokens minted for depositing `amount0` and `amount1` into a
+/// pair with the reserves `reserve0` and `reserve1` and `total_supply` LP tokens.
+///
+/// Rounds down in favor of the pair. The rounding residue of the deposit stays
+/// in the reserves and accrues to the existing LPs.
+pub fn mint_liquidity(
+    amount0: Balance,
+    amount1: Balance,
+    reserve0: Balance,
+    reserve1: Balance,
+    total_supply: Balance,
+) -> Balance {
+    min(
+        amount0 * total_supply / reserve0,
+        amount1 * total_supply / reserve1,
+    )
+}
+
+/// Returns the smallest amounts of token0 and token1 that mint at least
+/// `liquidity` LP tokens in a pair with the reserves `reserve0` and `reserve1`
+/// and `total_supply` LP tokens.
+///
+/// Rounds up, so the mint yields exactly `liquidity` as long as `total_supply`
+/// does not exceed the reserves.
+pub fn deposit_for_liquidity(
+    liquidity: Balance,
+    reserve0: Balance,
+    reserve1: Balance,
+    total_supply: Balance,
+) -> (Balance, Balance) {
+    let div_ceil = |numerator: Balance| (numerator + total_supply - 1) / total_supply;
+    (div_ceil(liquidity * reserve0), div_ceil(liquidity * reserve1))
+}
+
+/// Returns the amounts of token0 and token1 used from `amount0_desired` and
+/// `amount1_desired` to add liquidity at the ratio of the reserves `reserve0`
+/// and `reserve1`.
+///
+/// The full desired amount of the limiting token is used, the other token
+/// only up to the ratio, rounded down. An empty pair uses both amounts.
+pub fn optimal_amounts(
+    amount0_desired: Balance,
+    amount1_desired: Balance,
+    reserve0: Balance,
+    reserve1: Balance,
+) -> (Balance, Balance) {
+    if reserve0 == 0 && reserve1 == 0 {
+        return (amount0_desired, amount1_desired)
+    }
+    let amount1_optimal = amount0_desired * reserve1 / reserve0;
+    if amount1_optimal <= amount1_desired {
+        (amount0_desired, amount1_optimal)
+    } else {
+        (amount1_desired * reserve0 / reserve1, amount1_desired)
+    }
+}
+
+/// Returns the amounts of token0 and token1 paid out for burning `liquidity` of
+/// `total_supply` LP tokens of a pair holding `balance0` and `balance1`.
+///
+/// Rounds down in favor of the pair. The rounding residue stays in the reserves
+/// and accrues to the remaining LPs.
+pub fn burn_amounts(
+    liquidity: Balance,
+    balance0: Balance,
+    balance1: Balance,
+    total_supply: Balance,
+) -> (Balance, Balance) {
+    (
+        liquidity * balance0 / total_supply,
+        liquidity * balance1 / total_supply,
+    )
+}
+
+/// Returns the fewest of `total_supply` LP tokens of a pair holding `reserve0`
+/// and `reserve1` whose `burn_amounts` pay out at least `amount0` and `amount1`.
+///
+/// Rounds up in favor of the pair.
+pub fn liquidity_for_amounts(
+    amount0: Balance,
+    amount1: Balance,
+    reserve0: Balance,
+    reserve1: Balance,
+    total_supply: Balance,
+) -> Balance {
+    let div_ceil = |numerator: Balance, denominator: Balance| {
+        (numerator + denominator - 1) / denominator
+    };
+    core::cmp::max(
+        div_ceil(amount0 * total_supply, reserve0),
+        div_ceil(amount1 * total_supply, reserve1),
+    )
+}
+
+/// Returns `true` if moving the reserves from `(reserve0, reserve1)` to
+/// `(new_reserve0, new_reserve1)` brings their ratio closer to 1:1.
+pub fn reduces_imbalance(
+    reserve0: Balance,
+    reserve1: Balance,
+    new_reserve0: Balance,
+    new_reserve1: Balance,
+) -> bool {
+    let (high, low) = (core::cmp::max(reserve0, reserve1), min(reserve0, reserve1));
+    let (new_high, new_low) = (
+        core::cmp::max(new_reserve0, new_reserve1),
+        min(new_reserve0, new_reserve1),
+    );
+    if low == 0 || new_low == 0 {
+        return false
+    }
+    // new_high / new_low < high / low
+    new_high * low < high * new_low
+}
+
+/// Returns by how many basis points moving the reserves from `(reserve_in,
+/// reserve_out)` to `(new_reserve_in, new_reserve_out)` lowers the marginal
+/// price `reserve_out / reserve_in` of the in token, rounded down.
+///
+/// Returns zero if the price does not fall.
+pub fn price_impact_bps(
+    reserve_in: Balance,
+    reserve_out: Balance,
+    new_reserve_in: Balance,
+    new_reserve_out: Balance,
+) -> Balance {
+    // 1 - (new_reserve_out / new_reserve_in) / (reserve_out / reserve_in)
+    let before = reserve_out * new_reserve_in;
+    let after = new_reserve_out * reserve_in;
+    before.saturating_sub(after) * 10_000 / before
+}
+
+/// Splits `amount_out` into the part sent to the recipient of a swap and the
+/// `referral_bps` basis points paid to its referrer.
+///
+/// The referral rounds down, so the parts add up to `amount_out`.
+pub fn referral_split(amount_out: Balance, referral_bps: u16) -> (Balance, Balance) {
+    let referral = amount_out * referral_bps as Balance / 10_000;
+    (amount_out - referral, referral)
+}
+
+/// The number of decimals of fixed-point prices and LP token values.
+pub const PRICE_DECIMALS: u8 = 18;
+
+/// Converts the price feed `answer` with `decimals` decimals to
+/// `PRICE_DECIMALS` decimals.
+///
+/// Returns `None` for negative answers or if the price does not fit.
+pub fn normalize_price(answer: i128, decimals: u8) -> Option<Balance> {
+    if answer < 0 {
+        return None
+    }
+    normalize_amount(answer as Balance, decimals)
+}
+
+/// Converts `amount` of a token with `decimals` decimals to `PRICE_DECIMALS`
+/// decimals, rounding down.
+///
+/// Returns `None` if the amount does not fit.
+pub fn normalize_amount(amount: Balance, decimals: u8) -> Option<Balance> {
+    if decimals > PRICE_DECIMALS {
+        Some(amount / 10u128.checked_pow((decimals - PRICE_DECIMALS) as u32)?)
+    } else {
+        amount.checked_mul(10u128.pow((PRICE_DECIMALS - decimals) as u32))
+    }
+}
+
+/// Returns `reserve_out * 10^precision / reserve_in`, the spot price of the
+/// input token in the output token with `precision` decimals, rounded down.
+///
+/// Returns `None` if `reserve_in` is zero or on overflow.
+pub fn fixed_price(
+    reserve_in: Balance,
+    reserve_out: Balance,
+    precision: u32,
+) -> Option<Balance> {
+    reserve_out
+        .checked_mul(10u128.checked_pow(precision)?)?
+        .checked_div(reserve_in)
+}
+
+/// Returns `(reserve0 * price0 + reserve1 * price1) / total_supply`, the value
+/// of one LP token for reserves, prices and LP tokens with `PRICE_DECIMALS`
+/// decimals.
+///
+/// Returns `None` if there is no liquidity or on overflow.
+pub fn lp_value(
+    reserve0: Balance,
+    price0: Balance,
+    reserve1: Balance,
+    price1: Balance,
+    total_supply: Balance,
+) -> Option<Balance> {
+    // Normalized reserves times prices easily exceed 128 bits, so scale each
+    // product down by the supply first.
+    mul_div(reserve0, price0, total_supply)?
+        .checked_add(mul_div(reserve1, price1, total_supply)?)
+}
+
+/// A fee growth per LP token in the Q128.128 fixed-point format, as its
+/// `(high, low)` halves.
+///
+/// The high half is the whole fee per LP token, the low half the fraction in
+/// units of `2^-128`.
+pub type FeeGrowth = (Balance, Balance);
+
+/// Returns the growth of the fee per LP token when `total_supply` LP tokens
+/// share `fee`, rounded down.
+///
+/// The growth is `fee * 2^128 / total_supply`, so less than one unit of the
+/// fee is lost to rounding for every supply that fits into a `Balance`.
+pub fn fee_growth(fee: Balance, total_supply: Balance) -> FeeGrowth {
+    match full_math::div_rem_wide((fee, 0), total_supply) {
+        Some((growth, _)) => growth,
+        None => (0, 0),
+    }
+}
+
+/// Returns `a + b`, wrapping around at `2^256`.
+pub fn wrapping_add_growth(
+    (a_hi, a_lo): FeeGrowth,
+    (b_hi, b_lo): FeeGrowth,
+) -> FeeGrowth {
+    let (lo, carry) = a_lo.overflowing_add(b_lo);
+    (a_hi.wrapping_add(b_hi).wrapping_add(carry as Balance), lo)
+}
+
+/// Returns `a - b`, wrapping around at `2^256`.
+pub fn wrapping_sub_growth(
+    (a_hi, a_lo): FeeGrowth,
+    (b_hi, b_lo): FeeGrowth,
+) -> FeeGrowth {
+    let (lo, borrow) = a_lo.overflowing_sub(b_lo);
+    (a_hi.wrapping_sub(b_hi).wrapping_sub(borrow as Balance), lo)
+}
+
+/// Returns the fees earned by `liquidity` LP tokens while the fee growth per LP
+/// token grew by `growth`, rounded down and saturating on overflow.
+pub fn fees_earned((hi, lo): FeeGrowth, liquidity: Balance) -> Balance {
+    // The fraction contributes the high half of its product.
+    hi.saturating_mul(liquidity).saturating_add(full_mul(lo, liquidity).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitive_types::U256;
+    use proptest::prelude::*;
+
+    /// The largest reserve or amount for which the products of three values in
+    /// `get_amount_out` and the checks below fit into a `Balance`.
+    const MAX: Balance = 1 << 48;
+
+    /// Reserves together with a total supply of at most `sqrt(reserve0 * reserve1)`,
+    /// which the first mint establishes and later mints preserve.
+    fn pool() -> impl Strategy<Value = (Balance, Balance, Balance)> {
+        (1..MAX, 1..MAX).prop_flat_map(|(reserve0, reserve1)| {
+            (Just(reserve0), Just(reserve1), 1..=sqrt(reserve0 * reserve1))
+        })
+    }
+
+    fn to_u256((hi, lo): (u128, u128)) -> U256 {
+        (U256::from(hi) << 128) | U256::from(lo)
+    }
+
+    #[test]
+    fn encode_reserves_packed_works() {
+        assert_eq!(encode_reserves_packed(0, 0), (0, 0));
+        assert_eq!(encode_reserves_packed(1, 1), (0, 1 | 1 << 112));
+        assert_eq!(encode_reserves_packed(0, 1 << 16), (1, 0));
+        assert_eq!(
+            encode_reserves_packed(RESERVE_MAX, RESERVE_MAX),
+            ((1 << 96) - 1, u128::MAX)
+        );
+        assert_eq!(
+            to_u256(encode_reserves_packed(RESERVE_MAX, 12_345)),
+            U256::from(RESERVE_MAX) | U256::from(12_345) << 112
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Uniswap: OVERFLOW")]
+    fn encode_reserves_packed_fails_above_max() {
+        encode_reserves_packed(1, RESERVE_MAX + 1);
+    }
+
+    #[test]
+    fn mint_rounds_down() {
+        // 10 * 1 / 3 = 3.33 and 7 * 1 / 3 = 2.33
+        assert_eq!(mint_liquidity(10, 7, 3, 3, 1), 2);
+        assert_eq!(mint_liquidity(10, 10, 3, 3, 1), 3);
+    }
+
+    #[test]
+    fn burn_rounds_down() {
+        // 1 * 10 / 3 = 3.33 and 1 * 5 / 3 = 1.67
+        assert_eq!(burn_amounts(1, 10, 5, 3), (3, 1));
+    }
+
+    #[test]
+    fn optimal_amounts_keep_the_ratio() {
+        // Token1 limits: 300 token1 only take 150 of 500 token0.
+        assert_eq!(optimal_amounts(500, 300, 1_000, 2_000), (150, 300));
+        // Token0 limits: 100 token0 take 200 of 500 token1.
+        assert_eq!(optimal_amounts(100, 500, 1_000, 2_000), (100, 200));
+        assert_eq!(optimal_amounts(1, 1, 3, 2), (1, 0));
+        assert_eq!(optimal_amounts(500, 300, 0, 0), (500, 300));
+    }
+
+    #[test]
+    fn zero_fee_round_trip_only_loses_rounding() {
+        let out = get_amount_out(10_000, 1_000_000, 1_000_000, 0);
+        // 10_000 * 1_000_000 / 1_010_000 = 9_900.99
+        assert_eq!(out, 9_900);
+        assert!(keeps_k(1_000_000, 1_000_000, 1_010_000, 990_100, 10_000, 0));
+        let back = get_amount_out(out, 990_100, 1_010_000, 0);
+        assert_eq!(back, 9_999);
+    }
+
+    #[test]
+    fn max_input_to_price_lands_on_target() {
+        // Moving the price of 2 down to 1.8.
+        let amount_in = max_input_to_price(1_000_000, 2_000_000, 9, 5, FEE_BPS);
+        assert_eq!(amount_in, 54_173);
+        let amount_out = get_amount_out(amount_in, 1_000_000, 2_000_000, FEE_BPS);
+        let (reserve_in, reserve_out) = (1_000_000 + amount_in, 2_000_000 - amount_out);
+        // 1_897_515 / 1_054_173 = 1.800003
+        assert!(reserve_out * 5 >= reserve_in * 9);
+        assert!(reserve_out * 5 * 100_000 < reserve_in * 9 * 100_001);
+        // Without a fee less input is needed.
+        assert_eq!(max_input_to_price(1_000_000, 2_000_000, 9, 5, 0), 54_092);
+        // The price is already past the target.
+        assert_eq!(max_input_to_price(1_000_000, 2_000_000, 2, 1, FEE_BPS), 0);
+        assert_eq!(max_input_to_price(1_000_000, 2_000_000, 3, 1, FEE_BPS), 0);
+    }
+
+    #[test]
+    fn price_impact_rounds_down() {
+        // 10 in for 9 out moves 1000:1000 to 1010:991, a drop of 1.88%.
+        assert_eq!(price_impact_bps(1_000, 1_000, 1_010, 991), 188);
+        assert_eq!(price_impact_bps(1_000, 1_000, 1_000, 1_000), 0);
+        // Prices that rise have no impact.
+        assert_eq!(price_impact_bps(1_000, 1_000, 991, 1_010), 0);
+    }
+
+    #[test]
+    fn fixed_price_works() {
+        assert_eq!(fixed_price(1_000_000, 2_500_000, 6), Some(2_500_000));
+        assert_eq!(fixed_price(3, 1, 6), Some(333_333));
+        assert_eq!(fixed_price(0, 1, 6), None);
+        assert_eq!(fixed_price(1, Balance::MAX, 1), None);
+    }
+
+    #[test]
+    fn fee_growth_works() {
+        // 400 / 4_096 is 25 / 256 exactly.
+        assert_eq!(fee_growth(400, 4_096), (0, 25 << 120));
+        assert_eq!(fee_growth(1, 3), (0, u128::MAX / 3));
+        assert_eq!(fee_growth(400, 0), (0, 0));
+        assert_eq!(fee_growth(Balance::MAX, 1), (Balance::MAX, 0));
+        assert_eq!(fee_growth(Balance::MAX, Balance::MAX), (1, 0));
+        assert_eq!(fees_earned((0, 25 << 120), 1_024), 100);
+        assert_eq!(fees_earned(fee_growth(1, 3), 3), 0);
+        assert_eq!(fees_earned((1, 0), Balance::MAX), Balance::MAX);
+        assert_eq!(fees_earned((2, 0), Balance::MAX), Balance::MAX);
+    }
+
+    #[test]
+    fn fee_growth_is_exact_for_large_supplies() {
+        // With a per-LP growth far below one unit of the fee, one holder of the
+        // whole supply still earns all but the rounding of the last unit.
+        let supply = 10u128.pow(30);
+        let growth = fee_growth(1_000_000, supply);
+        assert_ne!(growth, (0, 0));
+        assert_eq!(fees_earned(growth, supply), 999_999);
+        assert_eq!(fees_earned(growth, supply / 2), 499_999);
+        // Even one unit of fee over the largest supply is tracked.
+        assert_eq!(fee_growth(1, Balance::MAX), (0, 1));
+        assert_eq!(fees_earned((0, 1), Balance::MAX), 0);
+    }
+
+    #[test]
+    fn fee_growth_wraps_around() {
+        let max = (u128::MAX, u128::MAX);
+        assert_eq!(wrapping_add_growth(max, (0, 1)), (0, 0));
+        assert_eq!(wrapping_add_growth((0, u128::MAX), (0, 1)), (1, 0));
+        assert_eq!(wrapping_sub_growth((0, 0), max), (0, 1));
+        assert_eq!(wrapping_sub_growth((1, 0), (0, 1)), (0, u128::MAX));
+    }
+
+    #[test]
+    fn referral_split_works() {
+        // 0.5% of 19_743 is 98.7.
+        assert_eq!(referral_split(19_743, 50), (19_645, 98));
+        assert_eq!(referral_split(19_743, 0), (19_743, 0));
+        assert_eq!(referral_split(19_743, 10_000), (0, 19_743));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4_096))]
+
+        #[test]
+        fn swaps_never_violate_k(
+            reserve_in in 1..MAX,
+            reserve_out in 1..MAX,
+            amount_in in 1..MAX,
+        ) {
+            let amount_out = get_amount_out(amount_in, reserve_in, reserve_out, FEE_BPS);
+            prop_assert!(amount_out < reserve_out);
+            let balance_in = reserve_in + amount_in;
+            let balance_out = reserve_out - amount_out;
+            prop_assert!(
+                (balance_in * 1000 - amount_in * 3) * balance_out
+                    >= reserve_in * reserve_out * 1000
+            );
+        }
+
+        #[test]
+        fn swaps_keep_k_for_any_fee(
+            reserve_in in 1..MAX,
+            reserve_out in 1..MAX,
+            amount_in in 1..MAX,
+            fee_bps in 0..=MAX_FEE_BPS,
+        ) {
+            let amount_out = get_amount_out(amount_in, reserve_in, reserve_out, fee_bps);
+            prop_assert!(amount_out < reserve_out);
+            prop_assert!(keeps_k(
+                reserve_in,
+                reserve_out,
+                reserve_in + amount_in,
+                reserve_out - amount_out,
+                amount_in,
+                fee_bps,
+            ));
+        }
+
+        #[test]
+        fn amount_out_is_monotone_in_amount_in(
+            reserve_in in 1..MAX,
+            reserve_out in 1..MAX,
+            amount_in in 1..MAX / 2,
+            extra in 0..MAX / 2,
+        ) {
+            prop_assert!(
+                get_amount_out(amount_in, reserve_in, reserve_out, FEE_BPS)
+                    <= get_amount_out(amount_in + extra, reserve_in, reserve_out, FEE_BPS)
+            );
+        }
+
+        #[test]
+        fn mint_then_burn_never_returns_more(
+            (reserve0, reserve1, total_supply) in pool(),
+            amount0 in 0..MAX,
+            amount1 in 0..MAX,
+        ) {
+            let liquidity =
+                mint_liquidity(amount0, amount1, reserve0, reserve1, total_supply);
+            let (out0, out1) = burn_amounts(
+                liquidity,
+                reserve0 + amount0,
+                reserve1 + amount1,
+                total_supply + liquidity,
+            );
+            prop_assert!(out0 <= amount0 && out1 <= amount1);
+        }
+
+        #[test]
+        fn liquidity_for_amounts_is_the_fewest_covering_the_amounts(
+            (reserve0, reserve1, total_supply) in pool(),
+            amount0 in 0..MAX,
+            amount1 in 0..MAX,
+        ) {
+            let (amount0, amount1) = (amount0 % reserve0, amount1 % reserve1);
+            let liquidity =
+                liquidity_for_amounts(amount0, amount1, reserve0, reserve1, total_supply);
+            let (out0, out1) = burn_amounts(liquidity, reserve0, reserve1, total_supply);
+            prop_assert!(out0 >= amount0 && out1 >= amount1);
+            if liquidity > 0 {
+                let (less0, less1) =
+                    burn_amounts(liquidity - 1, reserve0, reserve1, total_supply);
+                prop_assert!(less0 < amount0 || less1 < amount1);
+            }
+        }
+
+        #[test]
+        fn repeated_mint_and_burn_never_return_more(
+            (reserve0, reserve1, total_supply) in pool(),
+            amount0 in 1..MAX,
+            amount1 in 1..MAX,
+            cycles in 1..16usize,
+        ) {
+            let (mut balance0, mut balance1, mut supply) =
+                (reserve0, reserve1, total_supply);
+            for _ in 0..cycles {
+                let liquidity =
+                    mint_liquidity(amount0, amount1, balance0, balance1, supply);
+                balance0 += amount0;
+                balance1 += amount1;
+                supply += liquidity;
+                let (out0, out1) = burn_amounts(liquidity, balance0, balance1, supply);
+                prop_assert!(out0 <= amount0 && out1 <= amount1);
+                balance0 -= out0;
+                balance1 -= out1;
+                supply -= liquidity;
+            }
+            // The residue of every cycle stayed with the existing LPs.
+            prop_assert!(balance0 >= reserve0 && balance1 >= reserve1);
+            prop_assert_eq!(supply, total_supply);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An off-chain stand-in for the ERC-20 tokens of the pair.
+//!
+//! Cross-contract calls are not supported by the off-chain environment, so the
+//! unit tests swap the `Erc20` reference of the pair for [`MockErc20`]. The
+//! balances of all mock tokens are kept per test thread and can be seeded with
+//! [`set_balance`]. Allowances are not tracked: `transfer_from` only checks the
+//! balance of `from`.
+
+use core::cell::RefCell;
+use erc20::erc20::{
+    Error,
+    Result,
+};
+use ink_env::{
+    call::FromAccountId,
+    AccountId,
+    DefaultEnvironment,
+};
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+use std::collections::BTreeMap;
+
+type Balance = <DefaultEnvironment as ink_env::Environment>::Balance;
+
+thread_local! {
+    /// Mapping from `(token, owner)` to the token balance of `owner`.
+    static BALANCES: RefCell<BTreeMap<(AccountId, AccountId), Balance>> =
+        RefCell::new(BTreeMap::new());
+}
+
+/// Removes the balances of all mock tokens.
+pub fn reset() {
+    BALANCES.with(|balances| balances.borrow_mut().clear());
+}
+
+/// Returns the `token` balance of `owner`.
+pub fn balance(token: AccountId, owner: AccountId) -> Balance {
+    BALANCES.with(|balances| {
+        balances
+            .borrow()
+            .get(&(token, owner))
+            .copied()
+            .unwrap_or(0)
+    })
+}
+
+/// Sets the `token` balance of `owner` to `value`.
+pub fn set_balance(token: AccountId, owner: AccountId, value: Balance) {
+    BALANCES.with(|balances| balances.borrow_mut().insert((token, owner), value));
+}
+
+/// A reference to a mock token at `account_id`.
+#[derive(
+    Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+)]
+pub struct MockErc20 {
+    account_id: AccountId,
+}
+
+impl FromAccountId<DefaultEnvironment> for MockErc20 {
+    fn from_account_id(account_id: AccountId) -> Self {
+        Self { account_id }
+    }
+}
+
+impl ink_lang::ToAccountId<DefaultEnvironment> for MockErc20 {
+    fn to_account_id(&self) -> AccountId {
+        self.account_id
+    }
+}
+
+impl MockErc20 {
+    /// Returns the balance of `owner`.
+    pub fn balance_of(&self, owner: AccountId) -> Balance {
+        balance(self.account_id, owner)
+    }
+
+    /// Transfers `value` from the calling contract to `to`.
+    pub fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
+        let from = ink_env::account_id::<DefaultEnvironment>()
+            .expect("Cannot get contract id");
+        self.transfer_from(from, to, value)
+    }
+
+    /// Transfers `value` from `from` to `to`.
+    ///
+    /// Returns `InsufficientBalance` if `from` holds less than `value`.
+    pub fn transfer_from(
+        &mut self,
+        from: AccountId,
+        to: AccountId,
+        value: Balance,
+    ) -> Result<()> {
+        let from_balance = self.balance_of(from);
+        if from_balance < value {
+            return Err(Error::InsufficientBalance)
+        }
+        set_balance(self.account_id, from, from_balance - value);
+        let to_balance = self.balance_of(to);
+        set_balance(self.account_id, to, to_balance + value);
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod xcm_oracle {
+    #[cfg(not(feature = "ink-as-dependency"))]
+    use ink_storage::collections::HashMap as StorageHashMap;
+
+    /// The chain extension function returning the parachain ID the currently
+    /// executed XCM `Transact` originated from, or `None` for local calls.
+    pub const XCM_ORIGIN_FUNC_ID: u32 = 0x0000_0101;
+
+    /// The fixed-point precision of the prices returned by `remote_spot_price`.
+    pub const PRICE_PRECISION: u128 = 1_000_000_000_000;
+
+    /// The XCM oracle error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the caller is not the owner.
+        NotOwner,
+        /// Returned if the call did not originate from an XCM `Transact`.
+        NotXcmOrigin,
+        /// Returned if the originating parachain is not whitelisted.
+        ParachainNotWhitelisted,
+        /// Returned if the report is not newer than the stored one.
+        StaleReport,
+    }
+
+    /// The XCM oracle result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Stores reserves of pairs living on other parachains so that local
+    /// contracts can share their price feed.
+    #[ink(storage)]
+    pub struct XcmOracle {
+        /// The account allowed to manage the parachain whitelist.
+        owner: AccountId,
+        /// The parachains allowed to report reserves.
+        whitelisted_parachains: StorageHashMap<u32, ()>,
+        /// Mapping from remote pair address to `(reserve0, reserve1, timestamp)`.
+        remote_reserves: StorageHashMap<[u8; 32], (Balance, Balance, u64)>,
+    }
+
+    /// Event emitted when a whitelisted parachain reports the reserves of a pair.
+    #[ink(event)]
+    pub struct ReservesReceived {
+        #[ink(topic)]
+        para_id: u32,
+        #[ink(topic)]
+        pair: [u8; 32],
+        reserve0: Balance,
+        reserve1: Balance,
+        timestamp: u64,
+    }
+
+    /// Event emitted when a parachain is added to or removed from the whitelist.
+    #[ink(event)]
+    pub struct ParachainUpdated {
+        #[ink(topic)]
+        para_id: u32,
+        allowed: bool,
+    }
+
+    impl XcmOracle {
+        /// Creates a new XCM oracle with an empty parachain whitelist.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                owner: Self::env().caller(),
+                whitelisted_parachains: StorageHashMap::new(),
+                remote_reserves: StorageHashMap::new(),
+            }
+        }
+
+        /// Stores the reserves of a remote `pair` as decoded from an XCM `Transact`.
+        ///
+        /// On success a `ReservesReceived` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// - Returns `NotXcmOrigin` if the call was not dispatched by XCM.
+        /// - Returns `ParachainNotWhitelisted` if the origin parachain is unknown.
+        /// - Returns `StaleReport` if a report with the same or a later timestamp
+        ///   has already been stored for `pair`.
+        #[ink(message)]
+        pub fn receive_reserves(
+            &mut self,
+            pair: [u8; 32],
+            reserve0: Balance,
+            reserve1: Balance,
+            timestamp: u64,
+        ) -> Result<()> {
+            let para_id = self.xcm_origin().ok_or(Error::NotXcmOrigin)?;
+            if !self.whitelisted_parachains.contains_key(&para_id) {
+                return Err(Error::ParachainNotWhitelisted)
+            }
+            if let Some(&(_, _, last)) = self.remote_reserves.get(&pair) {
+                if timestamp <= last {
+                    return Err(Error::StaleReport)
+                }
+            }
+            self.remote_reserves
+                .insert(pair, (reserve0, reserve1, timestamp));
+            self.env().emit_event(ReservesReceived {
+                para_id,
+                pair,
+                reserve0,
+                reserve1,
+                timestamp,
+            });
+            Ok(())
+        }
+
+        /// Allows `para_id` to report reserves.
+        #[ink(message)]
+        pub fn add_parachain(&mut self, para_id: u32) -> Result<()> {
+            self.ensure_owner()?;
+            self.whitelisted_parachains.insert(para_id, ());
+            self.env().emit_event(ParachainUpdated {
+                para_id,
+                allowed: true,
+            });
+            Ok(())
+        }
+
+        /// Disallows `para_id` to report reserves.
+        ///
+        /// Reserves it reported earlier are kept.
+        #[ink(message)]
+        pub fn remove_parachain(&mut self, para_id: u32) -> Result<()> {
+            self.ensure_owner()?;
+            self.whitelisted_parachains.take(&para_id);
+            self.env().emit_event(ParachainUpdated {
+                para_id,
+                allowed: false,
+            });
+            Ok(())
+        }
+
+        /// Returns `true` if `para_id` is allowed to report reserves.
+        #[ink(message)]
+        pub fn is_whitelisted(&self, para_id: u32) -> bool {
+            self.whitelisted_parachains.contains_key(&para_id)
+        }
+
+        /// Returns the last reported `(reserve0, reserve1, timestamp)` of `pair`.
+        #[ink(message)]
+        pub fn remote_reserves(&self, pair: [u8; 32]) -> Option<(Balance, Balance, u64)> {
+            self.remote_reserves.get(&pair).copied()
+        }
+
+        /// Returns the price of token0 denominated in token1 of the remote `pair`,
+        /// scaled by `PRICE_PRECISION`.
+        ///
+        /// Returns `None` if nothing was reported for `pair`, if its `reserve0` is
+        /// zero or if the scaled price does not fit into a `u128`.
+        #[ink(message)]
+        pub fn remote_spot_price(&self, pair: [u8; 32]) -> Option<u128> {
+            let &(reserve0, reserve1, _) = self.remote_reserves.get(&pair)?;
+            if reserve0 == 0 {
+                return None
+            }
+            reserve1
+                .checked_mul(PRICE_PRECISION)
+                .map(|scaled| scaled / reserve0)
+        }
+
+        /// Returns the parachain ID the current call originated from via XCM.
+        fn xcm_origin(&self) -> Option<u32> {
+            ink_env::call_chain_extension::<(), Option<u32>>(XCM_ORIGIN_FUNC_ID, &())
+                .ok()
+                .flatten()
+        }
+
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            Ok(())
+        }
+    }
+
+    /// Unit tests.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+
+        const PARA_ID: u32 = 2000;
+        const PAIR: [u8; 32] = [0x42; 32];
+
+        /// Emulates the runtime reporting `origin` as the XCM origin.
+        struct MockXcmOrigin(Option<u32>);
+
+        impl ink_env::test::ChainExtension for MockXcmOrigin {
+            type Input = ();
+            type Output = Option<u32>;
+
+            fn func_id(&self) -> u32 {
+                XCM_ORIGIN_FUNC_ID
+            }
+
+            fn call(&mut self, _input: &()) -> ink_env::Result<Option<u32>> {
+                Ok(self.0)
+            }
+        }
+
+        fn set_xcm_origin(origin: Option<u32>) {
+            ink_env::test::register_chain_extension(MockXcmOrigin(origin));
+        }
+
+        #[ink::test]
+        fn receive_reserves_works() {
+            let mut oracle = XcmOracle::new();
+            assert_eq!(oracle.add_parachain(PARA_ID), Ok(()));
+            set_xcm_origin(Some(PARA_ID));
+            assert_eq!(oracle.receive_reserves(PAIR, 1_000, 4_000, 1), Ok(()));
+            assert_eq!(oracle.remote_reserves(PAIR), Some((1_000, 4_000, 1)));
+            assert_eq!(oracle.remote_spot_price(PAIR), Some(4 * PRICE_PRECISION));
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+        }
+
+        #[ink::test]
+        fn local_calls_are_rejected() {
+            let mut oracle = XcmOracle::new();
+            assert_eq!(oracle.add_parachain(PARA_ID), Ok(()));
+            set_xcm_origin(None);
+            assert_eq!(
+                oracle.receive_reserves(PAIR, 1_000, 4_000, 1),
+                Err(Error::NotXcmOrigin)
+            );
+            assert_eq!(oracle.remote_spot_price(PAIR), None);
+        }
+
+        #[ink::test]
+        fn unknown_parachains_are_rejected() {
+            let mut oracle = XcmOracle::new();
+            assert_eq!(oracle.add_parachain(PARA_ID), Ok(()));
+            set_xcm_origin(Some(PARA_ID + 1));
+            assert_eq!(
+                oracle.receive_reserves(PAIR, 1_000, 4_000, 1),
+                Err(Error::ParachainNotWhitelisted)
+            );
+            assert_eq!(oracle.remove_parachain(PARA_ID), Ok(()));
+            set_xcm_origin(Some(PARA_ID));
+            assert_eq!(
+                oracle.receive_reserves(PAIR, 1_000, 4_000, 1),
+                Err(Error::ParachainNotWhitelisted)
+            );
+        }
+
+        #[ink::test]
+        fn stale_reports_are_rejected() {
+            let mut oracle = XcmOracle::new();
+            assert_eq!(oracle.add_parachain(PARA_ID), Ok(()));
+            set_xcm_origin(Some(PARA_ID));
+            assert_eq!(oracle.receive_reserves(PAIR, 1_000, 4_000, 5), Ok(()));
+            assert_eq!(
+                oracle.receive_reserves(PAIR, 1_000, 2_000, 5),
+                Err(Error::StaleReport)
+            );
+            assert_eq!(oracle.remote_reserves(PAIR), Some((1_000, 4_000, 5)));
+        }
+
+        #[ink::test]
+        fn spot_price_handles_empty_reserves() {
+            let mut oracle = XcmOracle::new();
+            assert_eq!(oracle.add_parachain(PARA_ID), Ok(()));
+            set_xcm_origin(Some(PARA_ID));
+            assert_eq!(oracle.receive_reserves(PAIR, 0, 4_000, 1), Ok(()));
+            assert_eq!(oracle.remote_spot_price(PAIR), None);
+        }
+    }
+}